@@ -0,0 +1,43 @@
+// Compare the cost of borrowing cell contents through each kind of
+// owner against a plain `&mut` reference.  `TCell` and `TLCell`
+// borrows have no runtime check, so should time the same as the
+// baseline, whereas `QCell` borrows compare owner IDs.  Run with
+// `cargo bench`.
+
+use qcell::{QCell, QCellOwner, TCell, TCellOwner, TLCell, TLCellOwner};
+use std::hint::black_box;
+use std::time::Instant;
+
+struct Marker;
+
+const ITERATIONS: u32 = 100_000_000;
+
+fn time(name: &str, mut f: impl FnMut()) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<12} {:>8.2} ns/iter",
+        name,
+        elapsed.as_nanos() as f64 / f64::from(ITERATIONS)
+    );
+}
+
+fn main() {
+    let mut value = 0u64;
+    time("baseline", || *black_box(&mut value) += 1);
+
+    let mut owner = TCellOwner::<Marker>::new();
+    let cell = TCell::<Marker, _>::new(0u64);
+    time("tcell", || *owner.rw(black_box(&cell)) += 1);
+
+    let mut owner = TLCellOwner::<Marker>::new();
+    let cell = TLCell::<Marker, _>::new(0u64);
+    time("tlcell", || *owner.rw(black_box(&cell)) += 1);
+
+    let mut owner = QCellOwner::new();
+    let cell = QCell::new(&owner, 0u64);
+    time("qcell", || *owner.rw(black_box(&cell)) += 1);
+}
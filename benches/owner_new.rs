@@ -0,0 +1,53 @@
+// Compare the cost of creating and dropping a `TCellOwner` through
+// the registry (`new`) and through a marker flag (`new_fast`), and
+// of creating and dropping a `TLCellOwner` through the thread-local
+// set (`new`) and through a marker flag (`new_fast`).  The set is
+// timed both with only a few marker types in use and with enough in
+// use to spill it out of its inline storage.  Run with `cargo bench`.
+
+use qcell::{tcell_marker, tlcell_marker, TCellOwner, TLCellOwner};
+use std::time::Instant;
+
+struct RegistryMarker;
+struct ThreadMarker<const N: usize>;
+tcell_marker!(
+    struct FlagMarker;
+);
+tlcell_marker!(
+    struct ThreadFlagMarker;
+);
+
+const ITERATIONS: u32 = 1_000_000;
+
+fn time(name: &str, mut f: impl FnMut()) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<12} {:>8.1} ns/iter",
+        name,
+        elapsed.as_nanos() as f64 / f64::from(ITERATIONS)
+    );
+}
+
+fn main() {
+    time("new", || drop(TCellOwner::<RegistryMarker>::new()));
+    time("new_fast", || drop(TCellOwner::<FlagMarker>::new_fast()));
+    time("tl_new", || drop(TLCellOwner::<ThreadMarker<0>>::new()));
+    time("tl_new_fast", || {
+        drop(TLCellOwner::<ThreadFlagMarker>::new_fast())
+    });
+    let _others = (
+        TLCellOwner::<ThreadMarker<1>>::new(),
+        TLCellOwner::<ThreadMarker<2>>::new(),
+        TLCellOwner::<ThreadMarker<3>>::new(),
+        TLCellOwner::<ThreadMarker<4>>::new(),
+        TLCellOwner::<ThreadMarker<5>>::new(),
+        TLCellOwner::<ThreadMarker<6>>::new(),
+        TLCellOwner::<ThreadMarker<7>>::new(),
+        TLCellOwner::<ThreadMarker<8>>::new(),
+    );
+    time("tl_spilled", || drop(TLCellOwner::<ThreadMarker<0>>::new()));
+}
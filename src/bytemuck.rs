@@ -0,0 +1,85 @@
+//! Optional `bytemuck` support, enabled with the `bytemuck` cargo
+//! feature.
+//!
+//! [`TCell`] and [`TLCell`] implement `Zeroable` when their contents
+//! do, so for example a large buffer of cells can be allocated with
+//! `bytemuck::zeroed_box`.  To cast the contents, borrow them through
+//! the owner first, and then use the usual `bytemuck` calls on the
+//! plain reference:
+//!
+//! ```
+//!# use qcell::{TCell, TCellOwner};
+//! struct Marker;
+//! let mut owner = TCellOwner::<Marker>::new();
+//! let buffer: Box<TCell<Marker, [f32; 4]>> = bytemuck::zeroed_box();
+//! owner.rw(&buffer)[1] = 1.0;
+//! let bytes: &[u8] = bytemuck::cast_slice(&owner.ro(&buffer)[..]);
+//! assert_eq!(&bytes[4..8], &1.0f32.to_ne_bytes());
+//! ```
+//!
+//! The cells deliberately don't implement `TransparentWrapper`, even
+//! though they are `repr(transparent)`.  That trait provides safe
+//! conversions between `&TCell<Q, T>` and `&T`, which would allow the
+//! contents to be read or modified without the owner.  For slices of
+//! cells, use [`TCellOwner::ro_slice`] instead, or borrow
+//! [`TCell::from_slice_of_cells`] through the owner, which both check
+//! the owner.  `Pod` can't be implemented
+//! either, since the cells have interior mutability.
+//!
+//! [`TCell`]: ../struct.TCell.html
+//! [`TLCell`]: ../struct.TLCell.html
+//! [`TCellOwner::ro_slice`]: ../struct.TCellOwner.html#method.ro_slice
+//! [`TCell::from_slice_of_cells`]: ../struct.TCell.html#method.from_slice_of_cells
+
+use crate::{TCell, TLCell};
+use ::bytemuck::Zeroable;
+
+// Safe because the cells are `repr(transparent)` over the contents,
+// so the all-zeroes bit pattern is valid for the cell exactly when it
+// is valid for the contents
+unsafe impl<Q, T: Zeroable> Zeroable for TCell<Q, T> {}
+unsafe impl<Q, T: Zeroable> Zeroable for TLCell<Q, T> {}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::{TCell, TCellOwner, TLCell, TLCellOwner};
+
+    #[test]
+    fn bytemuck_zeroed() {
+        struct Marker;
+        let owner = TCellOwner::<Marker>::new();
+        let cell: TCell<Marker, [u64; 8]> = bytemuck::Zeroable::zeroed();
+        assert_eq!(owner.ro(&cell), &[0; 8]);
+        let cells: Vec<TCell<Marker, u32>> = bytemuck::zeroed_vec(16);
+        assert_eq!(owner.ro_slice(&cells), &[0; 16]);
+
+        let owner = TLCellOwner::<Marker>::new();
+        let cell: TLCell<Marker, (u8, u16)> = bytemuck::Zeroable::zeroed();
+        assert_eq!(owner.ro(&cell), &(0, 0));
+    }
+
+    #[test]
+    fn bytemuck_cast() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let buffer: Box<TCell<Marker, [f32; 1024]>> = bytemuck::zeroed_box();
+        for (i, value) in owner.rw(&buffer).iter_mut().enumerate() {
+            *value = i as f32;
+        }
+        let bytes: Vec<u8> = bytemuck::cast_slice(&owner.ro(&buffer)[..]).to_vec();
+        assert_eq!(bytes.len(), 4096);
+        assert_eq!(&bytes[8..12], &2.0f32.to_ne_bytes());
+        bytemuck::cast_slice_mut::<f32, u8>(&mut owner.rw(&buffer)[..]).fill(0);
+        assert!(owner.ro(&buffer).iter().all(|v| *v == 0.0));
+        bytemuck::cast_slice_mut(&mut owner.rw(&buffer)[..]).copy_from_slice(&bytes);
+        assert_eq!(owner.ro(&buffer)[1023], 1023.0);
+
+        // Slices of cells go through the owner too
+        let cells: Vec<TCell<Marker, u16>> = (0..4).map(TCell::new).collect();
+        let words = owner.rw(TCell::from_slice_of_cells(&cells));
+        bytemuck::cast_slice_mut::<u16, u8>(words)[0] = 0xFF;
+        let words = owner.ro_slice(&cells);
+        assert_eq!(words[0], u16::from_ne_bytes([0xFF, 0]));
+        assert_eq!(bytemuck::cast_slice::<u16, u8>(words).len(), 8);
+    }
+}
@@ -8,7 +8,7 @@ fn main() {
     let c1 = Rc::new(QCell::new(&owner, 100u32));
     let c2 = Rc::new(QCell::new(&owner, 200u32));
     fn test(o: &mut QCellOwner) {}
-
+   
     let c1ref = owner.ro(&c1);
     test(&mut owner);    // Compile error
     println!("{}", *c1ref);
@@ -6,7 +6,7 @@ fn main() {
     use std::rc::Rc;
     let owner = QCellOwner::new();
     let cell = QCell::new(&owner, Rc::new(100));
-
+   
     // We aren't permitted to move the Rc to another thread
     std::thread::spawn(move || {    // Compile fail
         assert_eq!(100, **owner.ro(&cell));
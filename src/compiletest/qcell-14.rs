@@ -0,0 +1,8 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::QCell;
+    let mut value = 1u32;
+    let cell: &mut QCell<u32> = QCell::from_mut(&mut value);  // Compile fail
+}
@@ -0,0 +1,15 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{CellIterExt, TCell, TCellOwner};
+    use std::rc::Rc;
+    struct Marker;
+    type ACellOwner = TCellOwner<Marker>;
+    type ACell<T> = TCell<Marker, T>;
+    let mut owner = ACellOwner::new();
+    let cells = vec![Rc::new(ACell::new(1)), Rc::new(ACell::new(2))];
+    let mut iter = cells.iter().ro(&owner);
+    *owner.rw(&cells[0]) += 1;  // Compile fail
+    iter.next();
+}
@@ -0,0 +1,15 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{TCell, TCellOwner};
+    use std::rc::Rc;
+    struct Marker;
+    type ACellOwner = TCellOwner<Marker>;
+    type ACell<T> = TCell<Marker, T>;
+    let mut owner = ACellOwner::new();
+    let c1 = Rc::new(ACell::new(100u32));
+    let c1ref = owner.ro(&c1);
+    owner.set(&c1, 200);  // Compile fail
+    assert_eq!(*c1ref, 100);
+}
@@ -0,0 +1,12 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{TCell, TCellOwner};
+    struct MarkerA;
+    struct MarkerB;
+    let mut owner_a = TCellOwner::<MarkerA>::new();
+    let c1 = TCell::<MarkerA, u32>::new(1);
+    let c2 = TCell::<MarkerB, u32>::new(2);
+    owner_a.swap(&c1, &c2);  // Compile fail
+}
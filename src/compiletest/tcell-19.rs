@@ -0,0 +1,13 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{TCell, TCellOwner};
+    struct Marker;
+    let mut owner = TCellOwner::<Marker>::new();
+    let c1 = TCell::<Marker, u32>::new(1);
+    let c2 = TCell::<Marker, u32>::new(2);
+    let c1mutref = owner.rw(&c1);
+    let c2mutref = owner.rw(&c2);  // Compile error
+    *c1mutref += *c2mutref;
+}
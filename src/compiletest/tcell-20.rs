@@ -0,0 +1,12 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{TCell, TCellOwner};
+    struct Marker;
+    let mut owner = TCellOwner::<Marker>::new();
+    let c1 = TCell::<Marker, u32>::new(1);
+    let token = owner.read_token();
+    *owner.rw(&c1) += 1;  // Compile error
+    assert_eq!(*token.ro(&c1), 2);
+}
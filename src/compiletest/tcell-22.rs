@@ -0,0 +1,11 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{TCell, TCellOwner};
+    use std::cell::Cell;
+    struct Marker;
+    let owner = TCellOwner::<Marker>::new();
+    let c1 = TCell::<Marker, Cell<u32>>::new(Cell::new(1));
+    let _ = owner.read_token().ro(&c1);  // Compile error
+}
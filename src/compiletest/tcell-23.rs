@@ -0,0 +1,11 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{TCell, TCellOwner};
+    struct Marker;
+    let owner = TCellOwner::<Marker>::new();
+    let c1 = TCell::<Marker, u32>::new(1);
+    let frozen = owner.freeze();
+    *frozen.rw(&c1) += 1;  // Compile fail
+}
@@ -0,0 +1,11 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{cell_project, TCell};
+    struct Marker;
+    struct Inner { a: u32 }
+    struct Outer { inner: Box<Inner> }
+    let cell = TCell::<Marker, _>::new(Outer { inner: Box::new(Inner { a: 1 }) });
+    let a = cell_project!(&cell, Outer, inner.a);  // Compile fail
+}
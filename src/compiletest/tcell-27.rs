@@ -0,0 +1,10 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{cell_project, TCell};
+    struct Marker;
+    enum Outer { A { a: u32 } }
+    let cell = TCell::<Marker, _>::new(Outer::A { a: 1 });
+    let a = cell_project!(&cell, Outer, a);  // Compile fail
+}
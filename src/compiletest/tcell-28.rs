@@ -0,0 +1,10 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{cell_project, TCell};
+    struct Marker;
+    union Outer { a: u32, b: f32 }
+    let cell = TCell::<Marker, _>::new(Outer { a: 1 });
+    let b = cell_project!(&cell, Outer, b);  // Compile fail
+}
@@ -0,0 +1,11 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{cell_project, TCell};
+    struct Marker;
+    #[repr(packed)]
+    struct Outer { a: u8, b: u32 }
+    let cell = TCell::<Marker, _>::new(Outer { a: 1, b: 2 });
+    let b = cell_project!(&cell, Outer, b);  // Compile fail
+}
@@ -0,0 +1,11 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{cell_project, TCell};
+    struct Marker;
+    struct Outer { a: u32 }
+    struct Other { a: u32 }
+    let cell = TCell::<Marker, _>::new(Outer { a: 1 });
+    let a = cell_project!(&cell, Other, a);  // Compile fail
+}
@@ -0,0 +1,11 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{StaticTCell, TCellOwner};
+    use std::cell::Cell;
+    struct Marker;
+    static COUNT: StaticTCell<Marker, Cell<u32>> = StaticTCell::new(Cell::new(0));
+    let owner = TCellOwner::<Marker>::new();
+    let count = COUNT.ro(&owner);  // Compile fail
+}
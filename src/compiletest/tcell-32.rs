@@ -0,0 +1,9 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::TCell;
+    use std::cell::Cell;
+    struct Marker;
+    static COUNT: TCell<Marker, Cell<u32>> = TCell::new(Cell::new(0));  // Compile fail
+}
@@ -0,0 +1,10 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{TCell, TCellOwner};
+    struct Marker;
+    let owner = TCellOwner::<Marker>::new();
+    let cell = TCell::<Marker, u32>::new(1);
+    let _ = std::panic::catch_unwind(|| *owner.ro(&cell));  // Compile fail
+}
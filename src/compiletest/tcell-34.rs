@@ -0,0 +1,13 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{RcCellExt, TCell, TCellOwner};
+    use std::rc::Rc;
+    struct Marker;
+    let owner = TCellOwner::<Marker>::new();
+    let rc = Rc::new(TCell::<Marker, _>::new(String::from("text")));
+    let text = rc.ro(&owner);
+    drop(rc);  // Compile fail
+    assert_eq!(text, "text");
+}
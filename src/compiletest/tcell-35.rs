@@ -0,0 +1,12 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{RcCellExt, TCell, TCellOwner};
+    use std::rc::Rc;
+    struct Marker;
+    let owner = TCellOwner::<Marker>::new();
+    let rc = Rc::new(TCell::<Marker, _>::new(String::from("text")));
+    let text = rc.clone().ro(&owner);  // Compile fail
+    assert_eq!(text, "text");
+}
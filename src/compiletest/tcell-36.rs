@@ -0,0 +1,13 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{RcCellExt, TCell, TCellOwner};
+    use std::rc::Rc;
+    struct Marker;
+    let mut owner = TCellOwner::<Marker>::new();
+    let rc = Rc::new(TCell::<Marker, _>::new(1));
+    let a = rc.ro(&owner);
+    *rc.rw(&mut owner) += 1;  // Compile fail
+    assert_eq!(*a, 1);
+}
@@ -0,0 +1,12 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{rw_each_owner, TCell, TCellOwner};
+    struct Marker;
+    let mut owner = TCellOwner::<Marker>::new();
+    let c1 = TCell::<Marker, u32>::new(1);
+    let c2 = TCell::<Marker, u32>::new(2);
+    let (a, b) = rw_each_owner((&mut owner, &c1), (&mut owner, &c2));  // Compile fail
+    *a += *b;
+}
@@ -10,11 +10,11 @@ fn main() {
     struct MarkerB;
     type BCellOwner = TLCellOwner<MarkerB>;
     type BCell<T> = TLCell<MarkerB, T>;
-
+   
     let mut owner_a = ACellOwner::new();
     let mut owner_b = BCellOwner::new();
     let c1 = Rc::new(ACell::new(100u32));
-
+   
     let c1ref = owner_b.ro(&*c1);   // Compile error
     println!("{}", *c1ref);
 }
@@ -13,7 +13,7 @@ fn main() {
     let mut owner_a = ACellOwner::new();
     let mut owner_b = BCellOwner::new();
     let c1 = Rc::new(ACell::new(100u32));
-
+   
     let c1mutref = owner_b.rw(&*c1);    // Compile error
     println!("{}", *c1mutref);
 }
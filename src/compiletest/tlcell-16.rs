@@ -0,0 +1,8 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::TLCellOwner;
+    struct Marker;
+    let owner = TLCellOwner::<Marker>::wait_for_new();  // Compile fail
+}
@@ -0,0 +1,10 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::TLCell;
+    use std::rc::Rc;
+    struct Marker;
+    let cell = Rc::new(TLCell::<Marker, u32>::new(1));
+    *cell.get_mut() += 1;  // Compile fail
+}
@@ -0,0 +1,12 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{TLCell, TLCellOwner};
+    struct Marker;
+    let mut owner = TLCellOwner::<Marker>::new();
+    let c1 = TLCell::<Marker, u32>::new(1);
+    let adapter = owner.debug(&c1);
+    *owner.rw(&c1) += 1;  // Compile fail
+    println!("{:?}", adapter);
+}
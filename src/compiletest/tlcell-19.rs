@@ -0,0 +1,9 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::TLCell;
+    struct Marker;
+    fn is_send<T: Send + ?Sized>() {}
+    is_send::<TLCell<Marker, dyn ToString>>();  // Compile fail
+}
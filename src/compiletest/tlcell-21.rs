@@ -0,0 +1,14 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{TLCell, TLCellOwner};
+    struct Marker;
+    fn spawn(_: impl std::future::Future + Send) {}
+    spawn(async {
+        let mut owner = TLCellOwner::<Marker>::new();
+        let cell = TLCell::<Marker, u32>::new(1);
+        std::future::ready(()).await;
+        *owner.rw(&cell) += 1;  // Compile fail
+    });
+}
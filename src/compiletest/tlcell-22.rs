@@ -0,0 +1,12 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{TLCell, TLCellOwner};
+    struct MarkerA;
+    struct MarkerB;
+    let mut owner = TLCellOwner::<MarkerA>::new();
+    let c1 = TLCell::<MarkerA, u32>::new(1);
+    let c2 = TLCell::<MarkerB, u32>::new(2);
+    let (a, b) = owner.rw_n((&c1, &c2));  // Compile fail
+}
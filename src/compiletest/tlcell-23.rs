@@ -0,0 +1,13 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{TLCell, TLCellOwner};
+    struct Marker;
+    let mut owner = TLCellOwner::<Marker>::new();
+    let c1 = TLCell::<Marker, Vec<u32>>::new(vec![1]);
+    let c2 = TLCell::<Marker, u32>::new(2);
+    let list = owner.rw(&c1);
+    let extra = *owner.ro(&c2);  // Compile error
+    list.push(extra);
+}
@@ -0,0 +1,11 @@
+extern crate qcell;
+
+#[allow(warnings)]
+fn main() {
+    use qcell::{rw_each_owner, TLCell, TLCellOwner};
+    struct Marker;
+    let mut owner = TLCellOwner::<Marker>::new();
+    let c1 = TLCell::<Marker, u32>::new(1);
+    let (a, b) = rw_each_owner((&mut owner, &c1), (&mut owner, &c1));  // Compile fail
+    *a += *b;
+}
@@ -0,0 +1,95 @@
+//! Queries to help debug owner creation failures, such as a panic
+//! from [`TLCellOwner::new`] because an owner with the same marker
+//! type already exists in the thread.
+//!
+//! These calls only read the per-thread registry, never change it.
+//! The results describe the current thread at the moment of the call,
+//! and so are advisory: they are intended for logging and debugging,
+//! not for deciding whether to create an owner.  Use
+//! [`TLCellOwner::try_new`] for that.  To test for a particular
+//! marker type, use [`TLCellOwner::is_active`].
+//!
+//! [`TLCellOwner::new`]: ../struct.TLCellOwner.html#method.new
+//! [`TLCellOwner::try_new`]: ../struct.TLCellOwner.html#method.try_new
+//! [`TLCellOwner::is_active`]: ../struct.TLCellOwner.html#method.is_active
+
+/// Number of marker types that currently have a [`TLCellOwner`] in
+/// the current thread.  Owners created with
+/// [`TLCellOwner::new_unchecked`] are not registered, so are not
+/// counted.  Marker types that have been used with
+/// [`TLCellOwner::new_fast`] in this thread are always counted, since
+/// they stay reserved.
+///
+/// [`TLCellOwner`]: ../struct.TLCellOwner.html
+/// [`TLCellOwner::new_unchecked`]: ../struct.TLCellOwner.html#method.new_unchecked
+/// [`TLCellOwner::new_fast`]: ../struct.TLCellOwner.html#method.new_fast
+pub fn thread_owner_count() -> usize {
+    crate::tlcell::thread_owner_count()
+}
+
+/// Names of the marker types that currently have a [`TLCellOwner`]
+/// in the current thread, as given by `std::any::type_name`, in no
+/// particular order.  Requires the `debug` cargo feature, which
+/// stores the name alongside each registered marker type.
+///
+/// [`TLCellOwner`]: ../struct.TLCellOwner.html
+#[cfg(feature = "debug")]
+pub fn thread_owner_names() -> Vec<&'static str> {
+    crate::tlcell::thread_owner_names()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::thread_owner_count;
+    use crate::TLCellOwner;
+
+    // Run in a new thread, so that owners held by other tests, or by
+    // the test harness's choice of thread, can't affect the counts
+    fn in_thread(f: impl FnOnce() + Send + 'static) {
+        std::thread::spawn(f).join().unwrap();
+    }
+
+    #[test]
+    fn diagnostics_thread_owner_count() {
+        in_thread(|| {
+            struct Marker1;
+            struct Marker2;
+            struct Marker3;
+            assert_eq!(thread_owner_count(), 0);
+            let owner1 = TLCellOwner::<Marker1>::new();
+            let owner2 = TLCellOwner::<Marker2>::new();
+            assert_eq!(thread_owner_count(), 2);
+            // A failed attempt doesn't change the registry
+            assert!(TLCellOwner::<Marker1>::try_new().is_err());
+            assert_eq!(thread_owner_count(), 2);
+            // Nor does an unregistered owner, created here for a
+            // marker type with no other owner, as its contract requires
+            drop(unsafe { TLCellOwner::<Marker3>::new_unchecked() });
+            assert_eq!(thread_owner_count(), 2);
+            drop(owner1);
+            assert_eq!(thread_owner_count(), 1);
+            drop(owner2);
+            assert_eq!(thread_owner_count(), 0);
+        });
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn diagnostics_thread_owner_names() {
+        use super::thread_owner_names;
+        in_thread(|| {
+            struct Marker1;
+            struct Marker2<const N: usize>;
+            assert!(thread_owner_names().is_empty());
+            let _owner1 = TLCellOwner::<Marker1>::new();
+            let owner2 = TLCellOwner::<Marker2<7>>::new();
+            let mut names = thread_owner_names();
+            names.sort_unstable();
+            assert_eq!(names.len(), 2);
+            assert!(names[0].ends_with("Marker1"));
+            assert!(names[1].ends_with("Marker2<7>"));
+            drop(owner2);
+            assert_eq!(thread_owner_names().len(), 1);
+        });
+    }
+}
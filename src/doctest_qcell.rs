@@ -258,3 +258,12 @@
 //!     assert_eq!(100, **owner.ro(&cell));
 //! }).join();
 //! ```
+//!
+//! A plain `&mut T` can't be viewed as a `QCell`, since there's no
+//! owner ID to go with it:
+//!
+//! ```compile_fail
+//!# use qcell::QCell;
+//! let mut value = 1u32;
+//! let cell: &mut QCell<u32> = QCell::from_mut(&mut value);  // Compile fail
+//! ```
@@ -338,3 +338,337 @@
 //!     assert_eq!(100, **owner.ro(&cell));
 //! }).join();
 //! ```
+//!
+//! The `ro()` iterator adaptor keeps the owner borrowed immutably, so
+//! the owner can't be used mutably until the iterator is gone:
+//!
+//! ```
+//!# use qcell::{CellIterExt, TCell, TCellOwner};
+//!# use std::rc::Rc;
+//!# struct Marker;
+//!# type ACellOwner = TCellOwner<Marker>;
+//!# type ACell<T> = TCell<Marker, T>;
+//! let mut owner = ACellOwner::new();
+//! let cells = vec![Rc::new(ACell::new(1)), Rc::new(ACell::new(2))];
+//! let sum: i32 = cells.iter().ro(&owner).sum();
+//! *owner.rw(&cells[0]) += sum;
+//! assert_eq!(*owner.ro(&cells[0]), 4);
+//! ```
+//!
+//! ```compile_fail
+//!# use qcell::{CellIterExt, TCell, TCellOwner};
+//!# use std::rc::Rc;
+//!# struct Marker;
+//!# type ACellOwner = TCellOwner<Marker>;
+//!# type ACell<T> = TCell<Marker, T>;
+//! let mut owner = ACellOwner::new();
+//! let cells = vec![Rc::new(ACell::new(1)), Rc::new(ACell::new(2))];
+//! let mut iter = cells.iter().ro(&owner);
+//! *owner.rw(&cells[0]) += 1;  // Compile fail
+//! iter.next();
+//! ```
+//!
+//! Small `Copy` values can be read with `get()` and `Clone` values
+//! with `cloned()`, which works through an `Rc` too.  The returned
+//! values are independent of the owner, so don't block later `rw()`
+//! calls:
+//!
+//! ```
+//!# use qcell::{TCell, TCellOwner};
+//!# use std::rc::Rc;
+//!# struct Marker;
+//!# type ACellOwner = TCellOwner<Marker>;
+//!# type ACell<T> = TCell<Marker, T>;
+//! let mut owner = ACellOwner::new();
+//! let count = Rc::new(ACell::new(100u32));
+//! let name = Rc::new(ACell::new(String::from("abc")));
+//!
+//! let old_count = owner.get(&count);
+//! let old_name = owner.cloned(&name);
+//! *owner.rw(&count) += 1;
+//! owner.rw(&name).push('d');
+//! assert_eq!((old_count, old_name.as_str()), (100, "abc"));
+//! assert_eq!((owner.get(&count), owner.cloned(&name).as_str()), (101, "abcd"));
+//! ```
+//!
+//! `set()`, `replace()` and `take()` need mutable access to the
+//! owner, so an immutable borrow can't be held across them:
+//!
+//! ```compile_fail
+//!# use qcell::{TCell, TCellOwner};
+//!# use std::rc::Rc;
+//!# struct Marker;
+//!# type ACellOwner = TCellOwner<Marker>;
+//!# type ACell<T> = TCell<Marker, T>;
+//! let mut owner = ACellOwner::new();
+//! let c1 = Rc::new(ACell::new(100u32));
+//! let c1ref = owner.ro(&c1);
+//! owner.set(&c1, 200);  // Compile fail
+//! assert_eq!(*c1ref, 100);
+//! ```
+//!
+//! `swap()` can only exchange the contents of cells belonging to the
+//! same owner:
+//!
+//! ```compile_fail
+//!# use qcell::{TCell, TCellOwner};
+//! struct MarkerA;
+//! struct MarkerB;
+//! let mut owner_a = TCellOwner::<MarkerA>::new();
+//! let c1 = TCell::<MarkerA, u32>::new(1);
+//! let c2 = TCell::<MarkerB, u32>::new(2);
+//! owner_a.swap(&c1, &c2);  // Compile fail
+//! ```
+//!
+//! The borrow passed to an `update()` closure ends when the closure
+//! returns, but binding two `rw()` references like this fails:
+//!
+//! ```compile_fail
+//!# use qcell::{TCell, TCellOwner};
+//!# struct Marker;
+//! let mut owner = TCellOwner::<Marker>::new();
+//! let c1 = TCell::<Marker, u32>::new(1);
+//! let c2 = TCell::<Marker, u32>::new(2);
+//! let c1mutref = owner.rw(&c1);
+//! let c2mutref = owner.rw(&c2);  // Compile error
+//! *c1mutref += *c2mutref;
+//! ```
+//!
+//! Whereas the closure form works:
+//!
+//! ```
+//!# use qcell::{TCell, TCellOwner};
+//!# struct Marker;
+//! let mut owner = TCellOwner::<Marker>::new();
+//! let c1 = TCell::<Marker, u32>::new(1);
+//! let c2 = TCell::<Marker, u32>::new(2);
+//! owner.update(&c1, |v| *v += 1);
+//! owner.update(&c2, |v| *v += 1);
+//! assert_eq!(owner.get(&c1) + owner.get(&c2), 5);
+//! ```
+//!
+//! A `ReadToken` holds a shared borrow of the owner, so `rw()` is
+//! rejected whilst a token is alive:
+//!
+//! ```compile_fail
+//!# use qcell::{TCell, TCellOwner};
+//!# struct Marker;
+//! let mut owner = TCellOwner::<Marker>::new();
+//! let c1 = TCell::<Marker, u32>::new(1);
+//! let token = owner.read_token();
+//! *owner.rw(&c1) += 1;  // Compile error
+//! assert_eq!(*token.ro(&c1), 2);
+//! ```
+//!
+//! And a reference obtained through the token cannot outlive it:
+//!
+//! ```compile_fail
+//!# use qcell::{TCell, TCellOwner};
+//!# struct Marker;
+//! let mut owner = TCellOwner::<Marker>::new();
+//! let c1 = TCell::<Marker, u32>::new(1);
+//! let c1ref = owner.read_token().ro(&c1);
+//! *owner.rw(&c1) += 1;  // Compile error
+//! assert_eq!(*c1ref, 2);
+//! ```
+//!
+//! Contents that are not `Sync` cannot be read through a token:
+//!
+//! ```compile_fail
+//!# use qcell::{TCell, TCellOwner};
+//!# use std::cell::Cell;
+//!# struct Marker;
+//! let owner = TCellOwner::<Marker>::new();
+//! let c1 = TCell::<Marker, Cell<u32>>::new(Cell::new(1));
+//! let _ = owner.read_token().ro(&c1);  // Compile error
+//! ```
+//!
+//! The owner is `Send` even when the marker type isn't, since the
+//! marker is never instantiated:
+//!
+//! ```
+//!# use qcell::TCellOwner;
+//! struct Marker(std::rc::Rc<()>);
+//! fn is_send<T: Send>() {}
+//! is_send::<TCellOwner<Marker>>();
+//! ```
+//!
+//! A `FrozenTCellOwner` only gives read-only access:
+//!
+//! ```compile_fail
+//!# use qcell::{TCell, TCellOwner};
+//!# struct Marker;
+//! let owner = TCellOwner::<Marker>::new();
+//! let c1 = TCell::<Marker, u32>::new(1);
+//! let frozen = owner.freeze();
+//! *frozen.rw(&c1) += 1;  // Compile fail
+//! ```
+//!
+//! `get_mut()` needs exclusive access to the cell, so it can't be
+//! called through an `Rc`:
+//!
+//! ```compile_fail
+//!# use qcell::TCell;
+//!# use std::rc::Rc;
+//!# struct Marker;
+//! let cell = Rc::new(TCell::<Marker, u32>::new(1));
+//! *cell.get_mut() += 1;  // Compile fail
+//! ```
+//!
+//! The adapter returned by `debug()` keeps the owner borrowed:
+//!
+//! ```compile_fail
+//!# use qcell::{TCell, TCellOwner};
+//!# struct Marker;
+//! let mut owner = TCellOwner::<Marker>::new();
+//! let c1 = TCell::<Marker, u32>::new(1);
+//! let adapter = owner.debug(&c1);
+//! *owner.rw(&c1) += 1;  // Compile fail
+//! println!("{:?}", adapter);
+//! ```
+//!
+//! `cell_project!` can't go through a `Deref`, since the field would
+//! not be stored within the cell:
+//!
+//! ```compile_fail
+//!# use qcell::{cell_project, TCell};
+//!# struct Marker;
+//! struct Inner { a: u32 }
+//! struct Outer { inner: Box<Inner> }
+//! let cell = TCell::<Marker, _>::new(Outer { inner: Box::new(Inner { a: 1 }) });
+//! let a = cell_project!(&cell, Outer, inner.a);  // Compile fail
+//! ```
+//!
+//! `cell_project!` can't project into an enum:
+//!
+//! ```compile_fail
+//!# use qcell::{cell_project, TCell};
+//!# struct Marker;
+//! enum Outer { A { a: u32 } }
+//! let cell = TCell::<Marker, _>::new(Outer::A { a: 1 });
+//! let a = cell_project!(&cell, Outer, a);  // Compile fail
+//! ```
+//!
+//! `cell_project!` can't project a union field, since that would
+//! allow reading the contents as a different type:
+//!
+//! ```compile_fail
+//!# use qcell::{cell_project, TCell};
+//!# struct Marker;
+//! union Outer { a: u32, b: f32 }
+//! let cell = TCell::<Marker, _>::new(Outer { a: 1 });
+//! let b = cell_project!(&cell, Outer, b);  // Compile fail
+//! ```
+//!
+//! `cell_project!` can't project a field of a packed struct, since
+//! the field may not be aligned:
+//!
+//! ```compile_fail
+//!# use qcell::{cell_project, TCell};
+//!# struct Marker;
+//! #[repr(packed)]
+//! struct Outer { a: u8, b: u32 }
+//! let cell = TCell::<Marker, _>::new(Outer { a: 1, b: 2 });
+//! let b = cell_project!(&cell, Outer, b);  // Compile fail
+//! ```
+//!
+//! The struct type named in `cell_project!` must match the contents
+//! of the cell:
+//!
+//! ```compile_fail
+//!# use qcell::{cell_project, TCell};
+//!# struct Marker;
+//! struct Outer { a: u32 }
+//! struct Other { a: u32 }
+//! let cell = TCell::<Marker, _>::new(Outer { a: 1 });
+//! let a = cell_project!(&cell, Other, a);  // Compile fail
+//! ```
+//!
+//! A `StaticTCell` containing a type that isn't `Sync` can't be
+//! read through a shared owner, since the owner may be shared
+//! between threads:
+//!
+//! ```compile_fail
+//!# use qcell::{StaticTCell, TCellOwner};
+//!# use std::cell::Cell;
+//!# struct Marker;
+//! static COUNT: StaticTCell<Marker, Cell<u32>> = StaticTCell::new(Cell::new(0));
+//! let owner = TCellOwner::<Marker>::new();
+//! let count = COUNT.ro(&owner);  // Compile fail
+//! ```
+//!
+//! A `TCell` containing a type that isn't `Sync` can't be stored in
+//! a `static`:
+//!
+//! ```compile_fail
+//!# use qcell::TCell;
+//!# use std::cell::Cell;
+//!# struct Marker;
+//! static COUNT: TCell<Marker, Cell<u32>> = TCell::new(Cell::new(0));  // Compile fail
+//! ```
+//!
+//! A shared `TCell` is not `RefUnwindSafe`, as for `RefCell`, so
+//! `catch_unwind` needs `AssertUnwindSafe` when borrowing one:
+//!
+//! ```compile_fail
+//!# use qcell::{TCell, TCellOwner};
+//!# struct Marker;
+//! let owner = TCellOwner::<Marker>::new();
+//! let cell = TCell::<Marker, u32>::new(1);
+//! let _ = std::panic::catch_unwind(|| *owner.ro(&cell));  // Compile fail
+//! ```
+//!
+//! A reference obtained through `RcCellExt` borrows the `Rc`, so the
+//! `Rc` can't be dropped whilst the reference is in use:
+//!
+//! ```compile_fail
+//!# use qcell::{RcCellExt, TCell, TCellOwner};
+//!# use std::rc::Rc;
+//!# struct Marker;
+//! let owner = TCellOwner::<Marker>::new();
+//! let rc = Rc::new(TCell::<Marker, _>::new(String::from("text")));
+//! let text = rc.ro(&owner);
+//! drop(rc);  // Compile fail
+//! assert_eq!(text, "text");
+//! ```
+//!
+//! Nor can a reference be kept from a temporary `Rc`, for example a
+//! clone made just for the call:
+//!
+//! ```compile_fail
+//!# use qcell::{RcCellExt, TCell, TCellOwner};
+//!# use std::rc::Rc;
+//!# struct Marker;
+//! let owner = TCellOwner::<Marker>::new();
+//! let rc = Rc::new(TCell::<Marker, _>::new(String::from("text")));
+//! let text = rc.clone().ro(&owner);  // Compile fail
+//! assert_eq!(text, "text");
+//! ```
+//!
+//! `rw` through `RcCellExt` still needs the owner mutably, so can't
+//! overlap with another borrow:
+//!
+//! ```compile_fail
+//!# use qcell::{RcCellExt, TCell, TCellOwner};
+//!# use std::rc::Rc;
+//!# struct Marker;
+//! let mut owner = TCellOwner::<Marker>::new();
+//! let rc = Rc::new(TCell::<Marker, _>::new(1));
+//! let a = rc.ro(&owner);
+//! *rc.rw(&mut owner) += 1;  // Compile fail
+//! assert_eq!(*a, 1);
+//! ```
+//!
+//! `rw_each_owner` can't be used to borrow two cells from the same
+//! owner, since the owner would be borrowed mutably twice.  Use `rw2`
+//! for that:
+//!
+//! ```compile_fail
+//!# use qcell::{rw_each_owner, TCell, TCellOwner};
+//!# struct Marker;
+//! let mut owner = TCellOwner::<Marker>::new();
+//! let c1 = TCell::<Marker, u32>::new(1);
+//! let c2 = TCell::<Marker, u32>::new(2);
+//! let (a, b) = rw_each_owner((&mut owner, &c1), (&mut owner, &c2));  // Compile fail
+//! *a += *b;
+//! ```
@@ -325,3 +325,205 @@
 //!     assert_eq!(100, **owner.ro(&cell));
 //! }).join();
 //! ```
+//!
+//! Unlike `TCellOwner`, there is no blocking `wait_for_new()` on
+//! `TLCellOwner`, because the only owner it could wait for would be
+//! in the same thread, so it would deadlock:
+//!
+//! ```compile_fail
+//!# use qcell::TLCellOwner;
+//!# struct Marker;
+//! let owner = TLCellOwner::<Marker>::wait_for_new();  // Compile fail
+//! ```
+//!
+//! `get_mut()` needs exclusive access to the cell, so it can't be
+//! called through an `Rc`:
+//!
+//! ```compile_fail
+//!# use qcell::TLCell;
+//!# use std::rc::Rc;
+//!# struct Marker;
+//! let cell = Rc::new(TLCell::<Marker, u32>::new(1));
+//! *cell.get_mut() += 1;  // Compile fail
+//! ```
+//!
+//! The adapter returned by `debug()` keeps the owner borrowed:
+//!
+//! ```compile_fail
+//!# use qcell::{TLCell, TLCellOwner};
+//!# struct Marker;
+//! let mut owner = TLCellOwner::<Marker>::new();
+//! let c1 = TLCell::<Marker, u32>::new(1);
+//! let adapter = owner.debug(&c1);
+//! *owner.rw(&c1) += 1;  // Compile fail
+//! println!("{:?}", adapter);
+//! ```
+//!
+//! Using `rw2()` on the same cell twice panics at runtime:
+//!
+//! ```should_panic
+//!# use qcell::{TLCell, TLCellOwner};
+//!# use std::rc::Rc;
+//!# struct Marker;
+//!# type ACellOwner = TLCellOwner<Marker>;
+//!# type ACell<T> = TLCell<Marker, T>;
+//! let mut owner = ACellOwner::new();
+//! let c1 = Rc::new(ACell::new(100u32));
+//! let c1b = c1.clone();
+//!
+//! let (r1, r2) = owner.rw2(&c1, &c1b);  // Panics here
+//! ```
+//!
+//! And likewise for `rw_n()`, which also checks every pair:
+//!
+//! ```should_panic
+//!# use qcell::{TLCell, TLCellOwner};
+//!# struct Marker;
+//! let mut owner = TLCellOwner::<Marker>::new();
+//! let c1 = TLCell::<Marker, u32>::new(1);
+//! let c2 = TLCell::<Marker, u32>::new(2);
+//! let c3 = TLCell::<Marker, u32>::new(3);
+//! let _ = owner.rw_n((&c1, &c2, &c3, &c2));  // Panics here
+//! ```
+//!
+//! Unlike `TCellOwner`, the singleton check is per-thread, so owners
+//! with the same marker type can exist at the same time in different
+//! threads:
+//!
+//! ```
+//!# use qcell::TLCellOwner;
+//!# struct Marker;
+//! let owner1 = TLCellOwner::<Marker>::new();
+//! std::thread::spawn(|| {
+//!     let owner2 = TLCellOwner::<Marker>::new();  // Okay
+//! }).join().unwrap();
+//! ```
+//!
+//! As for `TCell`, the contents may be unsized, for example a slice
+//! or a trait object:
+//!
+//! ```
+//!# use qcell::{TLCell, TLCellOwner};
+//!# struct Marker;
+//! let mut owner = TLCellOwner::<Marker>::new();
+//! let c1: Box<TLCell<Marker, [u32]>> = Box::new(TLCell::new([1, 2, 3]));
+//! let c2: Box<TLCell<Marker, dyn ToString>> = Box::new(TLCell::new(4u32));
+//! owner.rw(&*c1)[0] += 10;
+//! assert_eq!(owner.ro(&*c1), [11, 2, 3]);
+//! assert_eq!(owner.ro(&*c2).to_string(), "4");
+//! ```
+//!
+//! An unsized `TLCell` is only `Send` if its contents are:
+//!
+//! ```compile_fail
+//!# use qcell::TLCell;
+//!# struct Marker;
+//! fn is_send<T: Send + ?Sized>() {}
+//! is_send::<TLCell<Marker, dyn ToString>>();  // Compile fail
+//! ```
+//!
+//! `new_unchecked()` skips the per-thread singleton check, so it is
+//! `unsafe` to call:
+//!
+//! ```compile_fail
+//!# use qcell::TLCellOwner;
+//!# struct Marker;
+//! let owner = TLCellOwner::<Marker>::new_unchecked();  // Compile fail
+//! ```
+//!
+//! However an owner created that way is not registered, so dropping
+//! it leaves the registered owners untouched:
+//!
+//! ```should_panic
+//!# use qcell::TLCellOwner;
+//!# struct Marker1;
+//!# struct Marker2;
+//! let owner1 = TLCellOwner::<Marker1>::new();
+//! // Sound, since no other `Marker2` owner exists
+//! drop(unsafe { TLCellOwner::<Marker2>::new_unchecked() });
+//! let owner2 = TLCellOwner::<Marker1>::new();  // Panics here
+//! ```
+//!
+//! A future that holds a `TLCellOwner` across an `.await` is not
+//! `Send`, so can't be run on a multi-threaded executor, where it
+//! might resume on another thread:
+//!
+//! ```compile_fail
+//!# use qcell::{TLCell, TLCellOwner};
+//!# struct Marker;
+//! fn spawn(_: impl std::future::Future + Send) {}
+//! spawn(async {
+//!     let mut owner = TLCellOwner::<Marker>::new();
+//!     let cell = TLCell::<Marker, u32>::new(1);
+//!     std::future::ready(()).await;
+//!     *owner.rw(&cell) += 1;  // Compile fail
+//! });
+//! ```
+//!
+//! Creating the owner for each synchronous section and keeping only
+//! the cells across the `.await` gives a `Send` future:
+//!
+//! ```
+//!# use qcell::{TLCell, TLCellOwner};
+//!# struct Marker;
+//! fn spawn(_: impl std::future::Future + Send) {}
+//! spawn(async {
+//!     let cell = TLCell::<Marker, u32>::new(1);
+//!     std::future::ready(()).await;
+//!     TLCellOwner::<Marker>::scope(|owner| *owner.rw(&cell) += 1);
+//! });
+//! ```
+//!
+//! All the cells passed to `rw_n` must belong to the owner's marker
+//! type:
+//!
+//! ```compile_fail
+//!# use qcell::{TLCell, TLCellOwner};
+//!# struct MarkerA;
+//!# struct MarkerB;
+//! let mut owner = TLCellOwner::<MarkerA>::new();
+//! let c1 = TLCell::<MarkerA, u32>::new(1);
+//! let c2 = TLCell::<MarkerB, u32>::new(2);
+//! let (a, b) = owner.rw_n((&c1, &c2));  // Compile fail
+//! ```
+//!
+//! The borrow passed to an `update()` closure ends when the closure
+//! returns, but holding an `rw()` reference while borrowing another
+//! cell fails:
+//!
+//! ```compile_fail
+//!# use qcell::{TLCell, TLCellOwner};
+//!# struct Marker;
+//! let mut owner = TLCellOwner::<Marker>::new();
+//! let c1 = TLCell::<Marker, Vec<u32>>::new(vec![1]);
+//! let c2 = TLCell::<Marker, u32>::new(2);
+//! let list = owner.rw(&c1);
+//! let extra = *owner.ro(&c2);  // Compile error
+//! list.push(extra);
+//! ```
+//!
+//! Whereas the closure forms work:
+//!
+//! ```
+//!# use qcell::{TLCell, TLCellOwner};
+//!# struct Marker;
+//! let mut owner = TLCellOwner::<Marker>::new();
+//! let c1 = TLCell::<Marker, Vec<u32>>::new(vec![1]);
+//! let c2 = TLCell::<Marker, u32>::new(2);
+//! let extra = owner.read(&c2, |v| *v);
+//! owner.update(&c1, |list| list.push(extra));
+//! owner.update2(&c1, &c2, |list, v| list.push(*v * 10));
+//! assert_eq!(owner.ro(&c1), &[1, 2, 20]);
+//! ```
+//!
+//! Nor can `rw_each_owner` borrow the same `TLCell` twice, since the
+//! owner would be borrowed mutably twice:
+//!
+//! ```compile_fail
+//!# use qcell::{rw_each_owner, TLCell, TLCellOwner};
+//!# struct Marker;
+//! let mut owner = TLCellOwner::<Marker>::new();
+//! let c1 = TLCell::<Marker, u32>::new(1);
+//! let (a, b) = rw_each_owner((&mut owner, &c1), (&mut owner, &c1));  // Compile fail
+//! *a += *b;
+//! ```
@@ -0,0 +1,150 @@
+// Compile-time checks of the auto traits and layout of each public
+// cell and owner type.  These pin the matrix documented on each type,
+// so that a change to a field or an `impl` that alters it fails the
+// build.  A marker type that implements none of the auto traits is
+// used alongside a plain one, to show that the marker never matters.
+
+use crate::{
+    FrozenTCellOwner, LCell, LCellOwner, QCell, QCellOwner, StaticTCell, TCell, TCellCopy,
+    TCellOwner, TLCell, TLCellOwner,
+};
+use std::any::Any;
+use std::cell::Cell;
+use std::marker::{PhantomData, PhantomPinned};
+use std::mem::{align_of, size_of};
+use std::panic::{RefUnwindSafe, UnwindSafe};
+use std::rc::Rc;
+use std::sync::MutexGuard;
+
+// Evaluate to whether a type implements a trait, using the fact that
+// an inherent associated const takes priority over a trait one, but
+// only applies when its bounds are met
+macro_rules! implements {
+    ($t:ty: $trait:path) => {{
+        struct Probe<T: ?Sized>(PhantomData<T>);
+        #[allow(dead_code)]
+        trait Fallback {
+            const IMPLS: bool = false;
+        }
+        impl<T: ?Sized> Fallback for Probe<T> {}
+        #[allow(dead_code)]
+        impl<T: ?Sized + $trait> Probe<T> {
+            const IMPLS: bool = true;
+        }
+        Probe::<$t>::IMPLS
+    }};
+}
+
+// Assert the full auto trait matrix for a type, in the order `Send`,
+// `Sync`, `Unpin`, `UnwindSafe`, `RefUnwindSafe`
+#[rustfmt::skip]
+macro_rules! auto_traits {
+    ($t:ty: $send:expr, $sync:expr, $unpin:expr, $uw:expr, $ruw:expr) => {
+        const _: () = {
+            assert!(implements!($t: Send) == $send, "Send");
+            assert!(implements!($t: Sync) == $sync, "Sync");
+            assert!(implements!($t: Unpin) == $unpin, "Unpin");
+            assert!(implements!($t: UnwindSafe) == $uw, "UnwindSafe");
+            assert!(implements!($t: RefUnwindSafe) == $ruw, "RefUnwindSafe");
+        };
+    };
+}
+
+// Assert that a cell type has the given size and alignment
+macro_rules! layout {
+    ($t:ty: $size:expr, $align:expr) => {
+        const _: () = {
+            assert!(size_of::<$t>() == $size, "size");
+            assert!(align_of::<$t>() == $align, "align");
+        };
+    };
+}
+
+struct Marker;
+
+// Implements none of the auto traits
+#[allow(dead_code)]
+struct Hostile(Rc<Cell<()>>, PhantomPinned);
+
+// Representative contents
+type Plain = u32;
+type NotSync = Cell<u32>;
+type NotSend = Rc<u32>;
+type Pinned = PhantomPinned;
+type NotUnwindSafe = &'static mut u32;
+type SyncNotSend = MutexGuard<'static, u32>;
+
+#[allow(dead_code)]
+#[repr(align(64))]
+struct Aligned(u8);
+
+// Owners: the marker type never affects the auto traits
+auto_traits!(TCellOwner<Marker>: true, true, true, true, true);
+auto_traits!(TCellOwner<Hostile>: true, true, true, true, true);
+auto_traits!(FrozenTCellOwner<Hostile>: true, true, true, true, true);
+#[cfg(feature = "async")]
+auto_traits!(crate::TCellOwnerFuture<Hostile>: true, true, true, true, true);
+auto_traits!(TLCellOwner<Marker>: false, false, true, true, true);
+auto_traits!(TLCellOwner<Hostile>: false, false, true, true, true);
+auto_traits!(QCellOwner: true, true, true, true, true);
+auto_traits!(LCellOwner<'static>: true, true, true, true, true);
+
+// `TCell`: `Send` follows the contents, `Sync` needs `Send + Sync`
+// contents, and it is never `RefUnwindSafe`, as for `RefCell`
+auto_traits!(TCell<Marker, Plain>: true, true, true, true, false);
+auto_traits!(TCell<Hostile, Plain>: true, true, true, true, false);
+auto_traits!(TCell<Marker, NotSync>: true, false, true, true, false);
+auto_traits!(TCell<Marker, NotSend>: false, false, true, true, false);
+auto_traits!(TCell<Marker, Pinned>: true, true, false, true, false);
+auto_traits!(TCell<Marker, NotUnwindSafe>: true, true, true, false, false);
+auto_traits!(TCell<Marker, SyncNotSend>: false, false, true, true, false);
+auto_traits!(TCell<Marker, [Plain]>: true, true, true, true, false);
+
+// `StaticTCell` is always accessed through an owner, so it is `Sync`
+// whenever the contents are `Send`.  `TCellCopy` is never `Sync`.
+auto_traits!(StaticTCell<Hostile, NotSync>: true, true, true, true, false);
+auto_traits!(StaticTCell<Marker, NotSend>: false, false, true, true, false);
+auto_traits!(TCellCopy<Marker, Plain>: true, false, true, true, false);
+
+// `TLCell`: as `TCell`, but never `Sync`
+auto_traits!(TLCell<Marker, Plain>: true, false, true, true, false);
+auto_traits!(TLCell<Hostile, Plain>: true, false, true, true, false);
+auto_traits!(TLCell<Marker, NotSync>: true, false, true, true, false);
+auto_traits!(TLCell<Marker, NotSend>: false, false, true, true, false);
+auto_traits!(TLCell<Marker, Pinned>: true, false, false, true, false);
+auto_traits!(TLCell<Marker, NotUnwindSafe>: true, false, true, false, false);
+auto_traits!(TLCell<Marker, [Plain]>: true, false, true, true, false);
+auto_traits!(TLCell<Marker, dyn Any>: false, false, false, false, false);
+auto_traits!(TLCell<Marker, dyn Any + Send>: true, false, false, false, false);
+
+// `QCell` and `LCell`: as `TCell`
+auto_traits!(QCell<Plain>: true, true, true, true, false);
+auto_traits!(QCell<NotSync>: true, false, true, true, false);
+auto_traits!(QCell<NotSend>: false, false, true, true, false);
+auto_traits!(QCell<Pinned>: true, true, false, true, false);
+auto_traits!(QCell<NotUnwindSafe>: true, true, true, false, false);
+auto_traits!(LCell<'static, Plain>: true, true, true, true, false);
+auto_traits!(LCell<'static, NotSync>: true, false, true, true, false);
+auto_traits!(LCell<'static, NotSend>: false, false, true, true, false);
+auto_traits!(LCell<'static, Pinned>: true, true, false, true, false);
+auto_traits!(LCell<'static, NotUnwindSafe>: true, true, true, false, false);
+
+// `TCell`, `TLCell` and `LCell` have exactly the layout of their
+// contents.  `QCell` adds the owner ID, and since it isn't
+// `repr(C)`, only the case with no padding is pinned.
+layout!(TCell<Hostile, ()>: 0, 1);
+layout!(TCell<Marker, u8>: 1, 1);
+layout!(TCell<Marker, u64>: 8, align_of::<u64>());
+layout!(TCell<Marker, [u16; 3]>: 6, 2);
+layout!(TLCell<Hostile, ()>: 0, 1);
+layout!(TLCell<Marker, u8>: 1, 1);
+layout!(TLCell<Marker, u64>: 8, align_of::<u64>());
+layout!(TLCell<Marker, [u32; 0]>: 0, 4);
+layout!(TLCell<Marker, Aligned>: 64, 64);
+layout!(LCell<'static, ()>: 0, 1);
+layout!(LCell<'static, u64>: 8, align_of::<u64>());
+layout!(QCell<u32>: 8, 4);
+
+// `LCellOwner` carries no state, and `QCellOwner` only its ID
+layout!(LCellOwner<'static>: 0, 1);
+layout!(QCellOwner: 4, 4);
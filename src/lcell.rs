@@ -1,5 +1,9 @@
-use std::cell::{Cell, UnsafeCell};
-use std::marker::PhantomData;
+use core::cell::{Cell, UnsafeCell};
+use core::fmt;
+use core::marker::PhantomData;
+use core::panic::{RefUnwindSafe, UnwindSafe};
+
+use crate::{GuardedCellOwner, GuardedCellReader};
 
 type Id<'id> = PhantomData<Cell<&'id mut ()>>;
 
@@ -38,6 +42,9 @@ type Id<'id> = PhantomData<Cell<&'id mut ()>>;
 /// community sources that predate `GhostCell`.  Later `Send` and
 /// `Sync` support for `LCell` was contributed independently.
 ///
+/// Auto traits: `Send`, `Sync`, `Unpin`, `UnwindSafe` and
+/// `RefUnwindSafe`.  The owner is zero-sized.
+///
 /// See also [crate documentation](index.html).
 pub struct LCellOwner<'id> {
     _id: Id<'id>,
@@ -80,6 +87,31 @@ impl<'id> LCellOwner<'id> {
         unsafe { &mut *lc.value.get() }
     }
 
+    /// Replace the contents of `dst` with a clone of the contents of
+    /// `src`, using `clone_from` so that existing allocations in
+    /// `dst` may be reused.  If both references are to the same
+    /// `LCell`, this does nothing.  Panics if the two cells otherwise
+    /// overlap.
+    #[inline]
+    pub fn clone_into<T: Clone>(&mut self, src: &LCell<'id, T>, dst: &LCell<'id, T>) {
+        if !core::ptr::eq(src, dst) {
+            let (src, dst) = self.rw2(src, dst);
+            dst.clone_from(src);
+        }
+    }
+
+    /// Move the contents of `src` into `dst`, leaving
+    /// `Default::default()` in `src`.  If both references are to the
+    /// same `LCell`, this does nothing, rather than resetting the
+    /// contents.  Panics if the two cells otherwise overlap.
+    #[inline]
+    pub fn move_into<T: Default>(&mut self, src: &LCell<'id, T>, dst: &LCell<'id, T>) {
+        if !core::ptr::eq(src, dst) {
+            let (src, dst) = self.rw2(src, dst);
+            *dst = core::mem::take(src);
+        }
+    }
+
     /// Borrow contents of two `LCell` instances mutably.  Panics if
     /// the two `LCell` instances point to the same memory.
     #[inline]
@@ -89,7 +121,7 @@ impl<'id> LCellOwner<'id> {
         lc2: &'a LCell<'id, U>,
     ) -> (&'a mut T, &'a mut U) {
         assert!(
-            lc1 as *const _ as usize != lc2 as *const _ as usize,
+            !lc1.overlaps(lc2),
             "Illegal to borrow same LCell twice with rw2()"
         );
         unsafe { (&mut *lc1.value.get(), &mut *lc2.value.get()) }
@@ -105,9 +137,7 @@ impl<'id> LCellOwner<'id> {
         lc3: &'a LCell<'id, V>,
     ) -> (&'a mut T, &'a mut U, &'a mut V) {
         assert!(
-            (lc1 as *const _ as usize != lc2 as *const _ as usize)
-                && (lc2 as *const _ as usize != lc3 as *const _ as usize)
-                && (lc3 as *const _ as usize != lc1 as *const _ as usize),
+            !lc1.overlaps(lc2) && !lc2.overlaps(lc3) && !lc3.overlaps(lc1),
             "Illegal to borrow same LCell twice with rw3()"
         );
         unsafe {
@@ -120,6 +150,40 @@ impl<'id> LCellOwner<'id> {
     }
 }
 
+impl<'id> GuardedCellReader for LCellOwner<'id> {
+    type Cell<T> = LCell<'id, T>;
+    type Ro<'a, T: 'a>
+        = &'a T
+    where
+        'id: 'a;
+
+    #[inline]
+    fn ro_guard<'a, T>(&'a self, cell: &'a LCell<'id, T>) -> &'a T {
+        self.ro(cell)
+    }
+}
+
+impl<'id> GuardedCellOwner for LCellOwner<'id> {
+    type Rw<'a, T: 'a>
+        = &'a mut T
+    where
+        'id: 'a;
+
+    #[inline]
+    fn rw_guard<'a, T>(&'a mut self, cell: &'a LCell<'id, T>) -> &'a mut T {
+        self.rw(cell)
+    }
+
+    #[inline]
+    fn rw2_guard<'a, T, U>(
+        &'a mut self,
+        cell1: &'a LCell<'id, T>,
+        cell2: &'a LCell<'id, U>,
+    ) -> (&'a mut T, &'a mut U) {
+        self.rw2(cell1, cell2)
+    }
+}
+
 /// Cell whose contents are owned (for borrowing purposes) by a
 /// [`LCellOwner`].
 ///
@@ -127,6 +191,10 @@ impl<'id> LCellOwner<'id> {
 /// [`LCellOwner`] instance that owns it, i.e. that shares the same
 /// Rust lifetime.
 ///
+/// Auto traits: `Send` if `T: Send`, `Sync` if `T: Send + Sync`,
+/// `Unpin` if `T: Unpin`, `UnwindSafe` if `T: UnwindSafe`, and never
+/// `RefUnwindSafe`.
+///
 /// See also [crate documentation](index.html).
 ///
 /// [`LCellOwner`]: struct.LCellOwner.html
@@ -146,6 +214,41 @@ impl<'id, T> LCell<'id, T> {
             value: UnsafeCell::new(value),
         }
     }
+
+    // Test whether two cells' contents share memory.  Zero-sized
+    // contents are compared too, since a zero-sized type such as
+    // `LCellOwner` may still carry exclusive access to something, so
+    // distinct zero-sized cells that share an address are rejected.
+    #[inline]
+    fn overlaps<U>(&self, other: &LCell<'id, U>) -> bool {
+        core::ptr::addr_eq(self.value.get(), other.value.get())
+    }
+}
+
+impl<'id, T: Default> Default for LCell<'id, T> {
+    fn default() -> Self {
+        LCell::new(T::default())
+    }
+}
+
+impl<'id, T> From<T> for LCell<'id, T> {
+    fn from(value: T) -> Self {
+        LCell::new(value)
+    }
+}
+
+/// The contents are not shown, since they can only be accessed
+/// through the owner.
+impl<'id, T> fmt::Debug for LCell<'id, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LCell").finish_non_exhaustive()
+    }
+}
+
+impl<'id> fmt::Debug for LCellOwner<'id> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LCellOwner").finish_non_exhaustive()
+    }
 }
 
 // LCellOwner and LCell already automatically implement Send, but not
@@ -167,11 +270,57 @@ impl<'id, T> LCell<'id, T> {
 unsafe impl<'id> Sync for LCellOwner<'id> {}
 unsafe impl<'id, T: Send + Sync> Sync for LCell<'id, T> {}
 
-#[cfg(test)]
+// The lifetime marker contains `&mut ()` to make `'id` invariant,
+// which would otherwise make both types `!UnwindSafe`.  As for
+// `TCellOwner`, the owner has no state that a panic could leave
+// half-updated, and as for `TCell`, owning a cell is unwind-safe if
+// the contents are, but `&LCell` is not.
+impl<'id> UnwindSafe for LCellOwner<'id> {}
+impl<'id> RefUnwindSafe for LCellOwner<'id> {}
+impl<'id, T: UnwindSafe> UnwindSafe for LCell<'id, T> {}
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::{LCell, LCellOwner};
     use std::rc::Rc;
 
+    #[test]
+    fn lcell_debug_default() {
+        #[derive(Debug, Default)]
+        struct Cells<'id> {
+            count: LCell<'id, u32>,
+            name: LCell<'id, String>,
+        }
+        LCellOwner::scope(|mut owner| {
+            let cells = Cells::default();
+            let extra: LCell<'_, u32> = 5.into();
+            let extra = *owner.ro(&extra);
+            *owner.rw(&cells.count) += extra;
+            assert_eq!(*owner.ro(&cells.count), 5);
+            assert_eq!(owner.ro(&cells.name), "");
+            assert_eq!(
+                format!("{:?}", cells),
+                "Cells { count: LCell { .. }, name: LCell { .. } }"
+            );
+            assert_eq!(format!("{:?}", owner), "LCellOwner { .. }");
+        });
+    }
+
+    #[test]
+    fn lcell_clone_move_into() {
+        LCellOwner::scope(|mut owner| {
+            let a = owner.cell(String::from("a"));
+            let b = owner.cell(String::new());
+            owner.clone_into(&a, &b);
+            assert_eq!(owner.ro(&b), "a");
+            owner.clone_into(&a, &a);
+            owner.move_into(&a, &a);
+            assert_eq!(owner.ro(&a), "a");
+            owner.move_into(&a, &b);
+            assert_eq!((owner.ro(&a).as_str(), owner.ro(&b).as_str()), ("", "a"));
+        });
+    }
+
     #[test]
     fn lcell() {
         LCellOwner::scope(|mut owner| {
@@ -197,6 +346,19 @@ mod tests {
         });
     }
 
+    #[test]
+    #[should_panic]
+    fn lcell_rw2_zst() {
+        // A zero-sized owner held in a cell must not be borrowed
+        // mutably twice
+        LCellOwner::scope(|mut outer| {
+            LCellOwner::scope(|inner| {
+                let holder = LCell::new(inner);
+                outer.rw2(&holder, &holder); // Panic here
+            });
+        });
+    }
+
     #[test]
     #[should_panic]
     fn lcell_rw3_1() {
@@ -133,28 +133,28 @@
 //! safe:
 //!
 //! - Between the cell creation and destruction, the only way to
-//! access the contents (for read or write) is through the
-//! borrow-owner instance.  So the borrow-owner is the exclusive
-//! gatekeeper of this data.
+//!   access the contents (for read or write) is through the
+//!   borrow-owner instance.  So the borrow-owner is the exclusive
+//!   gatekeeper of this data.
 //!
 //! - The borrowing calls require a `&` owner reference to return a
-//! `&` cell reference, or a `&mut` on the owner to return a `&mut`
-//! cell reference.  So this is the same kind of borrow on both sides.
-//! The only borrow we allow for the cell is the borrow that Rust
-//! allows for the borrow-owner, and while that borrow is active, the
-//! borrow-owner and the cell's reference are blocked from further
-//! incompatible borrows.  The contents of the cells act as if they
-//! were owned by the borrow-owner, just like elements within a `Vec`.
-//! So Rust's guarantees are maintained.
+//!   `&` cell reference, or a `&mut` on the owner to return a `&mut`
+//!   cell reference.  So this is the same kind of borrow on both sides.
+//!   The only borrow we allow for the cell is the borrow that Rust
+//!   allows for the borrow-owner, and while that borrow is active, the
+//!   borrow-owner and the cell's reference are blocked from further
+//!   incompatible borrows.  The contents of the cells act as if they
+//!   were owned by the borrow-owner, just like elements within a `Vec`.
+//!   So Rust's guarantees are maintained.
 //!
 //! - The borrow-owner has no control over when the cell's contents
-//! are dropped, so the borrow-owner cannot act as a gatekeeper to the
-//! data at that point.  However this cannot clash with any active
-//! borrow on the data because whilst a borrow is active, the
-//! reference to the cell is effectively locked by Rust's borrow
-//! checking.  If this is behind an `Rc`, then it's impossible for the
-//! last strong reference to be released until that borrow is
-//! released.
+//!   are dropped, so the borrow-owner cannot act as a gatekeeper to the
+//!   data at that point.  However this cannot clash with any active
+//!   borrow on the data because whilst a borrow is active, the
+//!   reference to the cell is effectively locked by Rust's borrow
+//!   checking.  If this is behind an `Rc`, then it's impossible for the
+//!   last strong reference to be released until that borrow is
+//!   released.
 //!
 //! If you can see a flaw in this reasoning or in the code, please
 //! raise an issue, preferably with test code which demonstrates the
@@ -185,11 +185,12 @@
 //! - Pro: Compile-time borrowing checks
 //! - Pro: No overhead at runtime for borrowing or ownership checks
 //! - Pro: No cell space overhead
-//! - Con: Can only borrow up to 3 objects at a time
+//! - Con: Can only borrow up to 3 objects at a time (8 for TCell
+//!   and TLCell using `rw_n`)
 //! - Con: Uses singletons, either per-process (TCell) or per-thread
-//! (TLCell), meaning only one owner is allowed per thread or process
-//! per marker type.  Code intended to be nested on the call stack
-//! must be parameterised with an external marker type.
+//!   (TLCell), meaning only one owner is allowed per thread or process
+//!   per marker type.  Code intended to be nested on the call stack
+//!   must be parameterised with an external marker type.
 //!
 //! [`LCell`] pros and cons:
 //!
@@ -260,29 +261,78 @@
 //! Here's an overview of the reasoning:
 //!
 //! - Unlike `RefCell` these cell types may be `Sync` because mutable
-//! access is protected by the cell owner.  You can get mutable access
-//! to the cell contents only if you have mutable access to the cell
-//! owner.  (Note that `Sync` is only available where the contained
-//! type is `Send + Sync`.)
+//!   access is protected by the cell owner.  You can get mutable access
+//!   to the cell contents only if you have mutable access to the cell
+//!   owner.  (Note that `Sync` is only available where the contained
+//!   type is `Send + Sync`.)
 //!
 //! - The cell owner may be `Sync` because `Sync` only allows shared
-//! immutable access to the cell owner across threads.  So there may
-//! exist `&QCell` and `&QCellOwner` references in two threads, but
-//! only immutable access to the cell contents is possible like that,
-//! so there is no soundness issue.
+//!   immutable access to the cell owner across threads.  So there may
+//!   exist `&QCell` and `&QCellOwner` references in two threads, but
+//!   only immutable access to the cell contents is possible like that,
+//!   so there is no soundness issue.
 //!
 //! - In general `Send` is safe because that is a complete transfer of
-//! some right from one thread to another (assuming the contained type
-//! is also `Send`).
+//!   some right from one thread to another (assuming the contained type
+//!   is also `Send`).
 //!
 //! - `TLCell` is the exception because there can be a different owner
-//! with the same marker type in each thread, so owners must not be
-//! sent or shared.  Also if two threads have `&TLCell` references to
-//! the same cell then mutable references to the contained data could
-//! be created in both threads which would break Rust's guarantees.
-//! So `TLCell` cannot be `Sync`.  However it can be `Send` because in
-//! that case the right to access the data is being transferred
-//! completely from one thread to another.
+//!   with the same marker type in each thread, so owners must not be
+//!   sent or shared.  Also if two threads have `&TLCell` references to
+//!   the same cell then mutable references to the contained data could
+//!   be created in both threads which would break Rust's guarantees.
+//!   So `TLCell` cannot be `Sync`.  However it can be `Send` because in
+//!   that case the right to access the data is being transferred
+//!   completely from one thread to another.
+//!
+//! In async code run on a multi-threaded executor, a task may resume
+//! on a different thread after each `.await`.  Since `TLCellOwner` is
+//! not `Send`, a future that holds one across an `.await` is not
+//! `Send` either, so it can't be spawned on such an executor.  Instead
+//! create the owner within each synchronous section of the task, for
+//! example using [`TLCellOwner::scope`], and keep only the `TLCell`
+//! instances across the `.await`.  The owner carries no data, so this
+//! loses nothing.  A task-local owner, unique per marker type per
+//! task, is not offered, because cells shared by `Rc` between two
+//! tasks on the same thread could then be borrowed mutably by both
+//! tasks' owners at once, if a borrow were held across an `.await`.
+//!
+//! # `no_std` use
+//!
+//! The `std` cargo feature is enabled by default.  With it disabled
+//! the crate is `no_std` and doesn't need an allocator, but only the
+//! parts that don't depend on `std` are available:
+//!
+//! - `TCell` and its owner-independent calls, and `TCellOwner`
+//!   created with [`TCellOwner::new_fast`] for a marker type defined
+//!   with [`tcell_marker!`].  Its singleton check is a single static
+//!   atomic flag per marker type, so it works without the process-wide
+//!   registry, but it does need a target with atomic compare-exchange
+//!   on bytes.  The registry-based calls such as `TCellOwner::new`
+//!   are not available.
+//!
+//! - `TLCell`, but `TLCellOwner` only through the `unsafe`
+//!   [`TLCellOwner::new_unchecked`], since without thread-locals there
+//!   is no way to check for other owners in the same thread.  A
+//!   checked owner backed by a single global flag would just be a
+//!   `TCellOwner`, so use `TCell` with [`TCellOwner::new_fast`]
+//!   where the check is needed.
+//!
+//! - `LCell` and `LCellOwner`, which need no runtime check at all.
+//!
+//! On a target with an allocator, the `alloc` cargo feature adds the
+//! calls that only need allocation, such as `TCellOwner::rw_slice`,
+//! `TCellOwner::sort_cells_by`, `TCell::from_boxed`, `TCell::wrap_vec`
+//! and [`RcCellExt`], still without needing `std`:
+//!
+//! ```toml
+//! qcell = { version = "0.4", default-features = false, features = ["alloc"] }
+//! ```
+//!
+//! `QCell` requires `std`, since its owner IDs are allocated from a
+//! process-wide free list behind a mutex.  The error types only
+//! implement `std::error::Error` with `std`.  Enabling the `async`
+//! or `serde` features also enables `std`, and `std` implies `alloc`.
 //!
 //! # Origin of names
 //!
@@ -302,8 +352,13 @@
 //! [`TCellOwner`]: struct.TCellOwner.html
 //! [`TLCell`]: struct.TLCell.html
 //! [`TLCellOwner`]: struct.TLCellOwner.html
+//! [`TLCellOwner::scope`]: struct.TLCellOwner.html#method.scope
 //! [`LCell`]: struct.LCell.html
 //! [`LCellOwner`]: struct.LCellOwner.html
+//! [`TCellOwner::new_fast`]: struct.TCellOwner.html#method.new_fast
+//! [`tcell_marker!`]: macro.tcell_marker.html
+//! [`RcCellExt`]: trait.RcCellExt.html
+//! [`TLCellOwner::new_unchecked`]: struct.TLCellOwner.html#method.new_unchecked
 //! [`doctest_qcell`]: doctest_qcell/index.html
 //! [`doctest_tcell`]: doctest_tcell/index.html
 //! [`doctest_tlcell`]: doctest_tlcell/index.html
@@ -312,14 +367,33 @@
 //! [**pythonesque**]: https://github.com/pythonesque
 
 #![deny(rust_2018_idioms)]
+#![cfg_attr(feature = "nightly", feature(coerce_unsized))]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "std")]
 #[macro_use]
 extern crate lazy_static;
 
+#[cfg(all(test, feature = "std"))]
+mod guarantees;
 mod lcell;
+#[cfg(feature = "std")]
 mod qcell;
 mod tcell;
 mod tlcell;
+mod traits;
+
+#[cfg(feature = "bytemuck")]
+pub mod bytemuck;
+#[cfg(feature = "std")]
+pub mod diagnostics;
+#[cfg(feature = "serde")]
+pub mod serde;
+#[cfg(any(all(test, feature = "std"), feature = "testing"))]
+pub mod testing;
 
 pub mod doctest_lcell;
 pub mod doctest_qcell;
@@ -328,13 +402,55 @@ pub mod doctest_tlcell;
 
 pub use crate::lcell::LCell;
 pub use crate::lcell::LCellOwner;
+#[cfg(feature = "std")]
+pub use crate::qcell::OwnerMismatchError;
+#[cfg(feature = "std")]
 pub use crate::qcell::QCell;
+#[cfg(feature = "std")]
 pub use crate::qcell::QCellOwner;
+#[cfg(feature = "std")]
 pub use crate::qcell::QCellOwnerID;
+pub use crate::tcell::AliasError;
+pub use crate::tcell::AsCellRef;
+pub use crate::tcell::CellIterExt;
+pub use crate::tcell::DuplicateOwnerError;
+#[cfg(feature = "std")]
+pub use crate::tcell::FrozenTCellOwner;
+pub use crate::tcell::IndexError;
+pub use crate::tcell::OnceTCell;
+#[cfg(feature = "std")]
+pub use crate::tcell::OwnerReservation;
+pub use crate::tcell::ReadToken;
+pub use crate::tcell::RoIter;
+#[cfg(feature = "std")]
+pub use crate::tcell::SharedTCellOwner;
+pub use crate::tcell::StaticTCell;
 pub use crate::tcell::TCell;
+pub use crate::tcell::TCellCopy;
+pub use crate::tcell::TCellMarker;
+pub use crate::tcell::TCellMarkerFlag;
 pub use crate::tcell::TCellOwner;
+#[cfg(feature = "async")]
+pub use crate::tcell::TCellOwnerFuture;
+pub use crate::tcell::TupleOfCells;
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub use crate::tlcell::__TLStaticCells;
 pub use crate::tlcell::TLCell;
+#[cfg(feature = "std")]
+pub use crate::tlcell::TLCellMarker;
+#[cfg(feature = "std")]
+pub use crate::tlcell::TLCellMarkerFlag;
 pub use crate::tlcell::TLCellOwner;
+pub use crate::tlcell::TupleOfTLCells;
+pub use crate::traits::ro_each_owner;
+pub use crate::traits::rw_each_owner;
+pub use crate::traits::rw_each_owner3;
+pub use crate::traits::GuardedCellOwner;
+pub use crate::traits::GuardedCellReader;
+pub use crate::traits::OptionCellExt;
+#[cfg(feature = "alloc")]
+pub use crate::traits::RcCellExt;
 
 // The compile-tests double-check that the compile_fail tests in the
 // doctests actually fail for the reason intended, not for some other
@@ -342,9 +458,9 @@ pub use crate::tlcell::TLCellOwner;
 // crate.  However since the compiler error messages may change from
 // one release to the next, the tests only remain valid for a certain
 // range of compiler versions.
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 pub mod compiletest {
-    #[rustversion::all(stable, since(1.39), before(1.40))]
+    #[rustversion::all(stable, since(1.95), before(1.96))]
     #[test]
     fn ui() {
         let t = trybuild::TestCases::new();
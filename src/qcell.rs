@@ -1,7 +1,11 @@
 use std::cell::UnsafeCell;
+use std::fmt;
+use std::panic::Location;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Mutex;
 
+use crate::{GuardedCellOwner, GuardedCellReader};
+
 type OwnerID = u32;
 
 /// Internal ID associated with a [`QCellOwner`].
@@ -15,9 +19,13 @@ type OwnerID = u32;
 /// But `QCell` instances can outlive the owner in any case, so this
 /// makes no difference to safety.
 ///
+/// IDs may be compared, for example to test whether two cells belong
+/// to the same owner using [`QCell::owner_id`].
+///
 /// [`QCellOwner`]: struct.QCellOwner.html
 /// [`QCell`]: struct.QCell.html
-#[derive(Clone, Copy)]
+/// [`QCell::owner_id`]: struct.QCell.html#method.owner_id
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct QCellOwnerID {
     id: OwnerID,
 }
@@ -38,6 +46,9 @@ impl QCellOwnerID {
 /// Borrowing-owner of zero or more [`QCell`](struct.QCell.html)
 /// instances.
 ///
+/// Auto traits: `Send`, `Sync`, `Unpin`, `UnwindSafe` and
+/// `RefUnwindSafe`.
+///
 /// See [crate documentation](index.html).
 pub struct QCellOwner {
     // Reserve first half of range for safe version, second half for
@@ -84,6 +95,14 @@ impl Default for QCellOwner {
     }
 }
 
+impl fmt::Debug for QCellOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QCellOwner")
+            .field("id", &format_args!("{:#x}", self.id))
+            .finish()
+    }
+}
+
 impl QCellOwner {
     /// Create an owner that can be used for creating many `QCell`
     /// instances.  It will have a temporally unique ID associated
@@ -135,6 +154,8 @@ impl QCellOwner {
     /// increment to get a new ID, but it could be used maliciously to
     /// obtain unsafe behaviour, so the call is marked as `unsafe`.
     ///
+    /// # Safety
+    ///
     /// If used non-maliciously the chance of getting unsafe behaviour
     /// in practice is zero -- not just close to zero but actually
     /// zero.  To get unsafe behaviour, you'd have to accidentally
@@ -173,13 +194,33 @@ impl QCellOwner {
         QCellOwnerID { id: self.id }
     }
 
+    /// Test whether a `QCell` is owned by this `QCellOwner`, in which
+    /// case borrowing it through this owner won't panic.  This is
+    /// useful when cells from several owners are mixed together.
+    #[inline]
+    pub fn owns<T: ?Sized>(&self, qc: &QCell<T>) -> bool {
+        qc.owner == self.id
+    }
+
     /// Borrow contents of a `QCell` immutably (read-only).  Many
     /// `QCell` instances can be borrowed immutably at the same time
     /// from the same owner.  Panics if the `QCell` is not owned by
     /// this `QCellOwner`.
-    pub fn ro<'a, T>(&'a self, qc: &'a QCell<T>) -> &'a T {
-        assert_eq!(qc.owner, self.id, "QCell accessed with incorrect owner");
-        unsafe { &*qc.value.get() }
+    #[track_caller]
+    pub fn ro<'a, T: ?Sized>(&'a self, qc: &'a QCell<T>) -> &'a T {
+        match self.try_ro(qc) {
+            Ok(value) => value,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Borrow contents of a `QCell` immutably (read-only), or return
+    /// an error if the `QCell` is not owned by this `QCellOwner`.  The
+    /// error records the caller's location.
+    #[track_caller]
+    pub fn try_ro<'a, T: ?Sized>(&'a self, qc: &'a QCell<T>) -> Result<&'a T, OwnerMismatchError> {
+        self.check(qc)?;
+        Ok(unsafe { &*qc.value.get() })
     }
 
     /// Borrow contents of a `QCell` mutably (read-write).  Only one
@@ -187,23 +228,91 @@ impl QCellOwner {
     /// call.  The returned reference must go out of scope before
     /// another can be borrowed.  Panics if the `QCell` is not owned
     /// by this `QCellOwner`.
-    pub fn rw<'a, T>(&'a mut self, qc: &'a QCell<T>) -> &'a mut T {
-        assert_eq!(qc.owner, self.id, "QCell accessed with incorrect owner");
-        unsafe { &mut *qc.value.get() }
+    #[track_caller]
+    pub fn rw<'a, T: ?Sized>(&'a mut self, qc: &'a QCell<T>) -> &'a mut T {
+        match self.try_rw(qc) {
+            Ok(value) => value,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Borrow contents of a `QCell` mutably (read-write), or return
+    /// an error if the `QCell` is not owned by this `QCellOwner`.  The
+    /// error records the caller's location.
+    #[track_caller]
+    pub fn try_rw<'a, T: ?Sized>(
+        &'a mut self,
+        qc: &'a QCell<T>,
+    ) -> Result<&'a mut T, OwnerMismatchError> {
+        self.check(qc)?;
+        Ok(unsafe { &mut *qc.value.get() })
+    }
+
+    #[inline]
+    #[track_caller]
+    fn check<T: ?Sized>(&self, qc: &QCell<T>) -> Result<(), OwnerMismatchError> {
+        if self.owns(qc) {
+            Ok(())
+        } else {
+            Err(OwnerMismatchError {
+                cell_owner: qc.owner,
+                owner: self.id,
+                location: Location::caller(),
+            })
+        }
+    }
+
+    #[inline]
+    #[track_caller]
+    fn assert_owns<T: ?Sized>(&self, qc: &QCell<T>) {
+        if let Err(e) = self.check(qc) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Replace the contents of `dst` with a clone of the contents of
+    /// `src`, using `clone_from` so that existing allocations in
+    /// `dst` may be reused.  If both references are to the same
+    /// `QCell`, this does nothing.  Panics if either `QCell` is not
+    /// owned by this `QCellOwner`.
+    #[track_caller]
+    pub fn clone_into<T: Clone>(&mut self, src: &QCell<T>, dst: &QCell<T>) {
+        if std::ptr::eq(src, dst) {
+            self.assert_owns(src);
+        } else {
+            let (src, dst) = self.rw2(src, dst);
+            dst.clone_from(src);
+        }
+    }
+
+    /// Move the contents of `src` into `dst`, leaving
+    /// `Default::default()` in `src`.  If both references are to the
+    /// same `QCell`, this does nothing, rather than resetting the
+    /// contents.  Panics if either `QCell` is not owned by this
+    /// `QCellOwner`.
+    #[track_caller]
+    pub fn move_into<T: Default>(&mut self, src: &QCell<T>, dst: &QCell<T>) {
+        if std::ptr::eq(src, dst) {
+            self.assert_owns(src);
+        } else {
+            let (src, dst) = self.rw2(src, dst);
+            *dst = std::mem::take(src);
+        }
     }
 
     /// Borrow contents of two `QCell` instances mutably.  Panics if
     /// the two `QCell` instances point to the same memory.  Panics if
     /// either `QCell` is not owned by this `QCellOwner`.
-    pub fn rw2<'a, T, U>(
+    #[track_caller]
+    pub fn rw2<'a, T: ?Sized, U: ?Sized>(
         &'a mut self,
         qc1: &'a QCell<T>,
         qc2: &'a QCell<U>,
     ) -> (&'a mut T, &'a mut U) {
-        assert_eq!(qc1.owner, self.id, "QCell accessed with incorrect owner");
-        assert_eq!(qc2.owner, self.id, "QCell accessed with incorrect owner");
-        assert_ne!(
-            qc1 as *const _ as usize, qc2 as *const _ as usize,
+        self.assert_owns(qc1);
+        self.assert_owns(qc2);
+        assert!(
+            !std::ptr::addr_eq(qc1.value.get(), qc2.value.get()),
             "Illegal to borrow same QCell twice with rw2()"
         );
         unsafe { (&mut *qc1.value.get(), &mut *qc2.value.get()) }
@@ -212,19 +321,20 @@ impl QCellOwner {
     /// Borrow contents of three `QCell` instances mutably.  Panics if
     /// any pair of `QCell` instances point to the same memory.
     /// Panics if any `QCell` is not owned by this `QCellOwner`.
-    pub fn rw3<'a, T, U, V>(
+    #[track_caller]
+    pub fn rw3<'a, T: ?Sized, U: ?Sized, V: ?Sized>(
         &'a mut self,
         qc1: &'a QCell<T>,
         qc2: &'a QCell<U>,
         qc3: &'a QCell<V>,
     ) -> (&'a mut T, &'a mut U, &'a mut V) {
-        assert_eq!(qc1.owner, self.id, "QCell accessed with incorrect owner");
-        assert_eq!(qc2.owner, self.id, "QCell accessed with incorrect owner");
-        assert_eq!(qc3.owner, self.id, "QCell accessed with incorrect owner");
+        self.assert_owns(qc1);
+        self.assert_owns(qc2);
+        self.assert_owns(qc3);
         assert!(
-            (qc1 as *const _ as usize != qc2 as *const _ as usize)
-                && (qc2 as *const _ as usize != qc3 as *const _ as usize)
-                && (qc3 as *const _ as usize != qc1 as *const _ as usize),
+            !std::ptr::addr_eq(qc1.value.get(), qc2.value.get())
+                && !std::ptr::addr_eq(qc2.value.get(), qc3.value.get())
+                && !std::ptr::addr_eq(qc3.value.get(), qc1.value.get()),
             "Illegal to borrow same QCell twice with rw3()"
         );
         unsafe {
@@ -237,6 +347,115 @@ impl QCellOwner {
     }
 }
 
+impl GuardedCellReader for QCellOwner {
+    type Cell<T> = QCell<T>;
+    type Ro<'a, T: 'a> = &'a T;
+
+    #[inline]
+    #[track_caller]
+    fn ro_guard<'a, T>(&'a self, cell: &'a QCell<T>) -> &'a T {
+        self.ro(cell)
+    }
+}
+
+/// The guards are plain references, and borrowing panics if the cell
+/// is not owned by this owner, as for the inherent methods.
+impl GuardedCellOwner for QCellOwner {
+    type Rw<'a, T: 'a> = &'a mut T;
+
+    #[inline]
+    #[track_caller]
+    fn rw_guard<'a, T>(&'a mut self, cell: &'a QCell<T>) -> &'a mut T {
+        self.rw(cell)
+    }
+
+    #[inline]
+    #[track_caller]
+    fn rw2_guard<'a, T, U>(
+        &'a mut self,
+        cell1: &'a QCell<T>,
+        cell2: &'a QCell<U>,
+    ) -> (&'a mut T, &'a mut U) {
+        self.rw2(cell1, cell2)
+    }
+}
+
+crate::traits::rc_cell_ext!([T: ?Sized] QCell<T>, QCellOwner, T);
+
+/// Error returned when a [`QCell`] is accessed using a
+/// [`QCellOwner`] other than the one that owns it, by a call such as
+/// [`QCellOwner::try_rw`].  The owner IDs are included in the
+/// `Debug` and `Display` output to help track down which owner was
+/// expected.  The panicking calls such as [`QCellOwner::rw`] panic
+/// with the same message, and the panic reports the caller's
+/// location.  Two errors compare equal if they have the same owner
+/// IDs, whatever the location of the access.
+///
+/// [`QCell`]: struct.QCell.html
+/// [`QCellOwner`]: struct.QCellOwner.html
+/// [`QCellOwner::try_rw`]: struct.QCellOwner.html#method.try_rw
+/// [`QCellOwner::rw`]: struct.QCellOwner.html#method.rw
+#[derive(Clone, Copy, Debug)]
+pub struct OwnerMismatchError {
+    cell_owner: OwnerID,
+    owner: OwnerID,
+    location: &'static Location<'static>,
+}
+
+impl OwnerMismatchError {
+    // Error for an access to `qc` through the owner with ID `owner`,
+    // used by `testing::MockOwner` to simulate a failed check
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn new<T: ?Sized>(
+        qc: &QCell<T>,
+        owner: QCellOwnerID,
+        location: &'static Location<'static>,
+    ) -> Self {
+        Self {
+            cell_owner: qc.owner,
+            owner: owner.id,
+            location,
+        }
+    }
+
+    /// ID of the owner that the cell belongs to.
+    pub fn cell_owner_id(&self) -> QCellOwnerID {
+        QCellOwnerID {
+            id: self.cell_owner,
+        }
+    }
+
+    /// ID of the owner that was used to access the cell.
+    pub fn owner_id(&self) -> QCellOwnerID {
+        QCellOwnerID { id: self.owner }
+    }
+
+    /// Source location of the access.
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+}
+
+impl PartialEq for OwnerMismatchError {
+    fn eq(&self, other: &Self) -> bool {
+        self.cell_owner == other.cell_owner && self.owner == other.owner
+    }
+}
+
+impl Eq for OwnerMismatchError {}
+
+impl fmt::Display for OwnerMismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "QCell accessed with incorrect owner: cell belongs to QCellOwner ID {:#x}, accessed with QCellOwner ID {:#x}",
+            self.cell_owner, self.owner
+        )
+    }
+}
+
+impl std::error::Error for OwnerMismatchError {}
+
 /// Cell whose contents is owned (for borrowing purposes) by a
 /// [`QCellOwner`].
 ///
@@ -244,12 +463,33 @@ impl QCellOwner {
 /// [`QCellOwner`] instance that was used to create it.  See [crate
 /// documentation](index.html).
 ///
+/// The contents may be unsized.  Pointers to cells coerce to
+/// pointers to unsized cells in the same way as for the contained
+/// types, so for example `Rc<QCell<[u8; 16]>>` coerces to
+/// `Rc<QCell<[u8]>>` and `Box<QCell<Widget>>` to
+/// `Box<QCell<dyn Draw>>`.
+///
+/// Auto traits: `Send` if `T: Send`, `Sync` if `T: Send + Sync`,
+/// `Unpin` if `T: Unpin`, `UnwindSafe` if `T: UnwindSafe`, and never
+/// `RefUnwindSafe`.
+///
 /// [`QCellOwner`]: struct.QCellOwner.html
-pub struct QCell<T> {
+pub struct QCell<T: ?Sized> {
     owner: OwnerID,
     value: UnsafeCell<T>,
 }
 
+/// The contents are not shown, since they can only be accessed
+/// through the owner, but the owner ID is, to help match up cells
+/// with owners.
+impl<T: ?Sized> fmt::Debug for QCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QCell")
+            .field("owner", &format_args!("{:#x}", self.owner))
+            .finish_non_exhaustive()
+    }
+}
+
 // QCell already automatically implements Send, but not Sync.
 // We can add this implementation though, since it's fine to
 // send a &QCell to another thread, and even mutably borrow the value
@@ -266,7 +506,7 @@ pub struct QCell<T> {
 // as those of std::sync::RwLock<T>. That's not a coincidence.
 // The way these types let you access T concurrently is the same,
 // even though the locking mechanisms are different.
-unsafe impl<T: Send + Sync> Sync for QCell<T> {}
+unsafe impl<T: Send + Sync + ?Sized> Sync for QCell<T> {}
 
 impl<T> QCell<T> {
     /// Create a new `QCell` owned for borrowing purposes by the given
@@ -278,6 +518,82 @@ impl<T> QCell<T> {
             owner: owner.id,
         }
     }
+
+    /// Consume the cell, returning its contents.  No owner is needed,
+    /// since owning the cell means that nothing else can access it.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Consume the cell, returning the ID of its owner and its
+    /// contents.  The cell can be put back together later with
+    /// [`QCellOwnerID::cell`], for example after storing the parts
+    /// separately in some custom storage.  This is safe because an ID
+    /// only allows access through the one owner that has that ID.
+    ///
+    /// [`QCellOwnerID::cell`]: struct.QCellOwnerID.html#method.cell
+    #[inline]
+    pub fn into_parts(self) -> (QCellOwnerID, T) {
+        (self.owner_id(), self.value.into_inner())
+    }
+
+    /// Consume the cell, returning a new cell belonging to the same
+    /// owner that contains the result of passing the contents to the
+    /// given closure.  No owner is needed, since the owner ID is
+    /// carried over from this cell.
+    #[inline]
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> QCell<U> {
+        let owner = self.owner;
+        QCell {
+            value: UnsafeCell::new(f(self.into_inner())),
+            owner,
+        }
+    }
+
+    /// Consume the cell, passing the contents to the given fallible
+    /// closure, and return a new cell belonging to the same owner
+    /// containing the result, or the closure's error.
+    #[inline]
+    pub fn try_map<U, E>(self, f: impl FnOnce(T) -> Result<U, E>) -> Result<QCell<U>, E> {
+        let owner = self.owner;
+        let value = f(self.into_inner())?;
+        Ok(QCell {
+            value: UnsafeCell::new(value),
+            owner,
+        })
+    }
+}
+
+impl<T: ?Sized> QCell<T> {
+    /// Get the ID of the owner of this cell.  Two cells belong to the
+    /// same owner if their IDs are equal.  See also
+    /// [`QCellOwner::owns`].
+    ///
+    /// [`QCellOwner::owns`]: struct.QCellOwner.html#method.owns
+    #[inline]
+    pub fn owner_id(&self) -> QCellOwnerID {
+        QCellOwnerID { id: self.owner }
+    }
+
+    /// Borrow the contents mutably without an owner.  This is safe
+    /// because the `&mut` reference to the cell proves that nothing
+    /// else can currently access it.  Unlike `TCell` and `TLCell`
+    /// there is no `from_mut`, since a `QCell` must carry the ID of
+    /// its owner alongside the value.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// Get a raw pointer to the contents, as for `UnsafeCell::get`.
+    /// The same rules apply to accesses through the pointer as for
+    /// `TCell::as_ptr`, with the owner of this cell standing in for
+    /// the `TCellOwner`.
+    #[inline]
+    pub const fn as_ptr(&self) -> *mut T {
+        self.value.get()
+    }
 }
 
 #[cfg(test)]
@@ -304,6 +620,62 @@ mod tests {
         assert_eq!(total, 303);
     }
 
+    #[test]
+    fn qcell_try_ro_rw() {
+        let _lock = LOCK.lock().unwrap();
+        let mut owner1 = QCellOwner::new();
+        let mut owner2 = QCellOwner::new();
+        let c1 = owner1.cell(1u32);
+        *owner1.try_rw(&c1).unwrap() += 1;
+        assert_eq!(owner1.try_ro(&c1), Ok(&2));
+        let err = owner2.try_rw(&c1).unwrap_err();
+        let line = line!() - 1;
+        let err2 = owner2.try_ro(&c1).unwrap_err();
+        assert_eq!(err2, err, "Expected the location to be ignored");
+        assert_ne!(err2.location(), err.location());
+        assert_eq!(err.cell_owner_id(), owner1.id());
+        assert_eq!(err.owner_id(), owner2.id());
+        assert_eq!(err2.cell_owner_id(), err.cell_owner_id());
+        assert_eq!(
+            (err.location().file(), err.location().line()),
+            (file!(), line)
+        );
+        let text = err.to_string();
+        assert!(text.starts_with("QCell accessed with incorrect owner: "));
+        assert!(!text.contains(file!()));
+        assert!(text.contains(&format!("{:#x}", owner1.id)));
+        assert!(text.contains(&format!("{:#x}", owner2.id)));
+    }
+
+    #[test]
+    fn qcell_ro_wrong_owner() {
+        let _lock = LOCK.lock().unwrap();
+        let owner1 = QCellOwner::new();
+        let owner2 = QCellOwner::new();
+        let c1 = owner1.cell(1u32);
+        // Catch the panic, to avoid poisoning the lock
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            owner2.ro(&c1);
+        }));
+        let payload = result.unwrap_err();
+        let text = payload.downcast_ref::<String>().unwrap();
+        assert!(text.starts_with("QCell accessed with incorrect owner: "));
+        assert!(text.contains(&format!("{:#x}", owner1.id)));
+        // The location is reported by the panic itself, not repeated
+        // in the message
+        assert!(!text.contains(file!()));
+
+        // The multi-cell calls give the same message
+        let mut owner2 = owner2;
+        let c2 = owner2.cell(2u32);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            owner2.rw2(&c2, &c1);
+        }));
+        let payload = result.unwrap_err();
+        let text = payload.downcast_ref::<String>().unwrap();
+        assert!(text.starts_with("QCell accessed with incorrect owner: "));
+    }
+
     #[test]
     fn qcell_ids() {
         let _lock = LOCK.lock().unwrap();
@@ -364,4 +736,128 @@ mod tests {
             owner1.ro(&c11) + owner2.ro(&c12) + owner1.ro(&c21) + owner2.ro(&c22)
         );
     }
+
+    #[test]
+    fn qcell_map() {
+        let _lock = LOCK.lock().unwrap();
+        let mut owner = QCellOwner::new();
+        let cell = QCell::new(&owner, String::from("hello"));
+        let len = cell.map(|s| s.len());
+        *owner.rw(&len) += 1;
+        assert_eq!(*owner.ro(&len), 6);
+        let parsed = QCell::new(&owner, "12").try_map(|s| s.parse::<u32>());
+        assert_eq!(*owner.ro(&parsed.unwrap()), 12);
+        assert!(QCell::new(&owner, "x")
+            .try_map(|s| s.parse::<u32>())
+            .is_err());
+        assert_eq!(QCell::new(&owner, 3).into_inner(), 3);
+    }
+
+    #[test]
+    fn qcell_owns() {
+        let _lock = LOCK.lock().unwrap();
+        let mut owner1 = QCellOwner::new();
+        let owner2 = QCellOwner::new();
+        let cells = vec![
+            owner1.cell(1u32),
+            owner2.cell(2),
+            owner1.cell(3),
+            owner2.id().cell(4),
+        ];
+        // Only touch the cells belonging to `owner1`
+        for cell in &cells {
+            if owner1.owns(cell) {
+                *owner1.rw(cell) += 10;
+            }
+        }
+        let values: Vec<u32> = cells.into_iter().map(QCell::into_inner).collect();
+        assert_eq!(values, [11, 2, 13, 4]);
+        let (c1, c2) = (owner1.cell(()), owner2.cell(()));
+        assert_eq!(c1.owner_id(), owner1.id());
+        assert_eq!(c2.owner_id(), owner2.id());
+        assert_ne!(c1.owner_id(), c2.owner_id());
+        assert!(!owner2.owns(&c1));
+    }
+
+    #[test]
+    fn qcell_into_parts() {
+        let _lock = LOCK.lock().unwrap();
+        let mut owner = QCellOwner::new();
+        let cell = owner.cell(String::from("abc"));
+        let (id, value) = cell.into_parts();
+        assert_eq!(id, owner.id());
+        let cell = id.cell(value);
+        assert!(owner.owns(&cell));
+        owner.rw(&cell).push('d');
+        unsafe { (*cell.as_ptr()).push('e') };
+        assert_eq!(owner.ro(&cell), "abcde");
+    }
+
+    #[test]
+    fn qcell_debug() {
+        let _lock = LOCK.lock().unwrap();
+        #[derive(Debug)]
+        struct Context {
+            owner: QCellOwner,
+            cell: QCell<Vec<u8>>,
+        }
+        let owner = QCellOwner::new();
+        let id = owner.id;
+        let ctx = Context {
+            cell: owner.cell(vec![1]),
+            owner,
+        };
+        assert_eq!(
+            format!("{:?}", ctx),
+            format!(
+                "Context {{ owner: QCellOwner {{ id: {:#x} }}, cell: QCell {{ owner: {:#x}, .. }} }}",
+                id, id
+            )
+        );
+        assert_eq!(ctx.owner.ro(&ctx.cell), &[1]);
+    }
+
+    #[test]
+    fn qcell_unsized() {
+        use std::fmt::Write;
+        use std::rc::Rc;
+        let _lock = LOCK.lock().unwrap();
+        let mut owner = QCellOwner::new();
+        let slice: Rc<QCell<[u32]>> = Rc::new(owner.cell([1, 2, 3]));
+        let writer: Box<QCell<dyn Write>> = Box::new(owner.cell(String::new()));
+        owner.rw(&*slice)[2] += 10;
+        assert_eq!(owner.ro(&*slice), [1, 2, 13]);
+        let (s, w) = owner.rw2(&*slice, &*writer);
+        write!(w, "{}", s.len()).unwrap();
+        assert_eq!(writer.owner_id(), owner.id());
+        assert!(owner.try_ro(&*slice).is_ok());
+    }
+
+    #[test]
+    fn qcell_get_mut() {
+        let _lock = LOCK.lock().unwrap();
+        let owner = QCellOwner::new();
+        let mut cell = owner.cell(vec![1u32]);
+        // Build up contents before the cell is shared
+        cell.get_mut().push(2);
+        assert_eq!(owner.ro(&cell), &[1, 2]);
+        assert_eq!(cell.into_inner(), vec![1, 2]);
+    }
+
+    #[test]
+    fn qcell_clone_move_into() {
+        let _lock = LOCK.lock().unwrap();
+        let mut owner = QCellOwner::new();
+        let a = owner.cell(vec![1]);
+        let b = owner.cell(vec![]);
+        owner.clone_into(&a, &b);
+        assert_eq!(owner.ro(&b), &[1]);
+        owner.clone_into(&a, &a);
+        owner.move_into(&a, &a);
+        assert_eq!(owner.ro(&a), &[1]);
+        owner.rw(&a).push(2);
+        owner.move_into(&a, &b);
+        assert!(owner.ro(&a).is_empty());
+        assert_eq!(owner.ro(&b), &[1, 2]);
+    }
 }
@@ -0,0 +1,436 @@
+//! Optional `serde` support, enabled with the `serde` cargo feature.
+//!
+//! [`TCell`] and [`TLCell`] implement `Deserialize` by deserializing
+//! the contents and wrapping them in a new cell.  Reading the
+//! contents to serialize them needs the owner, so by default the
+//! cells don't implement `Serialize`.  Instead, pair the owner with
+//! the cell using [`SerializeWith`], or mark cell fields with
+//! `#[serde(serialize_with = "qcell::serde::cell_with_owner")]` and
+//! serialize from within [`with_owner`]:
+//!
+//! ```
+//!# use qcell::{TCell, TCellOwner};
+//!# use serde::Serialize;
+//! struct Marker;
+//!
+//! #[derive(Serialize)]
+//! struct Node {
+//!     #[serde(serialize_with = "qcell::serde::cell_with_owner")]
+//!     value: TCell<Marker, u32>,
+//! }
+//!
+//! let owner = TCellOwner::<Marker>::new();
+//! let node = Node { value: TCell::new(1) };
+//! let json = qcell::serde::with_owner(&owner, || serde_json::to_string(&node)).unwrap();
+//! assert_eq!(json, r#"{"value":1}"#);
+//! ```
+//!
+//! A marker type may opt in to `Serialize` being implemented for its
+//! cells by implementing [`AmbientSerialize`].  Those cells then work
+//! when nested anywhere, for example in an `Option` or a `Vec`, but
+//! serialization fails with a serializer error unless it happens
+//! within [`with_owner`] or [`SerializeWith`]:
+//!
+//! ```
+//!# use qcell::{TCell, TCellOwner};
+//!# use serde::Serialize;
+//! struct Marker;
+//! impl qcell::serde::AmbientSerialize for Marker {}
+//!
+//! #[derive(Serialize)]
+//! struct Node {
+//!     value: TCell<Marker, u32>,
+//!     children: Vec<TCell<Marker, u32>>,
+//! }
+//!
+//! let owner = TCellOwner::<Marker>::new();
+//! let node = Node { value: TCell::new(1), children: vec![TCell::new(2)] };
+//! assert!(serde_json::to_string(&node).is_err());
+//! let json = qcell::serde::with_owner(&owner, || serde_json::to_string(&node)).unwrap();
+//! assert_eq!(json, r#"{"value":1,"children":[2]}"#);
+//! ```
+//!
+//! Without the opt-in, such a struct doesn't compile:
+//!
+//! ```compile_fail
+//!# use qcell::TCell;
+//!# use serde::Serialize;
+//! struct Marker;
+//!
+//! #[derive(Serialize)]
+//! struct Node {
+//!     value: TCell<Marker, u32>,  // Compile fail
+//! }
+//! ```
+//!
+//! [`TCell`]: ../struct.TCell.html
+//! [`TLCell`]: ../struct.TLCell.html
+//! [`AmbientSerialize`]: trait.AmbientSerialize.html
+//! [`SerializeWith`]: struct.SerializeWith.html
+//! [`with_owner`]: fn.with_owner.html
+
+use crate::{TCell, TCellOwner, TLCell, TLCellOwner};
+use ::serde::ser::Error as _;
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::any::TypeId;
+use std::cell::RefCell;
+
+std::thread_local! {
+    // Owners made available by `with_owner()`, keyed by owner type,
+    // innermost last
+    static OWNERS: RefCell<Vec<(TypeId, *const ())>> = const { RefCell::new(Vec::new()) };
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Owner types that can be made available to serialization with
+/// [`with_owner`]: `TCellOwner` and `TLCellOwner`.  This cannot be
+/// implemented outside this crate.
+///
+/// [`with_owner`]: fn.with_owner.html
+pub trait SerializeOwner: private::Sealed + 'static {
+    /// Type of the cells owned by this owner, with contents `T`
+    type Cell<T: ?Sized>: SerializeCell<Owner = Self, Value = T> + ?Sized;
+}
+
+impl<Q: 'static> private::Sealed for TCellOwner<Q> {}
+impl<Q: 'static> SerializeOwner for TCellOwner<Q> {
+    type Cell<T: ?Sized> = TCell<Q, T>;
+}
+impl<Q: 'static> private::Sealed for TLCellOwner<Q> {}
+impl<Q: 'static> SerializeOwner for TLCellOwner<Q> {
+    type Cell<T: ?Sized> = TLCell<Q, T>;
+}
+
+/// Cell types that can be serialized using an owner made available
+/// with [`with_owner`]: `TCell` and `TLCell`.  This cannot be
+/// implemented outside this crate.
+///
+/// [`with_owner`]: fn.with_owner.html
+pub trait SerializeCell: private::Sealed {
+    /// Type of the owner needed to read the cell
+    type Owner: SerializeOwner;
+
+    /// Type contained in the cell
+    type Value: ?Sized;
+
+    #[doc(hidden)]
+    fn ro_with<'a>(&'a self, owner: &'a Self::Owner) -> &'a Self::Value;
+}
+
+impl<Q: 'static, T: ?Sized> private::Sealed for TCell<Q, T> {}
+impl<Q: 'static, T: ?Sized> SerializeCell for TCell<Q, T> {
+    type Owner = TCellOwner<Q>;
+    type Value = T;
+    #[inline]
+    fn ro_with<'a>(&'a self, owner: &'a TCellOwner<Q>) -> &'a T {
+        owner.ro(self)
+    }
+}
+
+impl<Q: 'static, T: ?Sized> private::Sealed for TLCell<Q, T> {}
+impl<Q: 'static, T: ?Sized> SerializeCell for TLCell<Q, T> {
+    type Owner = TLCellOwner<Q>;
+    type Value = T;
+    #[inline]
+    fn ro_with<'a>(&'a self, owner: &'a TLCellOwner<Q>) -> &'a T {
+        owner.ro(self)
+    }
+}
+
+impl<'de, Q, T: Deserialize<'de>> Deserialize<'de> for TCell<Q, T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(TCell::new)
+    }
+}
+
+impl<'de, Q, T: Deserialize<'de>> Deserialize<'de> for TLCell<Q, T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(TLCell::new)
+    }
+}
+
+/// Opt-in for a marker type, so that its [`TCell`] and [`TLCell`]
+/// cells implement `Serialize` using the owner made available by
+/// [`with_owner`].  Serializing such a cell outside of `with_owner`
+/// or [`SerializeWith`] compiles, but fails at runtime with a
+/// serializer error, which is why this is not the default.
+///
+/// [`TCell`]: ../struct.TCell.html
+/// [`TLCell`]: ../struct.TLCell.html
+/// [`with_owner`]: fn.with_owner.html
+/// [`SerializeWith`]: struct.SerializeWith.html
+pub trait AmbientSerialize: 'static {}
+
+/// Serializes the contents using the owner made available by
+/// [`with_owner`], or fails if there is none.
+///
+/// [`with_owner`]: fn.with_owner.html
+impl<Q: AmbientSerialize, T: Serialize + ?Sized> Serialize for TCell<Q, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        cell_with_owner(self, serializer)
+    }
+}
+
+/// Serializes the contents using the owner made available by
+/// [`with_owner`], or fails if there is none.
+///
+/// [`with_owner`]: fn.with_owner.html
+impl<Q: AmbientSerialize, T: Serialize + ?Sized> Serialize for TLCell<Q, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        cell_with_owner(self, serializer)
+    }
+}
+
+/// Pairs a cell with its owner, so that the contents can be
+/// serialized.  `O` is the owner type, either `TCellOwner` or
+/// `TLCellOwner`, and `T` the type of the contents.  Whilst the
+/// contents are being serialized, the owner is also made available
+/// as for [`with_owner`], so nested cells are handled too.
+///
+/// ```
+///# use qcell::{TLCell, TLCellOwner};
+///# use qcell::serde::SerializeWith;
+///# struct Marker;
+/// let owner = TLCellOwner::<Marker>::new();
+/// let cell = TLCell::new(vec![1, 2]);
+/// let json = serde_json::to_string(&SerializeWith::new(&owner, &cell)).unwrap();
+/// assert_eq!(json, "[1,2]");
+/// ```
+///
+/// [`with_owner`]: fn.with_owner.html
+pub struct SerializeWith<'a, O: SerializeOwner, T: ?Sized> {
+    owner: &'a O,
+    cell: &'a O::Cell<T>,
+}
+
+impl<'a, O: SerializeOwner, T: ?Sized> SerializeWith<'a, O, T> {
+    /// Pair a cell with its owner.
+    pub fn new(owner: &'a O, cell: &'a O::Cell<T>) -> Self {
+        Self { owner, cell }
+    }
+}
+
+impl<'a, O: SerializeOwner, T: Serialize + ?Sized> Serialize for SerializeWith<'a, O, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        with_owner(self.owner, || {
+            self.cell.ro_with(self.owner).serialize(serializer)
+        })
+    }
+}
+
+/// [`SerializeWith`] for a [`TCell`] and its `TCellOwner`.
+///
+/// [`SerializeWith`]: struct.SerializeWith.html
+/// [`TCell`]: ../struct.TCell.html
+pub type OwnerSerializeWrapper<'a, Q, T> = SerializeWith<'a, TCellOwner<Q>, T>;
+
+/// Make the owner available to serialization of its cells for the
+/// duration of the given closure, returning the closure's result.
+/// This accepts both `TCellOwner` and `TLCellOwner`.  Calls may be
+/// nested, for example for owners with different marker types.
+pub fn with_owner<O: SerializeOwner, R>(owner: &O, f: impl FnOnce() -> R) -> R {
+    struct Pop;
+    impl Drop for Pop {
+        fn drop(&mut self) {
+            OWNERS.with(|owners| owners.borrow_mut().pop());
+        }
+    }
+    OWNERS.with(|owners| {
+        owners
+            .borrow_mut()
+            .push((TypeId::of::<O>(), owner as *const O as *const ()))
+    });
+    let _pop = Pop;
+    f()
+}
+
+/// Serialize the contents of a [`TCell`] or [`TLCell`] using the
+/// owner made available by [`with_owner`] or [`SerializeWith`].
+/// This is intended for use with `#[serde(serialize_with = "...")]`,
+/// and is also what the `Serialize` implementations enabled by
+/// [`AmbientSerialize`] use.  Fails with a serializer error
+/// if no owner with the right marker type is available.
+///
+/// [`TCell`]: ../struct.TCell.html
+/// [`TLCell`]: ../struct.TLCell.html
+/// [`with_owner`]: fn.with_owner.html
+/// [`SerializeWith`]: struct.SerializeWith.html
+/// [`AmbientSerialize`]: trait.AmbientSerialize.html
+pub fn cell_with_owner<C, S>(cell: &C, serializer: S) -> Result<S::Ok, S::Error>
+where
+    C: SerializeCell + ?Sized,
+    C::Value: Serialize,
+    S: Serializer,
+{
+    let id = TypeId::of::<C::Owner>();
+    let owner = OWNERS.with(|owners| {
+        owners
+            .borrow()
+            .iter()
+            .rev()
+            .find(|(owner_id, _)| *owner_id == id)
+            .map(|(_, owner)| *owner)
+    });
+    match owner {
+        // Safe because the owner is borrowed by `with_owner()` for as
+        // long as the entry exists, and the entry was made for an
+        // owner of this type
+        Some(owner) => {
+            let owner = unsafe { &*(owner as *const C::Owner) };
+            cell.ro_with(owner).serialize(serializer)
+        }
+        None => Err(S::Error::custom(format!(
+            "No owner available for {}",
+            std::any::type_name::<C::Owner>()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        cell_with_owner, with_owner, AmbientSerialize, OwnerSerializeWrapper, SerializeWith,
+    };
+    use crate::{TCell, TCellOwner, TLCell, TLCellOwner};
+    use serde::{Deserialize, Serialize};
+
+    struct Marker;
+    impl AmbientSerialize for Marker {}
+
+    #[derive(Serialize, Deserialize)]
+    struct Inner {
+        #[serde(serialize_with = "cell_with_owner")]
+        count: TCell<Marker, u32>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Outer {
+        #[serde(serialize_with = "cell_with_owner")]
+        name: TCell<Marker, String>,
+        #[serde(serialize_with = "cell_with_owner")]
+        inner: TCell<Marker, Inner>,
+        #[serde(serialize_with = "cell_with_owner")]
+        list: TCell<Marker, Vec<u8>>,
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let mut owner = TCellOwner::<Marker>::new();
+        let outer = Outer {
+            name: TCell::new("outer".into()),
+            inner: TCell::new(Inner {
+                count: TCell::new(3),
+            }),
+            list: TCell::new(vec![1, 2]),
+        };
+        let json = with_owner(&owner, || serde_json::to_string(&outer)).unwrap();
+        assert_eq!(json, r#"{"name":"outer","inner":{"count":3},"list":[1,2]}"#);
+
+        let outer: Outer = serde_json::from_str(&json).unwrap();
+        assert_eq!(owner.ro(&outer.name), "outer");
+        assert_eq!(owner.ro(&outer.list), &[1, 2]);
+        let inner = owner.rw(&outer.inner);
+        *inner.count.get_mut() += 1;
+        let cell = TCell::new(outer);
+        let json = serde_json::to_string(&OwnerSerializeWrapper::new(&owner, &cell)).unwrap();
+        assert_eq!(json, r#"{"name":"outer","inner":{"count":4},"list":[1,2]}"#);
+    }
+
+    #[test]
+    fn serde_no_owner() {
+        let inner = Inner {
+            count: TCell::new(1),
+        };
+        assert!(serde_json::to_string(&inner).is_err());
+    }
+
+    #[test]
+    fn serde_tlcell() {
+        struct Marker;
+        let owner = TLCellOwner::<Marker>::new();
+        let cell: TLCell<Marker, Vec<u32>> = serde_json::from_str("[1,2,3]").unwrap();
+        assert_eq!(owner.ro(&cell), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn serde_tlcell_round_trip() {
+        struct Marker;
+        impl AmbientSerialize for Marker {}
+        #[derive(Serialize, Deserialize)]
+        struct State {
+            count: TLCell<Marker, u32>,
+            label: Option<TLCell<Marker, String>>,
+            missing: Option<TLCell<Marker, String>>,
+            items: Vec<TLCell<Marker, i8>>,
+        }
+        let mut owner = TLCellOwner::<Marker>::new();
+        let state = State {
+            count: TLCell::new(1),
+            label: Some(TLCell::new("a".into())),
+            missing: None,
+            items: vec![TLCell::new(-1), TLCell::new(2)],
+        };
+        // Without an owner, the cells can't be read
+        assert!(serde_json::to_string(&state).is_err());
+        let json = with_owner(&owner, || serde_json::to_string(&state)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"count":1,"label":"a","missing":null,"items":[-1,2]}"#
+        );
+
+        let state: State = serde_json::from_str(&json).unwrap();
+        *owner.rw(&state.count) += 1;
+        owner.rw(&state.items[1]).clone_from(&5);
+        let json = with_owner(&owner, || serde_json::to_string(&state)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"count":2,"label":"a","missing":null,"items":[-1,5]}"#
+        );
+    }
+
+    #[test]
+    fn serde_tlcell_with() {
+        struct Marker;
+        #[derive(Serialize, Deserialize)]
+        struct Pair {
+            #[serde(serialize_with = "cell_with_owner")]
+            left: TLCell<Marker, u8>,
+            #[serde(serialize_with = "cell_with_owner")]
+            right: TLCell<Marker, u8>,
+        }
+        let mut owner = TLCellOwner::<Marker>::new();
+        let cell = TLCell::new(Pair {
+            left: TLCell::new(1),
+            right: TLCell::new(2),
+        });
+        let json = serde_json::to_string(&SerializeWith::new(&owner, &cell)).unwrap();
+        assert_eq!(json, r#"{"left":1,"right":2}"#);
+
+        let pair: Pair = serde_json::from_str(&json).unwrap();
+        owner.swap(&pair.left, &pair.right);
+        let cell = TLCell::new(pair);
+        let json = serde_json::to_string(&SerializeWith::new(&owner, &cell)).unwrap();
+        assert_eq!(json, r#"{"left":2,"right":1}"#);
+        // The wrapper only makes the owner available whilst serializing
+        assert!(serde_json::to_string(owner.ro(&cell)).is_err());
+    }
+
+    #[test]
+    fn serde_owner_types_distinct() {
+        // A `TCellOwner` doesn't stand in for a `TLCellOwner` with the
+        // same marker type
+        let owner = TCellOwner::<Marker>::new();
+        let cell = TLCell::<Marker, u32>::new(1);
+        assert!(with_owner(&owner, || serde_json::to_string(&cell)).is_err());
+        assert_eq!(
+            with_owner(&owner, || serde_json::to_string(
+                &TCell::<Marker, u32>::new(2)
+            ))
+            .unwrap(),
+            "2"
+        );
+    }
+}
@@ -1,46 +1,576 @@
-use std::any::TypeId;
-use std::cell::UnsafeCell;
-use std::collections::HashSet;
-use std::marker::PhantomData;
+use core::cell::{Cell, RefCell, UnsafeCell};
+use core::cmp::Ordering;
+use core::convert::Infallible;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::panic::{Location, RefUnwindSafe, UnwindSafe};
+use core::pin::Pin;
+use core::sync::atomic::{self, AtomicU8};
 
+#[cfg(feature = "std")]
+use core::any::{Any, TypeId};
+#[cfg(feature = "std")]
+use core::cell::{BorrowMutError, Ref, RefMut};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet, VecDeque};
+#[cfg(feature = "std")]
+use std::rc::Weak;
+#[cfg(feature = "std")]
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::rc::Rc;
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "async")]
+use core::future::Future;
+#[cfg(feature = "async")]
+use core::task::{Context, Poll, Waker};
+
+use crate::traits::{GuardedCellOwner, GuardedCellReader, OptionCellExt};
+
+// Registry of the marker types that currently have an owner, plus a
+// FIFO queue of tickets per marker type for threads blocked in
+// `wait_for_new()` (or tasks pending in `new_async()`).  Whilst a
+// queue is non-empty, only the ticket at its front may claim the
+// marker, which stops `try_new()` callers or later waiters from
+// starving the earlier waiters.  In debug builds the location where
+// each current owner was created is also kept, for error messages.
+#[cfg(feature = "std")]
+struct Registry {
+    owners: HashSet<TypeId>,
+    #[cfg(debug_assertions)]
+    created_at: HashMap<TypeId, &'static Location<'static>>,
+    queues: HashMap<TypeId, VecDeque<u64>>,
+    next_ticket: u64,
+    #[cfg(feature = "async")]
+    wakers: HashMap<u64, Waker>,
+}
+
+#[cfg(feature = "std")]
+impl Registry {
+    fn can_claim(&self, id: TypeId, ticket: Option<u64>) -> bool {
+        !self.owners.contains(&id)
+            && self.queues.get(&id).and_then(|q| q.front()).copied() == ticket
+    }
+
+    #[cfg_attr(not(debug_assertions), allow(unused_variables))]
+    fn claim(&mut self, id: TypeId, location: &'static Location<'static>) {
+        self.owners.insert(id);
+        #[cfg(debug_assertions)]
+        self.created_at.insert(id, location);
+    }
+
+    fn release(&mut self, id: TypeId) {
+        self.owners.remove(&id);
+        #[cfg(debug_assertions)]
+        self.created_at.remove(&id);
+    }
+
+    fn duplicate_error<Q: 'static>(&self) -> DuplicateOwnerError {
+        #[cfg(debug_assertions)]
+        let first_created_at = self.created_at.get(&TypeId::of::<Q>()).copied();
+        #[cfg(not(debug_assertions))]
+        let first_created_at = None;
+        DuplicateOwnerError {
+            marker: core::any::type_name::<Q>(),
+            first_created_at,
+            thread_local: false,
+            // No owner exists, so the claim failed only because of
+            // queued waiters
+            reserved: !self.owners.contains(&TypeId::of::<Q>()),
+        }
+    }
+
+    fn enqueue(&mut self, id: TypeId) -> u64 {
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+        self.queues.entry(id).or_default().push_back(ticket);
+        ticket
+    }
+
+    fn dequeue(&mut self, id: TypeId, ticket: u64) {
+        #[cfg(feature = "async")]
+        self.wakers.remove(&ticket);
+        if let Some(queue) = self.queues.get_mut(&id) {
+            queue.retain(|t| *t != ticket);
+            if queue.is_empty() {
+                self.queues.remove(&id);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
 lazy_static! {
-    static ref SINGLETON_CHECK: std::sync::Mutex<HashSet<TypeId>> =
-        std::sync::Mutex::new(HashSet::new());
+    static ref SINGLETON_CHECK: Mutex<Registry> = Mutex::new(Registry {
+        owners: HashSet::new(),
+        #[cfg(debug_assertions)]
+        created_at: HashMap::new(),
+        queues: HashMap::new(),
+        next_ticket: 0,
+        #[cfg(feature = "async")]
+        wakers: HashMap::new(),
+    });
+    static ref SINGLETON_RELEASED: Condvar = Condvar::new();
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    // Owners shared through `new_or_share()` in this thread, each a
+    // `Weak<RefCell<TCellOwner<Q>>>` keyed by the `TypeId` of `Q`
+    static SHARED_OWNERS: RefCell<HashMap<TypeId, Weak<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+// The registry is only ever updated by single insert or remove calls
+// that leave it consistent, so if some thread panicked whilst holding
+// the lock, it is safe to carry on using it as it is.  Otherwise one
+// panic would make every later owner creation and drop panic too.
+#[cfg(feature = "std")]
+fn lock_registry() -> MutexGuard<'static, Registry> {
+    SINGLETON_CHECK
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+// Called when the waiter at the front of the queue for marker `id`
+// may now be able to claim it.  Any async waker is only woken after
+// the lock is released.
+#[cfg(feature = "std")]
+#[cfg_attr(not(feature = "async"), allow(unused_mut, unused_variables))]
+fn wake_waiters(mut reg: MutexGuard<'_, Registry>, id: TypeId) {
+    #[cfg(feature = "async")]
+    let waker = match reg.queues.get(&id).and_then(|q| q.front()).copied() {
+        Some(ticket) => reg.wakers.remove(&ticket),
+        None => None,
+    };
+    drop(reg);
+    SINGLETON_RELEASED.notify_all();
+    #[cfg(feature = "async")]
+    {
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
 }
 
 /// Borrowing-owner of zero or more [`TCell`](struct.TCell.html)
 /// instances.
 ///
+/// Auto traits: `Send`, `Sync`, `Unpin`, `UnwindSafe` and
+/// `RefUnwindSafe`, whatever the marker type.
+///
 /// See [crate documentation](index.html).
 pub struct TCellOwner<Q: 'static> {
-    // Allow Send and Sync
-    typ: PhantomData<Q>,
+    // Use *const so that the auto traits don't depend on the marker
+    // type, which is never instantiated.  `Send` and `Sync` are
+    // re-enabled below.
+    typ: PhantomData<*const Q>,
+    // What to release on drop
+    claim: Claim,
+}
+
+enum Claim {
+    // Created with `new_unchecked()`, so nothing to release
+    Unchecked,
+    // Entry in the registry
+    #[cfg(feature = "std")]
+    Registry,
+    // Flag of a `TCellMarker` type, claimed by `new_fast()`
+    Flag(&'static TCellMarkerFlag),
 }
 
 impl<Q: 'static> Drop for TCellOwner<Q> {
     fn drop(&mut self) {
-        SINGLETON_CHECK.lock().unwrap().remove(&TypeId::of::<Q>());
+        match self.claim {
+            Claim::Unchecked => (),
+            #[cfg(feature = "std")]
+            Claim::Registry => {
+                let id = TypeId::of::<Q>();
+                let mut reg = lock_registry();
+                reg.release(id);
+                wake_waiters(reg, id);
+            }
+            Claim::Flag(flag) => flag.state.store(FLAG_FREE, atomic::Ordering::Release),
+        }
     }
 }
 
+// The singleton check is process-wide, so moving an owner to another
+// thread can't ever result in two owners for the same marker type
+// co-existing.  The marker type itself is never instantiated, so
+// there is no need to require `Q: Send` either.
+unsafe impl<Q: 'static> Send for TCellOwner<Q> {}
+
+// Sharing `&TCellOwner` only allows read-only access, and reading a
+// cell from several threads at once also needs the cell to be shared,
+// which is limited by the `TCell` `Sync` implementation.  As for
+// `Send`, the marker type doesn't matter.
+unsafe impl<Q: 'static> Sync for TCellOwner<Q> {}
+
+// The owner has no state that a panic could leave half-updated, and
+// the marker type is never instantiated, so it doesn't matter whether
+// the marker type is unwind-safe.
+impl<Q: 'static> UnwindSafe for TCellOwner<Q> {}
+impl<Q: 'static> RefUnwindSafe for TCellOwner<Q> {}
+
+/// Creates the owner with [`TCellOwner::new`], so panics if another
+/// owner with the same marker type already exists.
+///
+/// [`TCellOwner::new`]: struct.TCellOwner.html#method.new
+#[cfg(feature = "std")]
 impl<Q: 'static> Default for TCellOwner<Q> {
+    #[track_caller]
     fn default() -> Self {
         TCellOwner::new()
     }
 }
 
+impl<Q: 'static> fmt::Debug for TCellOwner<Q> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TCellOwner")
+            .field("marker", &core::any::type_name::<Q>())
+            .finish()
+    }
+}
+
 impl<Q: 'static> TCellOwner<Q> {
     /// Create the singleton owner instance.  Each owner may be used
     /// to create many `TCell` instances.  There may be only one
     /// instance of this type per process at any given time for each
     /// different marker type `Q`.  This call panics if a second
-    /// simultaneous instance is created.
+    /// simultaneous instance is created.  It also panics if no
+    /// instance exists but the marker type is reserved for other
+    /// callers queued in [`wait_for_new`](#method.wait_for_new) or
+    /// `new_async`.  If you need to handle these cases, use
+    /// [`try_new`](#method.try_new) instead.
+    ///
+    /// The panic message names the marker type, and in debug builds
+    /// also gives the location where the existing owner was created.
+    #[cfg(feature = "std")]
+    #[track_caller]
     pub fn new() -> Self {
-        assert!(
-            SINGLETON_CHECK.lock().unwrap().insert(TypeId::of::<Q>()),
-            "Illegal to create two TCellOwner instances with the same marker type parameter"
+        match Self::try_new() {
+            Ok(owner) => owner,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Attempt to create the singleton owner instance.  This works
+    /// the same as [`new`](#method.new), except that if another
+    /// instance with the same marker type `Q` already exists
+    /// somewhere in the process, a [`DuplicateOwnerError`] is returned
+    /// instead of panicking.  In that case nothing is registered, so
+    /// a later call may succeed once the other owner is dropped.
+    /// This also fails if other threads are already queued in
+    /// [`wait_for_new`](#method.wait_for_new) for the same marker
+    /// type, since they have priority, even in the window after the
+    /// previous owner is dropped and before the first waiter takes
+    /// over.  The error then reports
+    /// [`is_reserved`](struct.DuplicateOwnerError.html#method.is_reserved)
+    /// as true.
+    ///
+    /// [`DuplicateOwnerError`]: struct.DuplicateOwnerError.html
+    #[cfg(feature = "std")]
+    #[track_caller]
+    pub fn try_new() -> Result<Self, DuplicateOwnerError> {
+        let id = TypeId::of::<Q>();
+        let mut reg = lock_registry();
+        if reg.can_claim(id, None) {
+            reg.claim(id, Location::caller());
+            Ok(Self::registered())
+        } else {
+            Err(reg.duplicate_error::<Q>())
+        }
+    }
+
+    /// Test whether an owner with marker type `Q` currently exists
+    /// anywhere in the process.  This does not change the registry.
+    /// Since another thread may create or drop an owner at any
+    /// moment, the result may already be out of date by the time it
+    /// is returned, so it is only advisory.  Use
+    /// [`try_new`](#method.try_new) to actually claim the marker
+    /// type.  Owners created with
+    /// [`new_unchecked`](#method.new_unchecked) are not registered
+    /// and so are not seen by this call.
+    ///
+    /// Once [`new_fast`](#method.new_fast) or
+    /// [`try_new_fast`](#method.try_new_fast) has been called for a
+    /// marker type, that marker type stays reserved in the registry
+    /// permanently, so this returns `true` from then on, even when
+    /// no fast owner currently exists.
+    #[cfg(feature = "std")]
+    pub fn is_active() -> bool {
+        lock_registry().owners.contains(&TypeId::of::<Q>())
+    }
+
+    /// Release the registration of marker type `Q`, so that a new
+    /// owner can be created even though the old one was never
+    /// dropped, for example because it was passed to `mem::forget` or
+    /// leaked.  This is intended for recovering in test harnesses or
+    /// long-running processes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee that any existing owner with marker
+    /// type `Q` will never be used again, from any thread.  Its drop
+    /// must also never run, since that would release the
+    /// registration of whatever owner replaces it.  This must not be
+    /// used at all with marker types used with
+    /// [`new_fast`](#method.new_fast), since the registry then holds a
+    /// permanent reservation.
+    #[cfg(feature = "std")]
+    pub unsafe fn force_release() {
+        let id = TypeId::of::<Q>();
+        let mut reg = lock_registry();
+        reg.release(id);
+        wake_waiters(reg, id);
+    }
+
+    /// Create the singleton owner instance and pass it to the given
+    /// closure, returning the closure's result.  The owner is dropped
+    /// when the closure returns or panics, so the marker type `Q` is
+    /// always released again afterwards.  Panics if another owner
+    /// with the same marker type already exists, like
+    /// [`new`](#method.new).
+    #[cfg(feature = "std")]
+    #[track_caller]
+    pub fn scope<R, F>(f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let mut owner = Self::new();
+        f(&mut owner)
+    }
+
+    /// Works the same as [`scope`](#method.scope), except that if
+    /// another owner with the same marker type `Q` already exists,
+    /// the closure is not called and an error is returned instead.
+    #[cfg(feature = "std")]
+    #[track_caller]
+    pub fn try_scope<R, F>(f: F) -> Result<R, DuplicateOwnerError>
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let mut owner = Self::try_new()?;
+        Ok(f(&mut owner))
+    }
+
+    /// Create an owner instance without checking or updating the
+    /// singleton registry.  This avoids the cost of the registry
+    /// lookup, so may be useful in performance-critical code.  The
+    /// returned owner leaves the registry untouched when it is
+    /// dropped, so any owner created with [`new`](#method.new) is
+    /// still tracked correctly.
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee that no other owner with the same
+    /// marker type `Q` exists anywhere in the process for as long as
+    /// the returned owner exists, whether created through this call
+    /// or through any of the checked constructors.  Otherwise two
+    /// owners could give out mutable references to the same cell
+    /// contents at the same time.
+    #[inline]
+    pub const unsafe fn new_unchecked() -> Self {
+        Self {
+            typ: PhantomData,
+            claim: Claim::Unchecked,
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    const fn registered() -> Self {
+        Self {
+            typ: PhantomData,
+            claim: Claim::Registry,
+        }
+    }
+
+    /// Create the singleton owner instance for a marker type defined
+    /// with [`tcell_marker!`], panicking if another owner with the
+    /// same marker type already exists.  This claims a flag belonging
+    /// to the marker type with a single atomic operation, avoiding
+    /// the hashing and locking of the registry used by
+    /// [`new`](#method.new).
+    ///
+    /// Each marker type should be used with either the fast calls or
+    /// the registry-based calls, not both.  The first call to
+    /// `new_fast` or `try_new_fast` for a marker type reserves it in
+    /// the registry permanently, so from then on `new`, `try_new`,
+    /// `wait_for_new` and so on will always fail for that marker
+    /// type, even when no fast owner currently exists.
+    ///
+    /// [`tcell_marker!`]: macro.tcell_marker.html
+    #[inline]
+    #[track_caller]
+    pub fn new_fast() -> Self
+    where
+        Q: TCellMarker,
+    {
+        match Self::try_new_fast() {
+            Ok(owner) => owner,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Works the same as [`new_fast`](#method.new_fast), except that
+    /// an error is returned instead of panicking if another owner
+    /// with the same marker type already exists.
+    #[inline]
+    pub fn try_new_fast() -> Result<Self, DuplicateOwnerError>
+    where
+        Q: TCellMarker,
+    {
+        let flag = Q::flag();
+        loop {
+            match flag.state.compare_exchange(
+                FLAG_FREE,
+                FLAG_OWNED,
+                atomic::Ordering::Acquire,
+                atomic::Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    return Ok(Self {
+                        typ: PhantomData,
+                        claim: Claim::Flag(flag),
+                    })
+                }
+                Err(FLAG_UNLINKED) => Self::link_flag(flag)?,
+                Err(_) => {
+                    return Err(DuplicateOwnerError {
+                        marker: core::any::type_name::<Q>(),
+                        first_created_at: None,
+                        thread_local: false,
+                        reserved: false,
+                    })
+                }
+            }
+        }
+    }
+
+    // First use of a flag: reserve the marker type in the registry
+    // for good, so that registry-based owners can't co-exist with
+    // flag-based ones
+    #[cfg(feature = "std")]
+    #[cold]
+    fn link_flag(flag: &'static TCellMarkerFlag) -> Result<(), DuplicateOwnerError> {
+        let id = TypeId::of::<Q>();
+        let mut reg = lock_registry();
+        if flag.state.load(atomic::Ordering::Relaxed) == FLAG_UNLINKED {
+            if !reg.can_claim(id, None) {
+                return Err(reg.duplicate_error::<Q>());
+            }
+            reg.owners.insert(id);
+            flag.state.store(FLAG_FREE, atomic::Ordering::Release);
+        }
+        Ok(())
+    }
+
+    // Without `std` there is no registry, so the flag alone is the
+    // singleton check
+    #[cfg(not(feature = "std"))]
+    #[cold]
+    fn link_flag(flag: &'static TCellMarkerFlag) -> Result<(), DuplicateOwnerError> {
+        let _ = flag.state.compare_exchange(
+            FLAG_UNLINKED,
+            FLAG_FREE,
+            atomic::Ordering::Relaxed,
+            atomic::Ordering::Relaxed,
         );
-        Self { typ: PhantomData }
+        Ok(())
+    }
+
+    /// Create the singleton owner instance, blocking the current
+    /// thread until any other owner with the same marker type `Q` has
+    /// been dropped.  Threads waiting on the same marker type are
+    /// served in FIFO order, and each dropped owner hands the slot to
+    /// exactly one waiter.
+    ///
+    /// This will deadlock if the existing owner belongs to the
+    /// calling thread and can only be dropped by it.  (For that
+    /// reason there is no equivalent on `TLCellOwner`, since there
+    /// the only owner it could wait for is in the same thread.)
+    #[cfg(feature = "std")]
+    #[track_caller]
+    pub fn wait_for_new() -> Self {
+        Self::wait_impl(None).unwrap()
+    }
+
+    /// Create the singleton owner instance, blocking the current
+    /// thread for up to `timeout` until any other owner with the same
+    /// marker type `Q` has been dropped.  Returns `None` if the
+    /// timeout expires first.  See
+    /// [`wait_for_new`](#method.wait_for_new).
+    #[cfg(feature = "std")]
+    #[track_caller]
+    pub fn wait_for_new_timeout(timeout: Duration) -> Option<Self> {
+        Self::wait_impl(Some(Instant::now() + timeout))
+    }
+
+    #[cfg(feature = "std")]
+    #[track_caller]
+    fn wait_impl(deadline: Option<Instant>) -> Option<Self> {
+        let id = TypeId::of::<Q>();
+        let location = Location::caller();
+        let mut reg = lock_registry();
+        if reg.can_claim(id, None) {
+            reg.claim(id, location);
+            return Some(Self::registered());
+        }
+        let ticket = reg.enqueue(id);
+        loop {
+            if reg.can_claim(id, Some(ticket)) {
+                reg.dequeue(id, ticket);
+                reg.claim(id, location);
+                return Some(Self::registered());
+            }
+            reg = match deadline {
+                None => SINGLETON_RELEASED
+                    .wait(reg)
+                    .unwrap_or_else(PoisonError::into_inner),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        reg.dequeue(id, ticket);
+                        wake_waiters(reg, id);
+                        return None;
+                    }
+                    SINGLETON_RELEASED
+                        .wait_timeout(reg, deadline - now)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+            };
+        }
+    }
+
+    /// Create the singleton owner instance asynchronously.  The
+    /// returned future completes once any other owner with the same
+    /// marker type `Q` has been dropped.  Pending futures are queued
+    /// in FIFO order together with threads blocked in
+    /// [`wait_for_new`](#method.wait_for_new).  Dropping the future
+    /// before it completes removes it from the queue.
+    ///
+    /// Requires the `async` cargo feature.
+    #[cfg(feature = "async")]
+    #[track_caller]
+    pub fn new_async() -> TCellOwnerFuture<Q> {
+        TCellOwnerFuture {
+            ticket: None,
+            location: Location::caller(),
+            typ: PhantomData,
+        }
     }
 
     /// Create a new cell owned by this owner instance.  See also
@@ -51,95 +581,2270 @@ impl<Q: 'static> TCellOwner<Q> {
         TCell::<Q, T>::new(value)
     }
 
+    /// Create a vector of new cells owned by this owner instance, one
+    /// for each value returned by the iterator.
+    #[cfg(feature = "alloc")]
+    pub fn cells_from_iter<T>(&self, values: impl IntoIterator<Item = T>) -> Vec<TCell<Q, T>> {
+        values.into_iter().map(TCell::new).collect()
+    }
+
+    /// Convert a vector of values into a vector of new cells owned by
+    /// this owner instance, without copying.  See
+    /// [`TCell::wrap_vec`].
+    ///
+    /// [`TCell::wrap_vec`]: struct.TCell.html#method.wrap_vec
+    #[cfg(feature = "alloc")]
+    pub fn cells_from_vec<T>(&self, values: Vec<T>) -> Vec<TCell<Q, T>> {
+        TCell::wrap_vec(values)
+    }
+
+    /// Create an array of new cells owned by this owner instance,
+    /// containing the results of calling the closure with each index
+    /// in turn, as for `std::array::from_fn`.
+    pub fn cell_array<T, const N: usize>(&self, f: impl FnMut(usize) -> T) -> [TCell<Q, T>; N] {
+        TCell::new_array(core::array::from_fn(f))
+    }
+
     /// Borrow contents of a `TCell` immutably (read-only).  Many
     /// `TCell` instances can be borrowed immutably at the same time
     /// from the same owner.
     #[inline]
-    pub fn ro<'a, T>(&'a self, tc: &'a TCell<Q, T>) -> &'a T {
+    pub fn ro<'a, T: ?Sized>(&'a self, tc: &'a TCell<Q, T>) -> &'a T {
         unsafe { &*tc.value.get() }
     }
 
+    /// Borrow the contents of a slice of `TCell` instances immutably,
+    /// as a plain slice.  This is the owner-checked way to go from
+    /// `&[TCell<Q, T>]` to `&[T]`, for example to pass the contents
+    /// to code that works on plain slices.  For mutable access, use
+    /// `owner.rw(TCell::from_slice_of_cells(cells))`.
+    #[inline]
+    pub fn ro_slice<'a, T>(&'a self, cells: &'a [TCell<Q, T>]) -> &'a [T] {
+        self.ro(TCell::from_slice_of_cells(cells))
+    }
+
+    /// Get a copy of the contents of a `TCell`.  This is a shorthand
+    /// for `*owner.ro(&cell)`, and also works on `Rc<TCell<..>>` and
+    /// similar by auto-deref, e.g. `owner.get(&rc)`.
+    #[inline]
+    pub fn get<T: Copy>(&self, tc: &TCell<Q, T>) -> T {
+        *self.ro(tc)
+    }
+
+    /// Get a clone of the contents of a `TCell`.  This is a
+    /// shorthand for `owner.ro(&cell).clone()`, and also works on
+    /// `Rc<TCell<..>>` and similar by auto-deref, e.g.
+    /// `owner.cloned(&rc)`.
+    #[inline]
+    pub fn cloned<T: Clone>(&self, tc: &TCell<Q, T>) -> T {
+        self.ro(tc).clone()
+    }
+
+    /// Create a new `TCell` containing a clone of the contents of the
+    /// given cell.  See [`TCell::clone_with`].
+    ///
+    /// [`TCell::clone_with`]: struct.TCell.html#method.clone_with
+    #[inline]
+    pub fn clone_cell<T: Clone>(&self, tc: &TCell<Q, T>) -> TCell<Q, T> {
+        TCell::new(self.cloned(tc))
+    }
+
+    /// Compare the contents of two `TCell` instances for equality.
+    /// Both may be the same cell.  This is convenient in closures, for
+    /// example `cells.dedup_by(|a, b| owner.eq(a, b))`.
+    #[inline]
+    pub fn eq<T: PartialEq + ?Sized>(&self, tc1: &TCell<Q, T>, tc2: &TCell<Q, T>) -> bool {
+        self.ro(tc1) == self.ro(tc2)
+    }
+
+    /// Compare the contents of two `TCell` instances, for example
+    /// `cells.sort_by(|a, b| owner.cmp(a, b))`.
+    #[inline]
+    pub fn cmp<T: Ord + ?Sized>(&self, tc1: &TCell<Q, T>, tc2: &TCell<Q, T>) -> Ordering {
+        self.ro(tc1).cmp(self.ro(tc2))
+    }
+
+    /// Compare the contents of two `TCell` instances, where the
+    /// contained type only has a partial order.
+    #[inline]
+    pub fn partial_cmp<T: PartialOrd + ?Sized>(
+        &self,
+        tc1: &TCell<Q, T>,
+        tc2: &TCell<Q, T>,
+    ) -> Option<Ordering> {
+        self.ro(tc1).partial_cmp(self.ro(tc2))
+    }
+
+    /// Feed the contents of a `TCell` into the given hasher, giving
+    /// the same result as hashing the contents directly.
+    #[inline]
+    pub fn hash_cell<T: Hash + ?Sized, H: Hasher>(&self, tc: &TCell<Q, T>, state: &mut H) {
+        self.ro(tc).hash(state)
+    }
+
+    /// Sort a slice of cells by the contents, using the given
+    /// comparison function, for example a `Vec<Rc<TCell<Q, T>>>`.
+    /// See [`AsCellRef`] for the supported ways of holding a cell.
+    /// Only the cell references are moved, not the contents.  This
+    /// uses `slice::sort_by`, so the sort is stable.
+    ///
+    /// [`AsCellRef`]: trait.AsCellRef.html
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn sort_cells_by<C: AsCellRef<Q>>(
+        &self,
+        cells: &mut [C],
+        mut compare: impl FnMut(&C::Value, &C::Value) -> Ordering,
+    ) {
+        cells.sort_by(|a, b| compare(self.ro(a.as_cell_ref()), self.ro(b.as_cell_ref())));
+    }
+
+    /// Sort a slice of cells by a key extracted from the contents.
+    /// This uses `slice::sort_by_key`, so the sort is stable.  See
+    /// [`sort_cells_by`](#method.sort_cells_by).
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn sort_cells_by_key<C: AsCellRef<Q>, K: Ord>(
+        &self,
+        cells: &mut [C],
+        mut f: impl FnMut(&C::Value) -> K,
+    ) {
+        cells.sort_by_key(|c| f(self.ro(c.as_cell_ref())));
+    }
+
+    /// Binary search a slice of cells, sorted by a key extracted from
+    /// the contents, for the given key.  The result is as for
+    /// `slice::binary_search_by_key`.
+    #[inline]
+    pub fn binary_search_cells_by_key<C: AsCellRef<Q>, K: Ord>(
+        &self,
+        cells: &[C],
+        key: &K,
+        mut f: impl FnMut(&C::Value) -> K,
+    ) -> Result<usize, usize> {
+        cells.binary_search_by_key(key, |c| f(self.ro(c.as_cell_ref())))
+    }
+
+    /// Get an adapter that formats the contents of a `TCell` with
+    /// `Debug`, for example `println!("{:?}", owner.debug(&cell))`.
+    /// The adapter borrows the owner immutably until it is dropped,
+    /// and can be embedded in other formatted output.
+    #[inline]
+    pub fn debug<'a, T: fmt::Debug + ?Sized>(
+        &'a self,
+        tc: &'a TCell<Q, T>,
+    ) -> impl fmt::Debug + 'a {
+        Formatted { owner: self, tc }
+    }
+
+    /// Get an adapter that formats the contents of a `TCell` with
+    /// `Display`, for example `println!("{}", owner.display(&cell))`.
+    /// See [`debug`](#method.debug).
+    #[inline]
+    pub fn display<'a, T: fmt::Display + ?Sized>(
+        &'a self,
+        tc: &'a TCell<Q, T>,
+    ) -> impl fmt::Display + 'a {
+        Formatted { owner: self, tc }
+    }
+
+    /// Pass a shared reference to the contents of a `TCell` to the
+    /// given closure, returning the closure's result.  The borrow is
+    /// scoped to the closure, so the owner is free again as soon as
+    /// the call returns.
+    #[inline]
+    pub fn read<T: ?Sized, R>(&self, tc: &TCell<Q, T>, f: impl FnOnce(&T) -> R) -> R {
+        f(self.ro(tc))
+    }
+
+    /// Get a [`ReadToken`] for this owner.  The token is `Copy`,
+    /// `Send` and `Sync`, so copies of it may be handed to scoped
+    /// threads to read `TCell` contents in parallel.  The token
+    /// borrows the owner immutably, so no `rw` borrow can happen
+    /// whilst any copy of it is alive.
+    ///
+    /// [`ReadToken`]: struct.ReadToken.html
+    #[inline]
+    pub fn read_token(&self) -> ReadToken<'_, Q> {
+        ReadToken {
+            owner: PhantomData,
+            typ: PhantomData,
+        }
+    }
+
+    /// Get a handle to the owner for marker type `Q` shared within
+    /// the current thread.  If an owner created by this call already
+    /// exists in this thread, another handle to it is returned.
+    /// Otherwise a new owner is created, which panics if some other
+    /// owner with the same marker type exists, like
+    /// [`new`](#method.new).  The owner is dropped and the marker type
+    /// released when the last handle is dropped.
+    ///
+    /// This allows independent code in the same thread to share an
+    /// owner without having to pass it around.  However, since the
+    /// handles alias the same owner, mutable access is checked at
+    /// runtime, using [`SharedTCellOwner::borrow_mut`].
+    ///
+    /// [`SharedTCellOwner::borrow_mut`]: struct.SharedTCellOwner.html#method.borrow_mut
+    #[cfg(feature = "std")]
+    #[track_caller]
+    pub fn new_or_share() -> SharedTCellOwner<Q> {
+        let id = TypeId::of::<Q>();
+        let existing = SHARED_OWNERS.with(|map| map.borrow().get(&id).and_then(Weak::upgrade));
+        if let Some(owner) = existing {
+            if let Ok(owner) = owner.downcast::<RefCell<TCellOwner<Q>>>() {
+                return SharedTCellOwner { owner };
+            }
+        }
+        let owner = Rc::new(RefCell::new(Self::new()));
+        let weak = Rc::downgrade(&owner) as Weak<dyn Any>;
+        SHARED_OWNERS.with(|map| map.borrow_mut().insert(id, weak));
+        SharedTCellOwner { owner }
+    }
+
+    /// Convert this owner into a [`FrozenTCellOwner`], which only
+    /// allows read-only access, but which can be cloned and shared
+    /// between threads.  The marker type `Q` stays claimed the whole
+    /// time, so no other owner can be created until the last clone is
+    /// dropped.  Use [`FrozenTCellOwner::thaw`] to get mutable access
+    /// back again.
+    ///
+    /// [`FrozenTCellOwner`]: struct.FrozenTCellOwner.html
+    /// [`FrozenTCellOwner::thaw`]: struct.FrozenTCellOwner.html#method.thaw
+    #[cfg(feature = "std")]
+    pub fn freeze(self) -> FrozenTCellOwner<Q> {
+        FrozenTCellOwner {
+            owner: Arc::new(self),
+        }
+    }
+
     /// Borrow contents of a `TCell` mutably (read-write).  Only one
     /// `TCell` at a time can be borrowed from the owner using this
     /// call.  The returned reference must go out of scope before
     /// another can be borrowed.
     #[inline]
-    pub fn rw<'a, T>(&'a mut self, tc: &'a TCell<Q, T>) -> &'a mut T {
+    pub fn rw<'a, T: ?Sized>(&'a mut self, tc: &'a TCell<Q, T>) -> &'a mut T {
         unsafe { &mut *tc.value.get() }
     }
 
+    /// Borrow contents of a `TCell` immutably, as for
+    /// [`ro`](#method.ro).  The marker type guarantees at compile
+    /// time that the owner matches the cell, so this can't fail, and
+    /// the error type is uninhabited.  It exists so that code written
+    /// against `QCellOwner::try_ro` can be switched over unchanged.
+    #[inline]
+    pub fn try_ro<'a, T: ?Sized>(&'a self, tc: &'a TCell<Q, T>) -> Result<&'a T, Infallible> {
+        Ok(self.ro(tc))
+    }
+
+    /// Borrow contents of a `TCell` mutably, as for
+    /// [`rw`](#method.rw).  This can't fail.  See
+    /// [`try_ro`](#method.try_ro).
+    #[inline]
+    pub fn try_rw<'a, T: ?Sized>(
+        &'a mut self,
+        tc: &'a TCell<Q, T>,
+    ) -> Result<&'a mut T, Infallible> {
+        Ok(self.rw(tc))
+    }
+
+    /// Borrow the contents of a pinned `TCell` immutably, as a pinned
+    /// reference.  See [`rw_pin`](#method.rw_pin) for the safety
+    /// requirements.
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee that the contents of the cell are
+    /// never moved again until they are dropped, as for `rw_pin`.
+    #[inline]
+    pub unsafe fn ro_pin<'a, T: ?Sized>(&'a self, tc: Pin<&'a TCell<Q, T>>) -> Pin<&'a T> {
+        Pin::new_unchecked(self.ro(tc.get_ref()))
+    }
+
+    /// Borrow the contents of a pinned `TCell` mutably, as a pinned
+    /// mutable reference.  This allows for example polling a future
+    /// stored in a `Pin<Rc<TCell<Q, F>>>`.
+    ///
+    /// The cell never moves its contents by itself, so pinning the
+    /// cell could pin the contents.  However a `Pin<&TCell>` still
+    /// derefs to a plain `&TCell`, and that allows the contents to be
+    /// moved out through the owner, using [`rw`](#method.rw) and
+    /// `mem::swap`, or [`replace`](#method.replace), [`take`] or
+    /// [`swap`].  Since this can't be prevented without `T: Unpin`
+    /// bounds on all of those calls, the pinning guarantee is left to
+    /// the caller.  For `T: Unpin` there is nothing to guarantee, and
+    /// `Pin::new(owner.rw(&cell))` may be used instead.
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee that once this has been called, the
+    /// contents of the cell are never moved again until they are
+    /// dropped, e.g. by not using any of the calls above on the same
+    /// cell, and not using `TCell::into_inner` or `TCell::get_mut` on
+    /// it.
+    ///
+    /// [`take`]: #method.take
+    /// [`swap`]: #method.swap
+    #[inline]
+    pub unsafe fn rw_pin<'a, T: ?Sized>(&'a mut self, tc: Pin<&'a TCell<Q, T>>) -> Pin<&'a mut T> {
+        Pin::new_unchecked(self.rw(tc.get_ref()))
+    }
+
+    /// Set the contents of a `TCell`, dropping the old value.  This
+    /// is a shorthand for `*owner.rw(&cell) = value`.
+    #[inline]
+    pub fn set<T>(&mut self, tc: &TCell<Q, T>, value: T) {
+        *self.rw(tc) = value;
+    }
+
+    /// Replace the contents of a `TCell`, returning the old value.
+    #[inline]
+    pub fn replace<T>(&mut self, tc: &TCell<Q, T>, value: T) -> T {
+        core::mem::replace(self.rw(tc), value)
+    }
+
+    /// Take the contents of a `TCell`, leaving `Default::default()`
+    /// in its place.
+    #[inline]
+    pub fn take<T: Default>(&mut self, tc: &TCell<Q, T>) -> T {
+        core::mem::take(self.rw(tc))
+    }
+
+    /// Initialize the contents of a `TCell` created with
+    /// [`TCell::new_uninit`], returning a mutable reference to the
+    /// value.  Any previous contents are overwritten without being
+    /// dropped, as for `MaybeUninit::write`.
+    ///
+    /// [`TCell::new_uninit`]: struct.TCell.html#method.new_uninit
+    #[inline]
+    pub fn write<'a, T>(&'a mut self, tc: &'a TCell<Q, MaybeUninit<T>>, value: T) -> &'a mut T {
+        self.rw(tc).write(value)
+    }
+
+    /// Pass a mutable reference to the contents of a `TCell` to the
+    /// given closure, returning the closure's result.  The borrow is
+    /// scoped to the closure, so several updates can be chained one
+    /// after another without needing to drop `rw` references
+    /// explicitly.
+    #[inline]
+    pub fn update<T: ?Sized, R>(&mut self, tc: &TCell<Q, T>, f: impl FnOnce(&mut T) -> R) -> R {
+        f(self.rw(tc))
+    }
+
+    /// Pass mutable references to the contents of two `TCell`
+    /// instances to the given closure, returning the closure's
+    /// result, for example `owner.update2(&c1, &c2, |a, b| a.append(b))`.
+    /// As for [`update`](#method.update), the borrows end when the
+    /// closure returns.  Panics if the two `TCell` instances point to
+    /// the same memory, as for [`rw2`](#method.rw2).
+    #[inline]
+    pub fn update2<T: ?Sized, U: ?Sized, R>(
+        &mut self,
+        tc1: &TCell<Q, T>,
+        tc2: &TCell<Q, U>,
+        f: impl FnOnce(&mut T, &mut U) -> R,
+    ) -> R {
+        let (v1, v2) = self.rw2(tc1, tc2);
+        f(v1, v2)
+    }
+
+    /// Replace the contents of `dst` with a clone of the contents of
+    /// `src`, using `clone_from` so that existing allocations in
+    /// `dst` may be reused.  If both references are to the same
+    /// `TCell`, this does nothing.  Panics if the two cells otherwise
+    /// overlap.
+    #[inline]
+    pub fn clone_into<T: Clone>(&mut self, src: &TCell<Q, T>, dst: &TCell<Q, T>) {
+        if !core::ptr::eq(src, dst) {
+            let (dst, src) = self.rw_ro1(dst, src);
+            dst.clone_from(src);
+        }
+    }
+
+    /// Move the contents of `src` into `dst`, leaving
+    /// `Default::default()` in `src`.  The old contents of `dst` are
+    /// dropped.  If both references are to the same `TCell`, this
+    /// does nothing, rather than resetting the contents.  Panics if
+    /// the two cells otherwise overlap.
+    #[inline]
+    pub fn move_into<T: Default>(&mut self, src: &TCell<Q, T>, dst: &TCell<Q, T>) {
+        if !core::ptr::eq(src, dst) {
+            let (src, dst) = self.rw2(src, dst);
+            *dst = core::mem::take(src);
+        }
+    }
+
+    /// Swap the contents of two `TCell` instances.  If both
+    /// references are to the same `TCell`, this does nothing, as for
+    /// `Cell::swap`.  Panics if the two cells otherwise overlap.
+    #[inline]
+    pub fn swap<T>(&mut self, tc1: &TCell<Q, T>, tc2: &TCell<Q, T>) {
+        if !core::ptr::eq(tc1, tc2) {
+            let (v1, v2) = self.rw2(tc1, tc2);
+            core::mem::swap(v1, v2);
+        }
+    }
+
     /// Borrow contents of two `TCell` instances mutably.  Panics if
     /// the two `TCell` instances point to the same memory.
     #[inline]
-    pub fn rw2<'a, T, U>(
+    pub fn rw2<'a, T: ?Sized, U: ?Sized>(
         &'a mut self,
         tc1: &'a TCell<Q, T>,
         tc2: &'a TCell<Q, U>,
     ) -> (&'a mut T, &'a mut U) {
-        assert!(
-            tc1 as *const _ as usize != tc2 as *const _ as usize,
-            "Illegal to borrow same TCell twice with rw2()"
-        );
-        unsafe { (&mut *tc1.value.get(), &mut *tc2.value.get()) }
+        match self.try_rw2(tc1, tc2) {
+            Ok(refs) => refs,
+            Err(_) => panic!("Illegal to borrow same TCell twice with rw2()"),
+        }
+    }
+
+    /// Borrow contents of two `TCell` instances mutably.  Returns an
+    /// error if the two `TCell` instances point to the same memory.
+    #[inline]
+    pub fn try_rw2<'a, T: ?Sized, U: ?Sized>(
+        &'a mut self,
+        tc1: &'a TCell<Q, T>,
+        tc2: &'a TCell<Q, U>,
+    ) -> Result<(&'a mut T, &'a mut U), AliasError> {
+        self.try_rw_n((tc1, tc2))
     }
 
     /// Borrow contents of three `TCell` instances mutably.  Panics if
     /// any pair of `TCell` instances point to the same memory.
     #[inline]
-    pub fn rw3<'a, T, U, V>(
+    pub fn rw3<'a, T: ?Sized, U: ?Sized, V: ?Sized>(
         &'a mut self,
         tc1: &'a TCell<Q, T>,
         tc2: &'a TCell<Q, U>,
         tc3: &'a TCell<Q, V>,
     ) -> (&'a mut T, &'a mut U, &'a mut V) {
-        assert!(
-            (tc1 as *const _ as usize != tc2 as *const _ as usize)
-                && (tc2 as *const _ as usize != tc3 as *const _ as usize)
-                && (tc3 as *const _ as usize != tc1 as *const _ as usize),
-            "Illegal to borrow same TCell twice with rw3()"
-        );
-        unsafe {
-            (
-                &mut *tc1.value.get(),
-                &mut *tc2.value.get(),
-                &mut *tc3.value.get(),
-            )
+        match self.try_rw3(tc1, tc2, tc3) {
+            Ok(refs) => refs,
+            Err(_) => panic!("Illegal to borrow same TCell twice with rw3()"),
         }
     }
-}
 
-/// Cell whose contents is owned (for borrowing purposes) by a
-/// [`TCellOwner`].
-///
-/// To borrow from this cell, use the borrowing calls on the
-/// [`TCellOwner`] instance that shares the same marker type.
-///
-/// See also [crate documentation](index.html).
-///
-/// [`TCellOwner`]: struct.TCellOwner.html
-pub struct TCell<Q, T> {
-    // Use *const to disable Send and Sync, which are then re-enabled
-    // below under certain conditions
-    owner: PhantomData<*const Q>,
-    value: UnsafeCell<T>,
-}
+    /// Borrow contents of three `TCell` instances mutably.  Returns an
+    /// error if any pair of `TCell` instances point to the same
+    /// memory.
+    #[inline]
+    pub fn try_rw3<'a, T: ?Sized, U: ?Sized, V: ?Sized>(
+        &'a mut self,
+        tc1: &'a TCell<Q, T>,
+        tc2: &'a TCell<Q, U>,
+        tc3: &'a TCell<Q, V>,
+    ) -> Result<(&'a mut T, &'a mut U, &'a mut V), AliasError> {
+        self.try_rw_n((tc1, tc2, tc3))
+    }
 
-impl<Q, T> TCell<Q, T> {
-    /// Create a new `TCell` owned for borrowing purposes by the
-    /// `TCellOwner` derived from the same marker type `Q`.
+    /// Borrow contents of one `TCell` mutably and another immutably.
+    /// Panics if the two `TCell` instances point to the same memory.
+    /// See [`rw_ro2`](#method.rw_ro2).
     #[inline]
-    pub const fn new(value: T) -> TCell<Q, T> {
-        TCell {
-            owner: PhantomData,
-            value: UnsafeCell::new(value),
+    pub fn rw_ro1<'a, T: ?Sized, U: ?Sized>(
+        &'a mut self,
+        w: &'a TCell<Q, T>,
+        r1: &'a TCell<Q, U>,
+    ) -> (&'a mut T, &'a U) {
+        match self.try_rw_ro1(w, r1) {
+            Ok(refs) => refs,
+            Err(e) => rw_ro_panic("rw_ro1", e),
         }
     }
-}
 
-// It's fine to Send a TCell to a different thread if the containted
-// type is Send, because you can only send something if nothing
-// borrows it, so nothing can be accessing its contents.
-unsafe impl<Q, T: Send> Send for TCell<Q, T> {}
+    /// Borrow contents of one `TCell` mutably and another immutably.
+    /// Returns an error if the two `TCell` instances point to the
+    /// same memory.
+    #[inline]
+    pub fn try_rw_ro1<'a, T: ?Sized, U: ?Sized>(
+        &'a mut self,
+        w: &'a TCell<Q, T>,
+        r1: &'a TCell<Q, U>,
+    ) -> Result<(&'a mut T, &'a U), AliasError> {
+        check_write_alias(&[w.overlaps(r1)])?;
+        unsafe { Ok((&mut *w.value.get(), &*r1.value.get())) }
+    }
+
+    /// Borrow contents of one `TCell` mutably and two others
+    /// immutably, for example to update a node whilst reading its
+    /// neighbours.  The read-only cells may be the same as each
+    /// other, but panics if either of them points to the same memory
+    /// as the mutably-borrowed cell, naming the argument that clashes.
+    #[inline]
+    pub fn rw_ro2<'a, T: ?Sized, U: ?Sized, V: ?Sized>(
+        &'a mut self,
+        w: &'a TCell<Q, T>,
+        r1: &'a TCell<Q, U>,
+        r2: &'a TCell<Q, V>,
+    ) -> (&'a mut T, &'a U, &'a V) {
+        match self.try_rw_ro2(w, r1, r2) {
+            Ok(refs) => refs,
+            Err(e) => rw_ro_panic("rw_ro2", e),
+        }
+    }
+
+    /// Borrow contents of one `TCell` mutably and two others
+    /// immutably.  Returns an error if either of the read-only cells
+    /// points to the same memory as the mutably-borrowed cell.
+    #[inline]
+    pub fn try_rw_ro2<'a, T: ?Sized, U: ?Sized, V: ?Sized>(
+        &'a mut self,
+        w: &'a TCell<Q, T>,
+        r1: &'a TCell<Q, U>,
+        r2: &'a TCell<Q, V>,
+    ) -> Result<(&'a mut T, &'a U, &'a V), AliasError> {
+        check_write_alias(&[w.overlaps(r1), w.overlaps(r2)])?;
+        unsafe { Ok((&mut *w.value.get(), &*r1.value.get(), &*r2.value.get())) }
+    }
+
+    /// Borrow contents of one `TCell` mutably and three others
+    /// immutably.  Panics if any of the read-only cells points to the
+    /// same memory as the mutably-borrowed cell.  See
+    /// [`rw_ro2`](#method.rw_ro2).
+    #[inline]
+    pub fn rw_ro3<'a, T: ?Sized, U: ?Sized, V: ?Sized, W: ?Sized>(
+        &'a mut self,
+        w: &'a TCell<Q, T>,
+        r1: &'a TCell<Q, U>,
+        r2: &'a TCell<Q, V>,
+        r3: &'a TCell<Q, W>,
+    ) -> (&'a mut T, &'a U, &'a V, &'a W) {
+        match self.try_rw_ro3(w, r1, r2, r3) {
+            Ok(refs) => refs,
+            Err(e) => rw_ro_panic("rw_ro3", e),
+        }
+    }
+
+    /// Borrow contents of one `TCell` mutably and three others
+    /// immutably.  Returns an error if any of the read-only cells
+    /// points to the same memory as the mutably-borrowed cell.
+    #[inline]
+    pub fn try_rw_ro3<'a, T: ?Sized, U: ?Sized, V: ?Sized, W: ?Sized>(
+        &'a mut self,
+        w: &'a TCell<Q, T>,
+        r1: &'a TCell<Q, U>,
+        r2: &'a TCell<Q, V>,
+        r3: &'a TCell<Q, W>,
+    ) -> Result<(&'a mut T, &'a U, &'a V, &'a W), AliasError> {
+        check_write_alias(&[w.overlaps(r1), w.overlaps(r2), w.overlaps(r3)])?;
+        unsafe {
+            Ok((
+                &mut *w.value.get(),
+                &*r1.value.get(),
+                &*r2.value.get(),
+                &*r3.value.get(),
+            ))
+        }
+    }
+
+    /// Borrow contents of a tuple of `TCell` instances mutably, for
+    /// example `owner.rw_n((&c1, &c2, &c3, &c4))`.  Tuples of up to
+    /// 8 cells are supported, and the cells may have different
+    /// contained types.  Panics if any pair of `TCell` instances point
+    /// to the same memory, naming the pair of positions that clash.
+    #[inline]
+    pub fn rw_n<'a, C>(&'a mut self, cells: C) -> C::Output
+    where
+        C: TupleOfCells<'a, Q>,
+    {
+        match self.try_rw_n(cells) {
+            Ok(refs) => refs,
+            Err(e) => panic!("Illegal to borrow same TCell twice with rw_n(): {}", e),
+        }
+    }
+
+    /// Borrow contents of a tuple of `TCell` instances mutably.
+    /// Returns an error naming the first pair of positions that clash
+    /// if any pair of `TCell` instances point to the same memory.
+    /// See [`rw_n`](#method.rw_n).
+    #[inline]
+    pub fn try_rw_n<'a, C>(&'a mut self, cells: C) -> Result<C::Output, AliasError>
+    where
+        C: TupleOfCells<'a, Q>,
+    {
+        match cells.find_alias() {
+            Some((first, second)) => Err(AliasError { first, second }),
+            None => Ok(unsafe { cells.rw_unchecked() }),
+        }
+    }
+
+    /// Borrow contents of all the `TCell` instances in a slice
+    /// mutably at the same time.  Panics if any two entries refer to
+    /// the same `TCell`.  See [`try_rw_slice`](#method.try_rw_slice).
+    #[cfg(feature = "alloc")]
+    pub fn rw_slice<'a, T>(&'a mut self, cells: &[&'a TCell<Q, T>]) -> Vec<&'a mut T> {
+        match self.try_rw_slice(cells) {
+            Ok(refs) => refs,
+            Err(e) => panic!("Illegal to borrow same TCell twice with rw_slice(): {}", e),
+        }
+    }
+
+    /// Borrow contents of all the `TCell` instances in a slice
+    /// mutably at the same time.  Returns an error naming two slice
+    /// positions that refer to the same `TCell` if there are any
+    /// duplicates.  For more than a few cells, the check sorts the
+    /// cell addresses, so it is O(n log n).
+    ///
+    /// Zero-sized contents are checked too, since a zero-sized type
+    /// may still carry exclusive access to something.  So distinct
+    /// zero-sized cells that share an address are reported as
+    /// duplicates.
+    #[cfg(feature = "alloc")]
+    pub fn try_rw_slice<'a, T>(
+        &'a mut self,
+        cells: &[&'a TCell<Q, T>],
+    ) -> Result<Vec<&'a mut T>, AliasError> {
+        if let Some((first, second)) = find_slice_alias(cells) {
+            return Err(AliasError { first, second });
+        }
+        Ok(cells
+            .iter()
+            .map(|tc| unsafe { &mut *tc.value.get() })
+            .collect())
+    }
+
+    /// Borrow contents of all the cells returned by an iterator
+    /// mutably at the same time, for example
+    /// `owner.rw_iter(&vec_of_rc_cells)`.  The cells may be held in
+    /// any of the ways supported by [`AsCellRef`].  Panics if any two
+    /// entries refer to the same `TCell`, naming their positions.  See
+    /// [`try_rw_iter`](#method.try_rw_iter).
+    ///
+    /// [`AsCellRef`]: trait.AsCellRef.html
+    #[cfg(feature = "alloc")]
+    pub fn rw_iter<'a, C, I>(&'a mut self, cells: I) -> Vec<&'a mut C::Value>
+    where
+        I: IntoIterator<Item = &'a C>,
+        C: AsCellRef<Q> + 'a,
+        C::Value: Sized,
+    {
+        match self.try_rw_iter(cells) {
+            Ok(refs) => refs,
+            Err(e) => panic!("Illegal to borrow same TCell twice with rw_iter(): {}", e),
+        }
+    }
+
+    /// Borrow contents of all the cells returned by an iterator
+    /// mutably at the same time.  Returns an error naming two
+    /// positions in the iteration that refer to the same `TCell` if
+    /// there are any duplicates.  The check is the same as for
+    /// [`try_rw_slice`](#method.try_rw_slice), so is O(n log n) for
+    /// more than a few cells, and also applies to zero-sized contents.
+    #[cfg(feature = "alloc")]
+    pub fn try_rw_iter<'a, C, I>(
+        &'a mut self,
+        cells: I,
+    ) -> Result<Vec<&'a mut C::Value>, AliasError>
+    where
+        I: IntoIterator<Item = &'a C>,
+        C: AsCellRef<Q> + 'a,
+        C::Value: Sized,
+    {
+        let cells: Vec<&'a TCell<Q, C::Value>> =
+            cells.into_iter().map(AsCellRef::as_cell_ref).collect();
+        self.try_rw_slice(&cells)
+    }
+
+    /// Pass a mutable reference to the contents of each cell returned
+    /// by an iterator to the given closure in turn.  Only one cell is
+    /// borrowed at a time, so unlike [`rw_iter`](#method.rw_iter) no
+    /// check for duplicates is needed, and a cell that appears more
+    /// than once is simply visited more than once.
+    #[inline]
+    pub fn for_each_rw<'a, C, I>(&mut self, cells: I, mut f: impl FnMut(&mut C::Value))
+    where
+        I: IntoIterator<Item = &'a C>,
+        C: AsCellRef<Q> + ?Sized + 'a,
+    {
+        for cell in cells {
+            f(self.rw(cell.as_cell_ref()));
+        }
+    }
+
+    /// Borrow two different elements of a `TCell` containing a slice
+    /// mutably at the same time, like `slice::split_at_mut` but by
+    /// index.  Panics if the indices are the same or if either is out
+    /// of bounds.
+    ///
+    /// For a cell containing a `Vec<T>`, borrow the whole `Vec` and
+    /// use `split_at_mut` from the standard library instead:
+    ///
+    /// ```
+    ///# use qcell::{TCell, TCellOwner};
+    ///# struct Marker;
+    /// let mut owner = TCellOwner::<Marker>::new();
+    /// let cell = TCell::<Marker, _>::new(vec![1, 2, 3]);
+    /// let (left, right) = owner.rw(&cell).split_at_mut(2);
+    /// std::mem::swap(&mut left[0], &mut right[0]);
+    /// assert_eq!(owner.ro(&cell), &[3, 2, 1]);
+    /// ```
+    #[inline]
+    pub fn rw_idx2<'a, T>(
+        &'a mut self,
+        tc: &'a TCell<Q, [T]>,
+        i: usize,
+        j: usize,
+    ) -> (&'a mut T, &'a mut T) {
+        match self.try_rw_idx2(tc, i, j) {
+            Ok(refs) => refs,
+            Err(e) => panic!("Illegal element indices for rw_idx2(): {}", e),
+        }
+    }
+
+    /// Borrow two different elements of a `TCell` containing a slice
+    /// mutably at the same time.  Returns an error if the indices are
+    /// the same or if either is out of bounds.
+    #[inline]
+    pub fn try_rw_idx2<'a, T>(
+        &'a mut self,
+        tc: &'a TCell<Q, [T]>,
+        i: usize,
+        j: usize,
+    ) -> Result<(&'a mut T, &'a mut T), IndexError> {
+        check_indices(tc, &[i, j])?;
+        let base = tc.value.get() as *mut T;
+        unsafe { Ok((&mut *base.add(i), &mut *base.add(j))) }
+    }
+
+    /// Borrow three different elements of a `TCell` containing a
+    /// slice mutably at the same time.  Panics if any two indices are
+    /// the same or if any is out of bounds.
+    #[inline]
+    pub fn rw_idx3<'a, T>(
+        &'a mut self,
+        tc: &'a TCell<Q, [T]>,
+        i: usize,
+        j: usize,
+        k: usize,
+    ) -> (&'a mut T, &'a mut T, &'a mut T) {
+        match self.try_rw_idx3(tc, i, j, k) {
+            Ok(refs) => refs,
+            Err(e) => panic!("Illegal element indices for rw_idx3(): {}", e),
+        }
+    }
+
+    /// Borrow three different elements of a `TCell` containing a
+    /// slice mutably at the same time.  Returns an error if any two
+    /// indices are the same or if any is out of bounds.
+    #[inline]
+    pub fn try_rw_idx3<'a, T>(
+        &'a mut self,
+        tc: &'a TCell<Q, [T]>,
+        i: usize,
+        j: usize,
+        k: usize,
+    ) -> Result<(&'a mut T, &'a mut T, &'a mut T), IndexError> {
+        check_indices(tc, &[i, j, k])?;
+        let base = tc.value.get() as *mut T;
+        unsafe { Ok((&mut *base.add(i), &mut *base.add(j), &mut *base.add(k))) }
+    }
+}
+
+// Check that element indices for `rw_idx*()` are in bounds and
+// distinct.  Duplicates are rejected for zero-sized elements too, see
+// `ranges_overlap`.
+fn check_indices<Q, T>(tc: &TCell<Q, [T]>, indices: &[usize]) -> Result<(), IndexError> {
+    let len = tc.len();
+    for (n, &index) in indices.iter().enumerate() {
+        if index >= len {
+            return Err(IndexError::OutOfBounds { index, len });
+        }
+        if indices[..n].contains(&index) {
+            return Err(IndexError::Duplicate { index });
+        }
+    }
+    Ok(())
+}
+
+// Check the results of comparing the mutably-borrowed cell (argument
+// 0) of a `rw_ro*()` call against each read-only cell in turn
+#[inline]
+fn check_write_alias(overlaps: &[bool]) -> Result<(), AliasError> {
+    match overlaps.iter().position(|o| *o) {
+        None => Ok(()),
+        Some(i) => Err(AliasError {
+            first: 0,
+            second: i + 1,
+        }),
+    }
+}
+
+// Test whether two (address, length) byte ranges overlap.  An empty
+// range still occupies its address, since a zero-sized type such as
+// `LCellOwner` may carry exclusive access, so it overlaps any range
+// that contains or touches that address.  This rejects some distinct
+// zero-sized cells that happen to share an address, which is safe.
+#[inline]
+pub(crate) fn ranges_overlap((a, alen): (usize, usize), (b, blen): (usize, usize)) -> bool {
+    if alen == 0 || blen == 0 {
+        a <= b + blen && b <= a + alen
+    } else {
+        a < b + blen && b < a + alen
+    }
+}
+
+#[cold]
+fn rw_ro_panic(call: &str, e: AliasError) -> ! {
+    panic!(
+        "Illegal to borrow same TCell mutably and immutably with {}(): read argument r{} is the same as the written argument",
+        call, e.second
+    )
+}
+
+// Find the positions of two slice entries that refer to the same
+// cell, if any.  A direct scan is fastest for short slices.
+#[cfg(feature = "alloc")]
+fn find_slice_alias<Q, T>(cells: &[&TCell<Q, T>]) -> Option<(usize, usize)> {
+    const SCAN_LIMIT: usize = 8;
+    if cells.len() <= SCAN_LIMIT {
+        for i in 0..cells.len() {
+            for j in i + 1..cells.len() {
+                if core::ptr::eq(cells[i].value.get(), cells[j].value.get()) {
+                    return Some((i, j));
+                }
+            }
+        }
+        return None;
+    }
+    let mut addrs: Vec<(*const T, usize)> = cells
+        .iter()
+        .enumerate()
+        .map(|(i, tc)| (tc.value.get() as *const T, i))
+        .collect();
+    addrs.sort_unstable();
+    addrs
+        .windows(2)
+        .find(|w| w[0].0 == w[1].0)
+        .map(|w| (w[0].1.min(w[1].1), w[0].1.max(w[1].1)))
+}
+
+/// Error returned when the same cell is passed twice to a call that
+/// borrows several cells mutably, such as [`TCellOwner::try_rw2`].
+///
+/// [`TCellOwner::try_rw2`]: struct.TCellOwner.html#method.try_rw2
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AliasError {
+    first: usize,
+    second: usize,
+}
+
+impl AliasError {
+    pub(crate) fn new(first: usize, second: usize) -> Self {
+        Self { first, second }
+    }
+
+    /// Positions of the two arguments that refer to the same cell,
+    /// counting from 1.  For [`TCellOwner::try_rw_slice`] these are
+    /// positions within the slice.
+    ///
+    /// [`TCellOwner::try_rw_slice`]: struct.TCellOwner.html#method.try_rw_slice
+    pub fn arguments(&self) -> (usize, usize) {
+        (self.first + 1, self.second + 1)
+    }
+
+    /// Indices of the two arguments that refer to the same cell,
+    /// counting from 0.
+    pub fn indices(&self) -> (usize, usize) {
+        (self.first, self.second)
+    }
+}
+
+impl fmt::Display for AliasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (first, second) = self.arguments();
+        write!(f, "arguments {} and {} are the same", first, second)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AliasError {}
+
+/// Error returned when borrowing several elements of a slice cell
+/// with a call such as [`TCellOwner::try_rw_idx2`].
+///
+/// [`TCellOwner::try_rw_idx2`]: struct.TCellOwner.html#method.try_rw_idx2
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndexError {
+    /// An index was not less than the length of the slice
+    OutOfBounds {
+        /// The index that was out of bounds
+        index: usize,
+        /// Length of the slice
+        len: usize,
+    },
+    /// The same index was given more than once
+    Duplicate {
+        /// The index that was repeated
+        index: usize,
+    },
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexError::OutOfBounds { index, len } => write!(
+                f,
+                "index {} is out of bounds for a slice of length {}",
+                index, len
+            ),
+            IndexError::Duplicate { index } => write!(f, "index {} is given twice", index),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for IndexError {}
+
+pub(crate) mod private {
+    pub trait Sealed {}
+}
+
+/// Tuple of `TCell` references that can be borrowed all at once with
+/// [`TCellOwner::rw_n`].  This is implemented for tuples of between 1
+/// and 8 `&TCell<Q, _>` references, and cannot be implemented
+/// outside this crate.
+///
+/// [`TCellOwner::rw_n`]: struct.TCellOwner.html#method.rw_n
+pub trait TupleOfCells<'a, Q>: private::Sealed {
+    /// Tuple of mutable references to the cell contents
+    type Output;
+
+    #[doc(hidden)]
+    fn find_alias(&self) -> Option<(usize, usize)>;
+
+    #[doc(hidden)]
+    unsafe fn rw_unchecked(self) -> Self::Output;
+}
+
+// Implement a tuple-of-cells trait such as `TupleOfCells` for tuples
+// of between 1 and 8 references to the given cell type, which must
+// have `byte_range()` and a `value: UnsafeCell` field
+macro_rules! tuple_of_cells {
+    ($trait:ident, $cell:ident) => {
+        crate::tcell::tuple_of_cells!(@impl $trait, $cell, A 0);
+        crate::tcell::tuple_of_cells!(@impl $trait, $cell, A 0, B 1);
+        crate::tcell::tuple_of_cells!(@impl $trait, $cell, A 0, B 1, C 2);
+        crate::tcell::tuple_of_cells!(@impl $trait, $cell, A 0, B 1, C 2, D 3);
+        crate::tcell::tuple_of_cells!(@impl $trait, $cell, A 0, B 1, C 2, D 3, E 4);
+        crate::tcell::tuple_of_cells!(@impl $trait, $cell, A 0, B 1, C 2, D 3, E 4, F 5);
+        crate::tcell::tuple_of_cells!(@impl $trait, $cell, A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+        crate::tcell::tuple_of_cells!(@impl $trait, $cell, A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+    };
+    (@impl $trait:ident, $cell:ident, $($T:ident $i:tt),+) => {
+        impl<'a, Q, $($T: ?Sized),+> crate::tcell::private::Sealed for ($(&'a $cell<Q, $T>,)+) {}
+
+        impl<'a, Q, $($T: ?Sized),+> $trait<'a, Q> for ($(&'a $cell<Q, $T>,)+) {
+            type Output = ($(&'a mut $T,)+);
+
+            fn find_alias(&self) -> Option<(usize, usize)> {
+                let ranges = [$(self.$i.byte_range()),+];
+                for i in 0..ranges.len() {
+                    for j in i + 1..ranges.len() {
+                        if crate::tcell::ranges_overlap(ranges[i], ranges[j]) {
+                            return Some((i, j));
+                        }
+                    }
+                }
+                None
+            }
+
+            #[inline]
+            unsafe fn rw_unchecked(self) -> Self::Output {
+                ($(&mut *self.$i.value.get(),)+)
+            }
+        }
+    };
+}
+pub(crate) use tuple_of_cells;
+
+tuple_of_cells!(TupleOfCells, TCell);
+
+/// Future returned by [`TCellOwner::new_async`], which resolves to
+/// the singleton owner instance.
+///
+/// Requires the `async` cargo feature.
+///
+/// [`TCellOwner::new_async`]: struct.TCellOwner.html#method.new_async
+#[cfg(feature = "async")]
+pub struct TCellOwnerFuture<Q: 'static> {
+    ticket: Option<u64>,
+    location: &'static Location<'static>,
+    // No `Q` is stored, so the marker type doesn't affect the auto
+    // traits, as for `TCellOwner`
+    typ: PhantomData<fn() -> Q>,
+}
+
+#[cfg(feature = "async")]
+impl<Q: 'static> Future for TCellOwnerFuture<Q> {
+    type Output = TCellOwner<Q>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<TCellOwner<Q>> {
+        let id = TypeId::of::<Q>();
+        let mut reg = lock_registry();
+        if reg.can_claim(id, self.ticket) {
+            if let Some(ticket) = self.ticket.take() {
+                reg.dequeue(id, ticket);
+            }
+            reg.claim(id, self.location);
+            return Poll::Ready(TCellOwner::registered());
+        }
+        let ticket = match self.ticket {
+            Some(ticket) => ticket,
+            None => {
+                let ticket = reg.enqueue(id);
+                self.ticket = Some(ticket);
+                ticket
+            }
+        };
+        reg.wakers.insert(ticket, cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Q: 'static> Drop for TCellOwnerFuture<Q> {
+    fn drop(&mut self) {
+        // Cancelled whilst still queued, so give up our place
+        if let Some(ticket) = self.ticket {
+            let id = TypeId::of::<Q>();
+            let mut reg = lock_registry();
+            reg.dequeue(id, ticket);
+            wake_waiters(reg, id);
+        }
+    }
+}
+
+pub(crate) const FLAG_UNLINKED: u8 = 0;
+pub(crate) const FLAG_FREE: u8 = 1;
+pub(crate) const FLAG_OWNED: u8 = 2;
+
+/// Per-marker flag used by [`TCellOwner::new_fast`].  A static
+/// instance of this is created for each marker type defined with
+/// [`tcell_marker!`].
+///
+/// [`TCellOwner::new_fast`]: struct.TCellOwner.html#method.new_fast
+/// [`tcell_marker!`]: macro.tcell_marker.html
+pub struct TCellMarkerFlag {
+    state: AtomicU8,
+}
+
+impl TCellMarkerFlag {
+    /// Create a new flag, which initially has no owner.
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            state: AtomicU8::new(FLAG_UNLINKED),
+        }
+    }
+}
+
+/// Marker type with its own flag, allowing owners to be created with
+/// [`TCellOwner::new_fast`].  Use [`tcell_marker!`] to define marker
+/// types that implement this trait.
+///
+/// This trait is public API and may also be implemented by hand, for
+/// example for a family of generic marker types that the macro can't
+/// express.  The implementation only provides storage for the flag:
+/// the flag's state is private to this crate, and all claiming and
+/// releasing of it is done by `TCellOwner`.
+///
+/// ```
+///# use qcell::{TCellMarker, TCellMarkerFlag, TCellOwner};
+/// struct Slot<const N: usize>;
+/// static FLAGS: [TCellMarkerFlag; 2] = [TCellMarkerFlag::new(), TCellMarkerFlag::new()];
+///
+/// // Safety: each `Slot<N>` gets its own element, and `N` is
+/// // limited to the length of the array, so no flag is shared
+/// unsafe impl TCellMarker for Slot<0> {
+///     fn flag() -> &'static TCellMarkerFlag { &FLAGS[0] }
+/// }
+/// unsafe impl TCellMarker for Slot<1> {
+///     fn flag() -> &'static TCellMarkerFlag { &FLAGS[1] }
+/// }
+///
+/// let _owner0 = TCellOwner::<Slot<0>>::new_fast();
+/// let _owner1 = TCellOwner::<Slot<1>>::new_fast();
+/// assert!(TCellOwner::<Slot<0>>::try_new_fast().is_err());
+/// ```
+///
+/// # Safety
+///
+/// The uniqueness of `TCellOwner` instances created with `new_fast`
+/// relies entirely on the flag, so the implementation must ensure
+/// that:
+///
+/// - `flag` always returns a reference to the same `TCellMarkerFlag`
+///   instance for a given marker type, for the whole life of the
+///   process.  Returning a fresh flag would allow a second owner to
+///   be created while the first still exists.
+///
+/// - That instance is never returned by `flag` for any other marker
+///   type.  Otherwise an owner of one marker type would block owners
+///   of the other, and if the first use of the flag linked it to one
+///   type, the other type would get owners without any registry
+///   check at all.
+///
+/// - `flag` does not panic and does not create owners itself.
+///
+/// [`TCellOwner::new_fast`]: struct.TCellOwner.html#method.new_fast
+/// [`tcell_marker!`]: macro.tcell_marker.html
+pub unsafe trait TCellMarker: 'static {
+    /// Get the flag belonging to this marker type.
+    fn flag() -> &'static TCellMarkerFlag;
+}
+
+/// Define a marker type that implements [`TCellMarker`], so that
+/// owners for it can be created with [`TCellOwner::new_fast`].
+///
+/// ```
+///# use qcell::{tcell_marker, TCell, TCellOwner};
+/// tcell_marker!(pub struct Marker;);
+/// let mut owner = TCellOwner::<Marker>::new_fast();
+/// let cell = TCell::<Marker, u32>::new(1);
+/// *owner.rw(&cell) += 1;
+/// assert_eq!(*owner.ro(&cell), 2);
+/// ```
+///
+/// [`TCellMarker`]: trait.TCellMarker.html
+/// [`TCellOwner::new_fast`]: struct.TCellOwner.html#method.new_fast
+#[macro_export]
+macro_rules! tcell_marker {
+    ($(#[$attr:meta])* $vis:vis struct $name:ident;) => {
+        $(#[$attr])*
+        $vis struct $name;
+
+        unsafe impl $crate::TCellMarker for $name {
+            #[inline]
+            fn flag() -> &'static $crate::TCellMarkerFlag {
+                static FLAG: $crate::TCellMarkerFlag = $crate::TCellMarkerFlag::new();
+                &FLAG
+            }
+        }
+    };
+}
+
+/// Project a reference to a cell containing a struct to a reference
+/// to a cell containing one of its fields, for example
+/// `cell_project!(&cell, Outer, inner.value)` turns a
+/// `&TCell<Q, Outer>` into a `&TCell<Q, Value>`.  This allows a
+/// component to be given access to just one part of a shared
+/// structure.  It works for [`TCell`] and [`TLCell`], including
+/// through an `Rc` or `Arc`, and the field path may be nested.
+///
+/// The struct type must be named, as for `std::mem::offset_of!`.
+/// Only fields stored directly within the struct may be projected,
+/// so paths that would go through a `Deref` such as a `Box`, and
+/// paths into enums, unions or packed structs are rejected at
+/// compile time.
+///
+/// Cells for different fields can be borrowed mutably at the same
+/// time, for example with `rw2`:
+///
+/// ```
+///# use qcell::{cell_project, TCell, TCellOwner};
+///# use std::rc::Rc;
+///# struct Marker;
+/// struct Point { x: u32, y: u32 }
+/// struct Shape { origin: Point, size: u32 }
+/// let mut owner = TCellOwner::<Marker>::new();
+/// let shape = Rc::new(TCell::<Marker, _>::new(Shape {
+///     origin: Point { x: 1, y: 2 },
+///     size: 3,
+/// }));
+/// let x = cell_project!(&shape, Shape, origin.x);
+/// let size = cell_project!(&shape, Shape, size);
+/// let (x, size) = owner.rw2(x, size);
+/// *x += 10;
+/// *size *= 2;
+/// assert_eq!(owner.ro(&shape).origin.x, 11);
+/// assert_eq!(owner.ro(&shape).size, 6);
+/// ```
+///
+/// Projecting the same field twice gives two references to the same
+/// memory, and cells that overlap, such as the outer cell and one of
+/// its fields, are detected as aliases in the same way as passing
+/// the same cell twice, so `rw2` panics.
+///
+/// [`TCell`]: struct.TCell.html
+/// [`TLCell`]: struct.TLCell.html
+#[macro_export]
+macro_rules! cell_project {
+    ($cell:expr, $outer:ty, $($field:tt).+) => {{
+        let cell = $cell;
+        // Taking a reference rejects fields of packed structs, and
+        // since this is outside the `unsafe` block, union fields are
+        // rejected too.  `offset_of!` rejects going through `Deref`.
+        let field = |outer: &$outer| {
+            let _ = &outer.$($field).+;
+            ::core::ptr::addr_of!(outer.$($field).+)
+        };
+        #[allow(unused_unsafe)]
+        unsafe {
+            cell.__project(::core::mem::offset_of!($outer, $($field).+), field)
+        }
+    }};
+}
+
+/// Error returned by [`TCellOwner::try_new`] when another
+/// [`TCellOwner`] with the same marker type already exists, or by
+/// [`TLCellOwner::try_new`] when another [`TLCellOwner`] with the
+/// same marker type already exists in the current thread.
+///
+/// [`TCellOwner::try_new`]: struct.TCellOwner.html#method.try_new
+/// [`TCellOwner`]: struct.TCellOwner.html
+/// [`TLCellOwner::try_new`]: struct.TLCellOwner.html#method.try_new
+/// [`TLCellOwner`]: struct.TLCellOwner.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DuplicateOwnerError {
+    marker: &'static str,
+    first_created_at: Option<&'static Location<'static>>,
+    // True if raised by `TLCellOwner`
+    thread_local: bool,
+    // True if no owner exists but the marker is held for a waiter
+    reserved: bool,
+}
+
+impl DuplicateOwnerError {
+    #[cfg(feature = "std")]
+    pub(crate) fn thread_local<Q: 'static>() -> Self {
+        Self {
+            marker: core::any::type_name::<Q>(),
+            first_created_at: None,
+            thread_local: true,
+            reserved: false,
+        }
+    }
+
+    /// Name of the marker type, as given by `std::any::type_name`.
+    pub fn marker(&self) -> &'static str {
+        self.marker
+    }
+
+    /// Location where the existing owner was created.  This is only
+    /// recorded in debug builds, so is always `None` in release
+    /// builds.  It is also `None` if the existing owner was obtained
+    /// through `new_unchecked`, or if [`is_reserved`] is true.
+    ///
+    /// [`is_reserved`]: #method.is_reserved
+    pub fn first_created_at(&self) -> Option<&'static Location<'static>> {
+        self.first_created_at
+    }
+
+    /// Test whether the call failed only because the marker type is
+    /// reserved for other callers queued in
+    /// [`TCellOwner::wait_for_new`] or [`TCellOwner::new_async`],
+    /// rather than because an owner currently exists.  In that case
+    /// a later call may succeed once those waiters have taken their
+    /// turn.
+    ///
+    /// [`TCellOwner::wait_for_new`]: struct.TCellOwner.html#method.wait_for_new
+    /// [`TCellOwner::new_async`]: struct.TCellOwner.html#method.new_async
+    pub fn is_reserved(&self) -> bool {
+        self.reserved
+    }
+}
+
+impl fmt::Display for DuplicateOwnerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.thread_local {
+            write!(
+                f,
+                "Illegal to create two TLCellOwner instances within the same thread with the same marker type parameter `{}`",
+                self.marker
+            )?;
+        } else if self.reserved {
+            write!(
+                f,
+                "Unable to create TCellOwner with marker type parameter `{}` as it is reserved for a queued waiter",
+                self.marker
+            )?;
+        } else {
+            write!(
+                f,
+                "Illegal to create two TCellOwner instances with the same marker type parameter `{}`",
+                self.marker
+            )?;
+        }
+        if let Some(location) = self.first_created_at {
+            write!(f, " (first owner created at {})", location)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DuplicateOwnerError {}
+
+// Adapter returned by `TCellOwner::debug()` and `display()`
+struct Formatted<'a, Q: 'static, T: ?Sized> {
+    owner: &'a TCellOwner<Q>,
+    tc: &'a TCell<Q, T>,
+}
+
+impl<'a, Q: 'static, T: fmt::Debug + ?Sized> fmt::Debug for Formatted<'a, Q, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.owner.ro(self.tc), f)
+    }
+}
+
+impl<'a, Q: 'static, T: fmt::Display + ?Sized> fmt::Display for Formatted<'a, Q, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.owner.ro(self.tc), f)
+    }
+}
+
+/// Cell whose contents is owned (for borrowing purposes) by a
+/// [`TCellOwner`].
+///
+/// To borrow from this cell, use the borrowing calls on the
+/// [`TCellOwner`] instance that shares the same marker type.
+///
+/// `TCell<Q, T>` is `repr(transparent)`, so is guaranteed to have the
+/// same memory layout as `T`.  This is part of the public API, so
+/// unsafe code may rely on it, and it won't change without a major
+/// version bump.
+///
+/// Pointers to cells coerce to pointers to unsized cells on stable
+/// Rust, in the same way as for the contained types, so for example
+/// `Rc<TCell<Q, [u8; 16]>>` coerces to `Rc<TCell<Q, [u8]>>` and
+/// `Box<TCell<Q, Widget>>` to `Box<TCell<Q, dyn Draw>>`.  With the
+/// `nightly` cargo feature, a cell containing a pointer also coerces
+/// by value, for example `TCell<Q, &[u8; 16]>` to `TCell<Q, &[u8]>`.
+///
+/// As for `RefCell`, a `TCell` is `UnwindSafe` if its contents are,
+/// but `&TCell` is not, since a panic during an update through the
+/// owner could leave the contents half-modified.  To use a shared
+/// cell within `std::panic::catch_unwind`, wrap the closure in
+/// `AssertUnwindSafe` and make sure that the contents are still
+/// valid afterwards.  The owner itself is always unwind-safe, but
+/// `&mut TCellOwner`, like any `&mut` reference, is not.
+///
+/// Auto traits: `Send` if `T: Send`, `Sync` if `T: Send + Sync`,
+/// `Unpin` if `T: Unpin`, `UnwindSafe` if `T: UnwindSafe`, and never
+/// `RefUnwindSafe`.  The marker type doesn't affect any of
+/// these.
+///
+/// See also [crate documentation](index.html).
+///
+/// [`TCellOwner`]: struct.TCellOwner.html
+#[repr(transparent)]
+pub struct TCell<Q, T: ?Sized> {
+    // Use *const to disable Send and Sync, which are then re-enabled
+    // below under certain conditions
+    owner: PhantomData<*const Q>,
+    value: UnsafeCell<T>,
+}
+
+impl<Q, T> TCell<Q, T> {
+    /// Create a new `TCell` owned for borrowing purposes by the
+    /// `TCellOwner` derived from the same marker type `Q`.
+    #[inline]
+    pub const fn new(value: T) -> TCell<Q, T> {
+        TCell {
+            owner: PhantomData,
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Consume the cell, returning its contents.  No owner is needed,
+    /// since owning the cell means that nothing else can access it.
+    /// This is useful when tearing down a structure:
+    ///
+    /// ```
+    ///# use qcell::{TCell, TCellOwner};
+    ///# use std::rc::Rc;
+    ///# struct Marker;
+    /// let cell = Rc::new(TCell::<Marker, _>::new(String::from("value")));
+    /// let value = Rc::try_unwrap(cell).ok().unwrap().into_inner();
+    /// assert_eq!(value, "value");
+    /// ```
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Consume the cell, returning a new cell belonging to the same
+    /// owner that contains the result of passing the contents to the
+    /// given closure.  No owner is needed, as for
+    /// [`into_inner`](#method.into_inner).  If the closure panics,
+    /// the contents have already been passed to it, so they are
+    /// dropped during unwinding along with its other locals.
+    #[inline]
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> TCell<Q, U> {
+        TCell::new(f(self.into_inner()))
+    }
+
+    /// Consume the cell, passing the contents to the given fallible
+    /// closure, and return a new cell containing the result, or the
+    /// closure's error.  See [`map`](#method.map).
+    #[inline]
+    pub fn try_map<U, E>(self, f: impl FnOnce(T) -> Result<U, E>) -> Result<TCell<Q, U>, E> {
+        f(self.into_inner()).map(TCell::new)
+    }
+
+    /// Convert the cell into a `RefCell`, for example for passing to
+    /// code that hasn't yet been converted to use `TCell`.  No owner
+    /// is needed, since the cell is consumed.
+    ///
+    /// Together with the `From<RefCell<T>>` and `From<Cell<T>>`
+    /// implementations, this allows a structure to be migrated a
+    /// field at a time, converting at the boundary between old and
+    /// new code:
+    ///
+    /// ```
+    ///# use qcell::{TCell, TCellOwner};
+    ///# use std::cell::{Cell, RefCell};
+    /// struct Marker;
+    /// // Old code still builds nodes using std cells
+    /// struct OldNode {
+    ///     name: RefCell<String>,
+    ///     hits: Cell<u32>,
+    /// }
+    /// // New code has moved `name` over to `TCell` so far
+    /// struct Node {
+    ///     name: TCell<Marker, String>,
+    ///     hits: Cell<u32>,
+    /// }
+    /// impl From<OldNode> for Node {
+    ///     fn from(old: OldNode) -> Self {
+    ///         Node { name: old.name.into(), hits: old.hits }
+    ///     }
+    /// }
+    /// impl From<Node> for OldNode {
+    ///     fn from(node: Node) -> Self {
+    ///         OldNode { name: node.name.into_refcell(), hits: node.hits }
+    ///     }
+    /// }
+    ///
+    /// let mut owner = TCellOwner::<Marker>::new();
+    /// let old = OldNode { name: RefCell::new("a".into()), hits: Cell::new(1) };
+    /// let node = Node::from(old);
+    /// owner.rw(&node.name).push('b');
+    /// node.hits.set(2);
+    /// let old = OldNode::from(node);
+    /// assert_eq!(*old.name.borrow(), "ab");
+    /// assert_eq!(old.hits.get(), 2);
+    /// ```
+    #[inline]
+    pub fn into_refcell(self) -> RefCell<T> {
+        RefCell::new(self.into_inner())
+    }
+
+    /// Convert the cell into a `Cell`.  No owner is needed, since the
+    /// cell is consumed.
+    #[inline]
+    pub fn into_std_cell(self) -> Cell<T> {
+        Cell::new(self.into_inner())
+    }
+
+    /// Create a new cell containing a clone of the contents of this
+    /// one, belonging to the same owner.  `TCell` can't implement
+    /// `Clone`, since reading the contents needs the owner.
+    #[inline]
+    pub fn clone_with(&self, owner: &TCellOwner<Q>) -> TCell<Q, T>
+    where
+        Q: 'static,
+        T: Clone,
+    {
+        owner.clone_cell(self)
+    }
+
+    // Used by `cell_project!`.  The macro guarantees that `offset` is
+    // the offset of a field of type `F` directly within `T`, and
+    // `field` is only there to let the compiler infer `F`.
+    #[doc(hidden)]
+    #[inline]
+    pub unsafe fn __project<F>(
+        &self,
+        offset: usize,
+        _field: impl FnOnce(&T) -> *const F,
+    ) -> &TCell<Q, F> {
+        debug_assert!(offset + core::mem::size_of::<F>() <= core::mem::size_of::<T>());
+        &*((self.value.get() as *const u8).add(offset) as *const TCell<Q, F>)
+    }
+}
+
+impl<Q, T: ?Sized> TCell<Q, T> {
+    /// View a mutable reference as a cell, so that code written
+    /// against `&TCell<Q, T>` and an owner can also be used on data
+    /// borrowed from elsewhere.
+    #[inline]
+    pub fn from_mut(value: &mut T) -> &mut TCell<Q, T> {
+        // Safe because `TCell` is `repr(transparent)` over
+        // `UnsafeCell<T>`, which has the same layout as `T`, and the
+        // exclusive borrow of `value` is kept for the lifetime
+        unsafe { &mut *(value as *mut T as *mut TCell<Q, T>) }
+    }
+
+    /// View a shared reference as a cell.  Unlike
+    /// [`from_mut`](#method.from_mut), this can't be safe in general,
+    /// since the owner would allow the contents to be modified
+    /// whilst other shared references to them exist.
+    ///
+    /// # Safety
+    ///
+    /// The contents must not be borrowed mutably through the owner
+    /// (with `rw`, `rw2`, `replace` and so on) for as long as the
+    /// returned reference exists, unless the original `&T` is the
+    /// only reference to the contents and is not used again whilst
+    /// the returned reference exists.  Reading through the owner is
+    /// always fine.
+    #[inline]
+    pub unsafe fn from_ref(value: &T) -> &TCell<Q, T> {
+        // `TCell` is `repr(transparent)` over `UnsafeCell<T>`, which
+        // has the same layout as `T`
+        &*(value as *const T as *const TCell<Q, T>)
+    }
+
+    /// Borrow the contents mutably without an owner.  This is safe
+    /// because the `&mut` reference to the cell proves that nothing
+    /// else can currently access it, for example whilst building a
+    /// structure before it is shared:
+    ///
+    /// ```
+    ///# use qcell::{TCell, TCellOwner};
+    ///# use std::rc::Rc;
+    ///# struct Marker;
+    /// let mut cells: Vec<TCell<Marker, u32>> = (0..4).map(TCell::new).collect();
+    /// for cell in cells.iter_mut() {
+    ///     *cell.get_mut() *= 10;
+    /// }
+    /// let cells: Vec<Rc<TCell<Marker, u32>>> = cells.into_iter().map(Rc::new).collect();
+    /// let owner = TCellOwner::<Marker>::new();
+    /// assert_eq!(owner.get(&cells[3]), 30);
+    /// ```
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// Get a raw pointer to the contents, as for `UnsafeCell::get`.
+    /// This is intended for FFI and for building other unsafe
+    /// abstractions on top of `TCell`.
+    ///
+    /// Accesses through the pointer are subject to the same rules as
+    /// the owner borrows, and may be freely mixed with them provided
+    /// that:
+    ///
+    /// - Reading through the pointer is only allowed when no `&mut T`
+    ///   from [`TCellOwner::rw`] (or `rw2`, `get_mut` and so on) for
+    ///   this cell is alive.  So holding `&TCellOwner<Q>` is sufficient.
+    ///
+    /// - Writing through the pointer, or creating a `&mut T` from it,
+    ///   is only allowed when no reference to the contents obtained in
+    ///   any way is alive.  So holding `&mut TCellOwner<Q>` is
+    ///   sufficient, since that excludes any outstanding `ro` and `rw`
+    ///   borrows.
+    ///
+    /// - References created from the pointer must in turn not be used
+    ///   after a conflicting owner borrow starts, just as if they had
+    ///   come from `ro` or `rw`.
+    ///
+    /// Creating the pointer is always safe, and the pointer remains
+    /// valid for as long as the cell is not moved or dropped.
+    ///
+    /// [`TCellOwner::rw`]: struct.TCellOwner.html#method.rw
+    #[inline]
+    pub const fn as_ptr(&self) -> *mut T {
+        self.value.get()
+    }
+
+    /// Get a raw pointer to the contents from a raw pointer to the
+    /// cell, without creating an intermediate reference, as for
+    /// `UnsafeCell::raw_get`.  This is useful when the cell may not
+    /// be initialized yet, or when only a raw pointer is available.
+    /// The same rules as for [`as_ptr`](#method.as_ptr) apply to
+    /// accesses through the returned pointer.
+    #[inline]
+    pub const fn raw_get(this: *const Self) -> *mut T {
+        // `TCell` is `repr(transparent)` over `UnsafeCell<T>`
+        UnsafeCell::raw_get(this as *const UnsafeCell<T>)
+    }
+
+    /// Convert a boxed value into a boxed `TCell`, without moving the
+    /// value.  This allows a cell with unsized contents to be
+    /// created, for example `TCell<Q, [u8]>` from a `Box<[u8]>`.
+    /// (Cells with sized contents can also be coerced, for example
+    /// from `Rc<TCell<Q, [u8; 4]>>` to `Rc<TCell<Q, [u8]>>`.)
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn from_boxed(value: Box<T>) -> Box<TCell<Q, T>> {
+        // Safe because `TCell` is `repr(transparent)` over
+        // `UnsafeCell<T>`, which has the same layout as `T`
+        unsafe { Box::from_raw(Box::into_raw(value) as *mut TCell<Q, T>) }
+    }
+
+    // Test whether two cells' contents share memory.  Cells projected
+    // with `cell_project!` may overlap without starting at the same
+    // address, so compare the whole byte ranges.  Zero-sized contents
+    // are included, see `ranges_overlap`.
+    #[inline]
+    fn overlaps<U: ?Sized>(&self, other: &TCell<Q, U>) -> bool {
+        ranges_overlap(self.byte_range(), other.byte_range())
+    }
+
+    #[inline]
+    fn byte_range(&self) -> (usize, usize) {
+        (
+            self.value.get() as *const u8 as usize,
+            core::mem::size_of_val(self),
+        )
+    }
+}
+
+impl<Q, T> TCell<Q, [T]> {
+    /// View a cell containing a slice as a slice of cells, one per
+    /// element, like `Cell::as_slice_of_cells`.  The elements can then
+    /// be borrowed individually, for example two different elements
+    /// with `rw2`.
+    #[inline]
+    pub fn as_slice_of_cells(&self) -> &[TCell<Q, T>] {
+        // Safe because `TCell<Q, T>` has the same layout as `T`, so
+        // `TCell<Q, [T]>` has the same layout as `[TCell<Q, T>]`
+        unsafe { &*(self as *const TCell<Q, [T]> as *const [TCell<Q, T>]) }
+    }
+
+    /// View a slice of cells as a single cell containing a slice.
+    /// This is the reverse of
+    /// [`as_slice_of_cells`](#method.as_slice_of_cells).
+    #[inline]
+    pub fn from_slice_of_cells(cells: &[TCell<Q, T>]) -> &TCell<Q, [T]> {
+        // Safe for the same reason as `as_slice_of_cells`
+        unsafe { &*(cells as *const [TCell<Q, T>] as *const TCell<Q, [T]>) }
+    }
+
+    /// Get the number of elements in the slice.  This doesn't need
+    /// the owner, since the length is not part of the contents.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.as_slice_of_cells().len()
+    }
+
+    /// Test whether the slice is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Divide a cell containing a slice into two cells at an index,
+    /// like `slice::split_at`.  The two halves don't overlap, so can
+    /// be borrowed mutably at the same time, for example with `rw2`.
+    /// This also holds when `mid` is 0 or `len`, since an empty half
+    /// is placed at a dangling address outside the slice.  Panics if
+    /// `mid > len`.
+    #[inline]
+    pub fn split_at(&self, mid: usize) -> (&Self, &Self) {
+        let (left, right) = self.as_slice_of_cells().split_at(mid);
+        (Self::from_split(left), Self::from_split(right))
+    }
+
+    /// Get a cell for the first element of the slice and a cell for
+    /// the rest, like `slice::split_first`, or `None` if the slice is
+    /// empty.
+    #[inline]
+    pub fn split_first(&self) -> Option<(&TCell<Q, T>, &Self)> {
+        let (first, rest) = self.as_slice_of_cells().split_first()?;
+        Some((first, Self::from_split(rest)))
+    }
+
+    // Convert one part of a split back into a cell.  An empty part
+    // would otherwise sit at the edge of the other part, and so be
+    // treated as overlapping it (see `ranges_overlap`), so give it a
+    // dangling address instead.  It grants access to nothing, so it
+    // doesn't matter where it points.
+    #[inline]
+    fn from_split(cells: &[TCell<Q, T>]) -> &Self {
+        if cells.is_empty() {
+            let dangling = core::ptr::NonNull::<TCell<Q, T>>::dangling();
+            // Safe because a zero-length slice may use any non-null,
+            // aligned pointer
+            Self::from_slice_of_cells(unsafe { core::slice::from_raw_parts(dangling.as_ptr(), 0) })
+        } else {
+            Self::from_slice_of_cells(cells)
+        }
+    }
+}
+
+impl<Q, T> TCell<Q, T> {
+    /// Create an array of cells from an array of values.  This is a
+    /// `const fn`, so can be used to build a `static` table of cells:
+    ///
+    /// ```
+    ///# use qcell::{TCell, TCellOwner};
+    ///# struct Marker;
+    /// static TABLE: [TCell<Marker, u32>; 3] = TCell::new_array([1, 2, 3]);
+    /// let mut owner = TCellOwner::<Marker>::new();
+    /// *owner.rw(&TABLE[1]) += 10;
+    /// assert_eq!(owner.get(&TABLE[1]), 12);
+    /// ```
+    #[inline]
+    pub const fn new_array<const N: usize>(values: [T; N]) -> [TCell<Q, T>; N] {
+        let values = ManuallyDrop::new(values);
+        // Safe because `TCell<Q, T>` has the same layout as `T`, so
+        // the arrays have the same layout, and `values` is not
+        // dropped
+        unsafe {
+            core::ptr::read(&values as *const ManuallyDrop<[T; N]> as *const [TCell<Q, T>; N])
+        }
+    }
+
+    /// Convert a vector of values into a vector of cells, reusing
+    /// the same allocation.  No owner is needed, since the marker type
+    /// alone identifies the owner.
+    #[cfg(feature = "alloc")]
+    pub fn wrap_vec(values: Vec<T>) -> Vec<TCell<Q, T>> {
+        let mut values = ManuallyDrop::new(values);
+        let (ptr, len, cap) = (values.as_mut_ptr(), values.len(), values.capacity());
+        // Safe because `TCell<Q, T>` has the same layout as `T`, so the
+        // allocation is valid for the same number of cells, and
+        // `values` is not dropped
+        unsafe { Vec::from_raw_parts(ptr as *mut TCell<Q, T>, len, cap) }
+    }
+}
+
+impl<Q, T, const N: usize> TCell<Q, [T; N]> {
+    /// Create a cell containing an array.  This is the same as
+    /// `TCell::new`, but makes the intent clearer alongside
+    /// [`into_array`](#method.into_array).
+    #[inline]
+    pub const fn from_array(values: [T; N]) -> Self {
+        TCell::new(values)
+    }
+
+    /// Convert a cell containing an array into an array of cells, one
+    /// per element.  This is the by-value equivalent of
+    /// [`as_slice_of_cells`](#method.as_slice_of_cells).
+    #[inline]
+    pub fn into_array(self) -> [TCell<Q, T>; N] {
+        TCell::new_array(self.into_inner())
+    }
+}
+
+impl<Q: 'static, T, const N: usize> TCell<Q, [T; N]> {
+    /// Borrow all the elements of an array cell immutably, as an
+    /// array of references.
+    #[inline]
+    pub fn each_ref<'a>(&'a self, owner: &'a TCellOwner<Q>) -> [&'a T; N] {
+        owner.ro(self).each_ref()
+    }
+
+    /// Borrow all the elements of an array cell mutably, as an array
+    /// of references.  The elements never overlap, so no check is
+    /// needed.
+    #[inline]
+    pub fn each_mut<'a>(&'a self, owner: &'a mut TCellOwner<Q>) -> [&'a mut T; N] {
+        owner.rw(self).each_mut()
+    }
+}
+
+impl<Q, T> TCell<Q, MaybeUninit<T>> {
+    /// Create a new cell with uninitialized contents, so that cells
+    /// can be allocated and linked up before their contents are
+    /// known.  Write the contents with [`TCellOwner::write`], and
+    /// then convert with [`assume_init`] or [`assume_init_ref`].
+    ///
+    /// ```
+    ///# use qcell::{TCell, TCellOwner};
+    ///# use std::mem::MaybeUninit;
+    ///# struct Marker;
+    /// let mut owner = TCellOwner::<Marker>::new();
+    /// let cell = TCell::<Marker, MaybeUninit<u32>>::new_uninit();
+    /// owner.write(&cell, 100);
+    /// let cell = unsafe { cell.assume_init() };
+    /// assert_eq!(owner.get(&cell), 100);
+    /// ```
+    ///
+    /// [`TCellOwner::write`]: struct.TCellOwner.html#method.write
+    /// [`assume_init`]: #method.assume_init
+    /// [`assume_init_ref`]: #method.assume_init_ref
+    #[inline]
+    pub const fn new_uninit() -> Self {
+        TCell::new(MaybeUninit::uninit())
+    }
+
+    /// Convert a cell with uninitialized contents into a cell with
+    /// initialized contents, as for `MaybeUninit::assume_init`.
+    ///
+    /// # Safety
+    ///
+    /// The contents must have been initialized, for example with
+    /// [`TCellOwner::write`].
+    ///
+    /// [`TCellOwner::write`]: struct.TCellOwner.html#method.write
+    #[inline]
+    pub unsafe fn assume_init(self) -> TCell<Q, T> {
+        TCell::new(self.into_inner().assume_init())
+    }
+
+    /// View a reference to a cell with uninitialized contents as a
+    /// reference to a cell with initialized contents, without moving
+    /// it, for example when the cell is already shared through an
+    /// `Rc`.
+    ///
+    /// # Safety
+    ///
+    /// The contents must have been initialized, for example with
+    /// [`TCellOwner::write`], and must not be de-initialized again
+    /// whilst the returned reference exists.
+    ///
+    /// [`TCellOwner::write`]: struct.TCellOwner.html#method.write
+    #[inline]
+    pub unsafe fn assume_init_ref(&self) -> &TCell<Q, T> {
+        // `TCell` and `MaybeUninit` are both `repr(transparent)`, so
+        // this has the same layout as `TCell<Q, T>`
+        &*(self as *const Self as *const TCell<Q, T>)
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<Q, T: core::ops::CoerceUnsized<U>, U> core::ops::CoerceUnsized<TCell<Q, U>> for TCell<Q, T> {}
+
+impl<Q, T: Default> Default for TCell<Q, T> {
+    fn default() -> Self {
+        TCell::new(T::default())
+    }
+}
+
+impl<Q, T> From<T> for TCell<Q, T> {
+    fn from(value: T) -> Self {
+        TCell::new(value)
+    }
+}
+
+/// Convert a `RefCell` into a `TCell`, to allow a structure to be
+/// migrated from `RefCell` a piece at a time.  Since `From<T>` also
+/// applies, the type of the contents must be known, for example
+/// `TCell::<Q, u32>::from(refcell)`.
+impl<Q, T> From<RefCell<T>> for TCell<Q, T> {
+    fn from(cell: RefCell<T>) -> Self {
+        TCell::new(cell.into_inner())
+    }
+}
+
+/// Convert a `Cell` into a `TCell`.
+impl<Q, T> From<Cell<T>> for TCell<Q, T> {
+    fn from(cell: Cell<T>) -> Self {
+        TCell::new(cell.into_inner())
+    }
+}
+
+/// The contents are not shown, since they can only be accessed
+/// through the owner.
+impl<Q, T: ?Sized> fmt::Debug for TCell<Q, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TCell").finish_non_exhaustive()
+    }
+}
+
+/// Types that give access to a [`TCell`] reference, such as the cell
+/// itself, `&TCell`, `Rc<TCell>`, `Arc<TCell>` or `Box<TCell>`.
+/// This allows generic code to accept any of the common ways of
+/// holding a cell.
+///
+/// [`TCell`]: struct.TCell.html
+pub trait AsCellRef<Q> {
+    /// Type contained in the cell
+    type Value: ?Sized;
+
+    /// Get a reference to the cell
+    fn as_cell_ref(&self) -> &TCell<Q, Self::Value>;
+}
+
+impl<Q, T: ?Sized> AsCellRef<Q> for TCell<Q, T> {
+    type Value = T;
+    #[inline]
+    fn as_cell_ref(&self) -> &TCell<Q, T> {
+        self
+    }
+}
+
+impl<Q, C: AsCellRef<Q> + ?Sized> AsCellRef<Q> for &C {
+    type Value = C::Value;
+    #[inline]
+    fn as_cell_ref(&self) -> &TCell<Q, C::Value> {
+        (**self).as_cell_ref()
+    }
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! as_cell_ref_for_pointer {
+    ($($P:ident),*) => {$(
+        impl<Q, T: ?Sized> AsCellRef<Q> for $P<TCell<Q, T>> {
+            type Value = T;
+            #[inline]
+            fn as_cell_ref(&self) -> &TCell<Q, T> {
+                self
+            }
+        }
+    )*};
+}
+
+#[cfg(feature = "alloc")]
+as_cell_ref_for_pointer!(Box, Rc, Arc);
+
+/// Extension trait for iterators over references to cells, for
+/// example `cells.iter().ro(&owner)`.  See [`AsCellRef`] for the
+/// supported ways of holding a cell.
+///
+/// [`AsCellRef`]: trait.AsCellRef.html
+pub trait CellIterExt: Iterator + Sized {
+    /// Adapt this iterator to return immutable references to the
+    /// contents of the cells instead.  The owner remains borrowed
+    /// immutably for as long as the adapted iterator exists.
+    #[inline]
+    fn ro<Q: 'static>(self, owner: &TCellOwner<Q>) -> RoIter<'_, Q, Self> {
+        RoIter { iter: self, owner }
+    }
+
+    /// Adapt this iterator to return clones of the contents of the
+    /// cells instead.
+    #[inline]
+    fn ro_cloned<'a, Q, T>(
+        self,
+        owner: &'a TCellOwner<Q>,
+    ) -> core::iter::Cloned<RoIter<'a, Q, Self>>
+    where
+        Q: 'static,
+        T: Clone + 'a,
+        RoIter<'a, Q, Self>: Iterator<Item = &'a T>,
+    {
+        self.ro(owner).cloned()
+    }
+}
+
+impl<I: Iterator> CellIterExt for I {}
+
+impl<Q, T> private::Sealed for TCell<Q, Option<T>> {}
+
+impl<Q: 'static, T> OptionCellExt<T> for TCell<Q, Option<T>> {
+    type Owner = TCellOwner<Q>;
+
+    #[inline]
+    fn ro_option<'a>(&'a self, owner: &'a TCellOwner<Q>) -> &'a Option<T> {
+        owner.ro(self)
+    }
+
+    #[inline]
+    fn rw_option<'a>(&'a self, owner: &'a mut TCellOwner<Q>) -> &'a mut Option<T> {
+        owner.rw(self)
+    }
+}
+
+impl<Q: 'static> GuardedCellReader for TCellOwner<Q> {
+    type Cell<T> = TCell<Q, T>;
+    type Ro<'a, T: 'a> = &'a T;
+
+    #[inline]
+    fn ro_guard<'a, T>(&'a self, cell: &'a TCell<Q, T>) -> &'a T {
+        self.ro(cell)
+    }
+}
+
+impl<Q: 'static> GuardedCellOwner for TCellOwner<Q> {
+    type Rw<'a, T: 'a> = &'a mut T;
+
+    #[inline]
+    fn rw_guard<'a, T>(&'a mut self, cell: &'a TCell<Q, T>) -> &'a mut T {
+        self.rw(cell)
+    }
+
+    #[inline]
+    fn rw2_guard<'a, T, U>(
+        &'a mut self,
+        cell1: &'a TCell<Q, T>,
+        cell2: &'a TCell<Q, U>,
+    ) -> (&'a mut T, &'a mut U) {
+        self.rw2(cell1, cell2)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Q: 'static> GuardedCellReader for SharedTCellOwner<Q> {
+    type Cell<T> = TCell<Q, T>;
+    type Ro<'a, T: 'a> = Ref<'a, T>;
+
+    #[inline]
+    fn ro_guard<'a, T>(&'a self, cell: &'a TCell<Q, T>) -> Ref<'a, T> {
+        self.ro(cell)
+    }
+}
+
+/// The guards hold a borrow of the shared owner, so whilst any `Rw`
+/// guard exists, other handles can't borrow the owner, and whilst any
+/// `Ro` guard exists, they can only borrow it immutably.
+#[cfg(feature = "std")]
+impl<Q: 'static> GuardedCellOwner for SharedTCellOwner<Q> {
+    type Rw<'a, T: 'a> = RefMut<'a, T>;
+
+    #[inline]
+    #[track_caller]
+    fn rw_guard<'a, T>(&'a mut self, cell: &'a TCell<Q, T>) -> RefMut<'a, T> {
+        let value = cell.value.get();
+        // Safe because the contents can't be borrowed at all whilst
+        // the owner is borrowed mutably, and `cell` outlives the
+        // `RefMut`
+        RefMut::map(self.borrow_mut(), |_| unsafe { &mut *value })
+    }
+
+    #[inline]
+    #[track_caller]
+    fn rw2_guard<'a, T, U>(
+        &'a mut self,
+        cell1: &'a TCell<Q, T>,
+        cell2: &'a TCell<Q, U>,
+    ) -> (RefMut<'a, T>, RefMut<'a, U>) {
+        let mut owner = self.borrow_mut();
+        let (v1, v2) = owner.rw2(cell1, cell2);
+        let (v1, v2) = (v1 as *mut T, v2 as *mut U);
+        // Safe as for `rw_guard`, and `rw2` has checked that the cells
+        // don't overlap
+        RefMut::map_split(owner, |_| unsafe { (&mut *v1, &mut *v2) })
+    }
+}
+
+#[cfg(feature = "alloc")]
+crate::traits::rc_cell_ext!([Q: 'static, T: ?Sized] TCell<Q, T>, TCellOwner<Q>, T);
+
+/// Iterator returned by [`CellIterExt::ro`].
+///
+/// [`CellIterExt::ro`]: trait.CellIterExt.html#method.ro
+pub struct RoIter<'a, Q: 'static, I> {
+    iter: I,
+    owner: &'a TCellOwner<Q>,
+}
+
+impl<'a, Q, C, I> Iterator for RoIter<'a, Q, I>
+where
+    Q: 'static,
+    C: AsCellRef<Q> + ?Sized + 'a,
+    I: Iterator<Item = &'a C>,
+{
+    type Item = &'a C::Value;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a C::Value> {
+        let owner = self.owner;
+        self.iter.next().map(|c| owner.ro(c.as_cell_ref()))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Handle to an owner shared within a thread, created by
+/// [`TCellOwner::new_or_share`].  Since several handles may refer to
+/// the same owner, borrowing it is checked at runtime, like a
+/// `RefCell`.
+///
+/// [`TCellOwner::new_or_share`]: struct.TCellOwner.html#method.new_or_share
+#[cfg(feature = "std")]
+pub struct SharedTCellOwner<Q: 'static> {
+    owner: Rc<RefCell<TCellOwner<Q>>>,
+}
+
+#[cfg(feature = "std")]
+impl<Q: 'static> Clone for SharedTCellOwner<Q> {
+    fn clone(&self) -> Self {
+        Self {
+            owner: self.owner.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Q: 'static> Drop for SharedTCellOwner<Q> {
+    fn drop(&mut self) {
+        if Rc::strong_count(&self.owner) == 1 {
+            // Last handle, so forget the entry.  This may run during
+            // thread-local teardown, in which case there is nothing
+            // left to tidy up.
+            let _ = SHARED_OWNERS.try_with(|map| {
+                if let Ok(mut map) = map.try_borrow_mut() {
+                    map.remove(&TypeId::of::<Q>());
+                }
+            });
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Q: 'static> fmt::Debug for SharedTCellOwner<Q> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedTCellOwner")
+            .field("marker", &core::any::type_name::<Q>())
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Q: 'static> SharedTCellOwner<Q> {
+    /// Borrow contents of a `TCell` immutably (read-only).  The owner
+    /// is borrowed as long as the returned `Ref` exists.  Panics if
+    /// the owner is currently borrowed mutably through any handle.
+    #[inline]
+    pub fn ro<'a, T: ?Sized>(&'a self, tc: &'a TCell<Q, T>) -> Ref<'a, T> {
+        let value = tc.value.get();
+        // Safe because the contents can't be borrowed mutably whilst
+        // the owner is borrowed, and `tc` outlives the `Ref`
+        Ref::map(self.owner.borrow(), |_| unsafe { &*value })
+    }
+
+    /// Borrow the owner mutably, giving access to `rw`, `rw2` and so
+    /// on.  Panics if the owner is already borrowed through any
+    /// handle.
+    #[inline]
+    #[track_caller]
+    pub fn borrow_mut(&self) -> RefMut<'_, TCellOwner<Q>> {
+        self.owner.borrow_mut()
+    }
+
+    /// Borrow the owner mutably, or return an error if the owner is
+    /// already borrowed through any handle.
+    #[inline]
+    pub fn try_borrow_mut(&self) -> Result<RefMut<'_, TCellOwner<Q>>, BorrowMutError> {
+        self.owner.try_borrow_mut()
+    }
+}
+
+/// Claim on the marker type `Q`, which can be converted into a
+/// [`TCellOwner`] later.  This allows a marker type to be claimed
+/// early, for example at startup so that a conflict is detected
+/// straight away, whilst only creating the owner later, perhaps in
+/// another thread.  Dropping the reservation without converting it
+/// releases the marker type again.
+///
+/// [`TCellOwner`]: struct.TCellOwner.html
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct OwnerReservation<Q: 'static> {
+    owner: TCellOwner<Q>,
+}
+
+#[cfg(feature = "std")]
+impl<Q: 'static> OwnerReservation<Q> {
+    /// Claim the marker type `Q`.  Fails if an owner or reservation
+    /// with the same marker type already exists, in the same way as
+    /// [`TCellOwner::try_new`].
+    ///
+    /// [`TCellOwner::try_new`]: struct.TCellOwner.html#method.try_new
+    #[track_caller]
+    pub fn claim() -> Result<Self, DuplicateOwnerError> {
+        Ok(Self {
+            owner: TCellOwner::try_new()?,
+        })
+    }
+
+    /// Convert the reservation into the owner.  The registry is not
+    /// touched again.
+    #[inline]
+    pub fn into_owner(self) -> TCellOwner<Q> {
+        self.owner
+    }
+}
+
+/// Read-only owner created by [`TCellOwner::freeze`].  Clones of this
+/// can be read from many threads at once.  All clones share the
+/// original owner, so the marker type stays claimed until the last
+/// clone is dropped or thawed.
+///
+/// [`TCellOwner::freeze`]: struct.TCellOwner.html#method.freeze
+#[cfg(feature = "std")]
+pub struct FrozenTCellOwner<Q: 'static> {
+    owner: Arc<TCellOwner<Q>>,
+}
+
+#[cfg(feature = "std")]
+impl<Q: 'static> Clone for FrozenTCellOwner<Q> {
+    fn clone(&self) -> Self {
+        Self {
+            owner: self.owner.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Q: 'static> fmt::Debug for FrozenTCellOwner<Q> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrozenTCellOwner")
+            .field("marker", &core::any::type_name::<Q>())
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Q: 'static> FrozenTCellOwner<Q> {
+    /// Borrow contents of a `TCell` immutably (read-only).
+    #[inline]
+    pub fn ro<'a, T: ?Sized>(&'a self, tc: &'a TCell<Q, T>) -> &'a T {
+        self.owner.ro(tc)
+    }
+
+    /// Get the [`TCellOwner`] back, allowing mutable access again.
+    /// This only succeeds if this is the last remaining clone.
+    /// Otherwise `self` is returned unchanged as the error.
+    ///
+    /// [`TCellOwner`]: struct.TCellOwner.html
+    pub fn thaw(self) -> Result<TCellOwner<Q>, Self> {
+        Arc::try_unwrap(self.owner).map_err(|owner| Self { owner })
+    }
+}
+
+/// Token granting read-only access to the `TCell` instances of a
+/// [`TCellOwner`], returned by [`TCellOwner::read_token`].
+///
+/// Unlike `&TCellOwner`, this is always `Copy`, `Send` and `Sync`,
+/// regardless of the marker type.  It only allows reading contents
+/// that are `Sync`.
+///
+/// [`TCellOwner`]: struct.TCellOwner.html
+/// [`TCellOwner::read_token`]: struct.TCellOwner.html#method.read_token
+pub struct ReadToken<'a, Q: 'static> {
+    owner: PhantomData<&'a ()>,
+    typ: PhantomData<fn() -> Q>,
+}
+
+impl<'a, Q: 'static> Clone for ReadToken<'a, Q> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, Q: 'static> Copy for ReadToken<'a, Q> {}
+
+impl<'a, Q: 'static> fmt::Debug for ReadToken<'a, Q> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadToken")
+            .field("marker", &core::any::type_name::<Q>())
+            .finish()
+    }
+}
+
+impl<'a, Q: 'static> ReadToken<'a, Q> {
+    /// Borrow contents of a `TCell` immutably (read-only).  The
+    /// returned reference cannot outlive the borrow of the owner
+    /// that the token was created from.
+    #[inline]
+    pub fn ro<'b, T: Sync + ?Sized>(&self, tc: &'b TCell<Q, T>) -> &'b T
+    where
+        'a: 'b,
+    {
+        // Safe because the token holds a shared borrow of the owner
+        // for 'a, so nothing can be borrowing the cell mutably.
+        unsafe { &*tc.value.get() }
+    }
+}
+
+// It's fine to Send a TCell to a different thread if the contained
+// type is Send, because you can only send something if nothing
+// borrows it, so nothing can be accessing its contents.  In
+// particular no `&T` obtained through an owner in the original thread
+// can still exist, since that borrows the cell too, so `T: Sync` is
+// not needed.  The marker `Q` is never instantiated, so doesn't need
+// to be `Send`.
+unsafe impl<Q, T: Send + ?Sized> Send for TCell<Q, T> {}
 
 // We can add a Sync implementation, since it's fine to send a &TCell
 // to another thread, and even mutably borrow the value there, as long
@@ -156,33 +2861,2194 @@ unsafe impl<Q, T: Send> Send for TCell<Q, T> {}
 // as those of std::sync::RwLock<T>. That's not a coincidence.
 // The way these types let you access T concurrently is the same,
 // even though the locking mechanisms are different.
-unsafe impl<Q, T: Send + Sync> Sync for TCell<Q, T> {}
+unsafe impl<Q, T: Send + Sync + ?Sized> Sync for TCell<Q, T> {}
+
+// This follows `RefCell`.  Owning a cell is unwind-safe if the
+// contents are, whatever the marker type.  However `&TCell` is not,
+// since a panic in the middle of an update through `rw` could leave
+// the contents half-modified, which could then be observed through
+// another `&TCell` after `catch_unwind`.  So there is no
+// `RefUnwindSafe` implementation, and callers that accept that risk
+// must use `AssertUnwindSafe`.
+impl<Q, T: UnwindSafe + ?Sized> UnwindSafe for TCell<Q, T> {}
+
+/// Cell that can be written only once, through a [`TCellOwner`], and
+/// then read without restriction.
+///
+/// Setting the value needs `&mut` on the owner, and checking whether
+/// it has been set needs `&` on the owner.  However once the value
+/// has been set it never changes again, so the references returned
+/// from [`get`], [`set`] and [`get_or_init`] only borrow the cell,
+/// not the owner.  So for example a configuration value can be
+/// initialized once whilst holding the owner mutably, and the
+/// reference then passed to code that doesn't have access to the
+/// owner at all.
+///
+/// [`TCellOwner`]: struct.TCellOwner.html
+/// [`get`]: #method.get
+/// [`set`]: #method.set
+/// [`get_or_init`]: #method.get_or_init
+pub struct OnceTCell<Q, T> {
+    owner: PhantomData<*const Q>,
+    value: UnsafeCell<Option<T>>,
+}
+
+impl<Q, T> OnceTCell<Q, T> {
+    /// Create a new empty `OnceTCell`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            owner: PhantomData,
+            value: UnsafeCell::new(None),
+        }
+    }
+
+    /// Take the value out of the cell, if it has been set.
+    #[inline]
+    pub fn into_inner(self) -> Option<T> {
+        self.value.into_inner()
+    }
+}
+
+impl<Q: 'static, T> OnceTCell<Q, T> {
+    /// Get a reference to the value, or `None` if it has not been set
+    /// yet.  The owner is only needed for the check, and the returned
+    /// reference is not bound to the owner borrow.
+    #[inline]
+    pub fn get<'a>(&'a self, _owner: &TCellOwner<Q>) -> Option<&'a T> {
+        // Safe because the owner borrow means no `set` is running,
+        // and once set the value never changes whilst `self` is
+        // borrowed
+        unsafe { (*self.value.get()).as_ref() }
+    }
+
+    /// Set the value, returning a reference to it.  If the value has
+    /// already been set, the cell is left unchanged and `value` is
+    /// returned as the error.
+    #[inline]
+    pub fn set<'a>(&'a self, owner: &mut TCellOwner<Q>, value: T) -> Result<&'a T, T> {
+        let mut value = Some(value);
+        let r = self.get_or_init(owner, || value.take().unwrap());
+        match value {
+            None => Ok(r),
+            Some(value) => Err(value),
+        }
+    }
+
+    /// Get a reference to the value, first setting it to the result
+    /// of the given closure if it has not been set yet.  The closure
+    /// runs at most once.  If it panics, the cell is left unset.
+    pub fn get_or_init<'a>(&'a self, _owner: &mut TCellOwner<Q>, f: impl FnOnce() -> T) -> &'a T {
+        // Safe because the mutable owner borrow means that nothing
+        // else can be checking or setting the value, and whilst it is
+        // unset there can be no outstanding references.  The closure
+        // can't reach the owner, so can't reenter.
+        unsafe {
+            if (*self.value.get()).is_none() {
+                let value = f();
+                *self.value.get() = Some(value);
+            }
+            (*self.value.get()).as_ref().unwrap()
+        }
+    }
+}
+
+impl<Q, T> Default for OnceTCell<Q, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Q, T> fmt::Debug for OnceTCell<Q, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnceTCell").finish_non_exhaustive()
+    }
+}
+
+// Same reasoning as for TCell.  The value may be set from any thread
+// that has the owner, and then read from many threads at once.
+unsafe impl<Q, T: Send> Send for OnceTCell<Q, T> {}
+unsafe impl<Q, T: Send + Sync> Sync for OnceTCell<Q, T> {}
+
+/// Cell that can be stored in a `static` even when the contents are
+/// only `Send`, for example a `Cell` or a `RefCell`.
+///
+/// A `TCell` is already `Sync` when its contents are `Send + Sync`,
+/// so can be used directly in a `static` in that case.  However it
+/// can't be `Sync` for contents that are only `Send`, since a shared
+/// owner (for example a [`FrozenTCellOwner`]) would let several
+/// threads read the contents at once.  `StaticTCell` avoids this by
+/// only allowing shared reads through [`ro`] when the contents are
+/// `Sync`, and otherwise requiring `&mut` on the owner through
+/// [`rw`].  Since there is only one owner per marker type in the
+/// whole process, this means that only one thread at a time can
+/// access the contents, in the same way as for a `Mutex`.  Moving
+/// the owner between threads, for example by keeping it in a
+/// `Mutex`, is up to the caller.
+///
+/// ```
+///# use qcell::{StaticTCell, TCellOwner};
+///# use std::cell::Cell;
+/// struct Marker;
+/// static COUNT: StaticTCell<Marker, Cell<u32>> = StaticTCell::new(Cell::new(0));
+/// let mut owner = TCellOwner::<Marker>::new();
+/// COUNT.rw(&mut owner).set(1);
+/// assert_eq!(COUNT.rw(&mut owner).get(), 1);
+/// ```
+///
+/// [`FrozenTCellOwner`]: struct.FrozenTCellOwner.html
+/// [`ro`]: #method.ro
+/// [`rw`]: #method.rw
+pub struct StaticTCell<Q, T> {
+    cell: TCell<Q, T>,
+}
+
+impl<Q, T> StaticTCell<Q, T> {
+    /// Create a new `StaticTCell` owned for borrowing purposes by the
+    /// `TCellOwner` derived from the same marker type `Q`.
+    #[inline]
+    pub const fn new(value: T) -> Self {
+        Self {
+            cell: TCell::new(value),
+        }
+    }
+
+    /// Consume the cell, returning its contents.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.cell.into_inner()
+    }
+
+    /// Borrow the contents mutably without an owner.  This is safe
+    /// because the `&mut` reference to the cell proves that nothing
+    /// else can currently access it.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.cell.get_mut()
+    }
+}
+
+impl<Q: 'static, T> StaticTCell<Q, T> {
+    /// Borrow the contents immutably (read-only).  Since the owner
+    /// may be shared between threads, this needs the contents to be
+    /// `Sync`.
+    #[inline]
+    pub fn ro<'a>(&'a self, owner: &'a TCellOwner<Q>) -> &'a T
+    where
+        T: Sync,
+    {
+        owner.ro(&self.cell)
+    }
+
+    /// Borrow the contents mutably (read-write).  This can be used
+    /// whatever the contents, since holding the owner mutably means
+    /// that no other thread can be accessing the cell.
+    #[inline]
+    pub fn rw<'a>(&'a self, owner: &'a mut TCellOwner<Q>) -> &'a mut T {
+        owner.rw(&self.cell)
+    }
+}
+
+impl<Q, T: Default> Default for StaticTCell<Q, T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<Q, T> fmt::Debug for StaticTCell<Q, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticTCell").finish_non_exhaustive()
+    }
+}
+
+// Only T: Send is needed for Sync here, unlike TCell, because shared
+// reads through `ro` need T: Sync, and otherwise all access needs
+// `&mut` on the single owner, which can only be held by one thread
+// at a time.  This is the same reasoning as for Mutex<T>.
+unsafe impl<Q, T: Send> Send for StaticTCell<Q, T> {}
+unsafe impl<Q, T: Send> Sync for StaticTCell<Q, T> {}
+
+/// Cell for `Copy` values, such as flags and counters, which can be
+/// both read and written with just a shared borrow of the
+/// [`TCellOwner`].
+///
+/// The contents are kept in a `std::cell::Cell`, and no references to
+/// them are ever handed out, so writing through `&TCellOwner` can't
+/// invalidate any outstanding borrow.  This means that updates don't
+/// need to be serialized through `&mut TCellOwner`, and can be made
+/// whilst other cells are borrowed with `ro`:
+///
+/// ```
+///# use qcell::{TCell, TCellCopy, TCellOwner};
+///# struct Marker;
+/// let owner = TCellOwner::<Marker>::new();
+/// let name = TCell::<Marker, _>::new(String::from("node"));
+/// let visits = TCellCopy::<Marker, u32>::new(0);
+/// let name_ref = owner.ro(&name);
+/// visits.update(&owner, |v| v + 1);
+/// assert_eq!(name_ref, "node");
+/// assert_eq!(visits.get(&owner), 1);
+/// ```
+///
+/// Like `Cell`, this type is not `Sync`, so all access to a given
+/// cell happens in one thread at a time, even though the owner may
+/// be shared between threads.
+///
+/// [`TCellOwner`]: struct.TCellOwner.html
+pub struct TCellCopy<Q, T: Copy> {
+    // Use *const to disable Send, which is re-enabled below
+    owner: PhantomData<*const Q>,
+    value: Cell<T>,
+}
+
+impl<Q, T: Copy> TCellCopy<Q, T> {
+    /// Create a new `TCellCopy` owned for borrowing purposes by the
+    /// `TCellOwner` derived from the same marker type `Q`.
+    #[inline]
+    pub const fn new(value: T) -> Self {
+        Self {
+            owner: PhantomData,
+            value: Cell::new(value),
+        }
+    }
+
+    /// Consume the cell, returning its contents.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Borrow the contents mutably without an owner.  This is safe
+    /// because the `&mut` reference to the cell proves that nothing
+    /// else can currently access it.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+impl<Q: 'static, T: Copy> TCellCopy<Q, T> {
+    /// Get a copy of the contents.
+    #[inline]
+    pub fn get(&self, _owner: &TCellOwner<Q>) -> T {
+        self.value.get()
+    }
+
+    /// Set the contents.
+    #[inline]
+    pub fn set(&self, _owner: &TCellOwner<Q>, value: T) {
+        self.value.set(value);
+    }
+
+    /// Replace the contents, returning the old value.
+    #[inline]
+    pub fn replace(&self, _owner: &TCellOwner<Q>, value: T) -> T {
+        self.value.replace(value)
+    }
+
+    /// Take the contents, leaving `Default::default()` in its place.
+    #[inline]
+    pub fn take(&self, _owner: &TCellOwner<Q>) -> T
+    where
+        T: Default,
+    {
+        self.value.take()
+    }
+
+    /// Update the contents by passing a copy to the given closure and
+    /// storing the result, which is also returned.
+    #[inline]
+    pub fn update(&self, _owner: &TCellOwner<Q>, f: impl FnOnce(T) -> T) -> T {
+        let value = f(self.value.get());
+        self.value.set(value);
+        value
+    }
+}
+
+impl<Q, T: Copy + Default> Default for TCellCopy<Q, T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+/// The contents are not shown, since they can only be accessed
+/// through the owner.
+impl<Q, T: Copy> fmt::Debug for TCellCopy<Q, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TCellCopy").finish_non_exhaustive()
+    }
+}
+
+// Sending the cell transfers it completely to the other thread, as
+// for TCell.  It must never be Sync, since writes only need a shared
+// owner borrow, and that is guaranteed by the `Cell` field.
+unsafe impl<Q, T: Copy + Send> Send for TCellCopy<Q, T> {}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::{
+        IndexError, OnceTCell, OptionCellExt, OwnerReservation, StaticTCell, TCell, TCellCopy,
+        TCellMarker, TCellMarkerFlag, TCellOwner,
+    };
+    use std::any::TypeId;
+    use std::rc::Rc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    // Block until `len` threads or tasks are queued for marker `Q`, so
+    // that tests don't depend on sleeps to order the waiters
+    fn wait_until_queued<Q: 'static>(len: usize) {
+        while super::lock_registry()
+            .queues
+            .get(&TypeId::of::<Q>())
+            .map_or(0, |q| q.len())
+            != len
+        {
+            std::thread::yield_now();
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn tcell_singleton_1() {
+        struct Marker;
+        let _owner1 = TCellOwner::<Marker>::new();
+        let _owner2 = TCellOwner::<Marker>::new(); // Panic here
+    }
+
+    #[test]
+    fn tcell_singleton_2() {
+        struct Marker;
+        let owner1 = TCellOwner::<Marker>::new();
+        drop(owner1);
+        let _owner2 = TCellOwner::<Marker>::new();
+    }
+
+    #[test]
+    fn tcell_singleton_3() {
+        struct Marker1;
+        struct Marker2;
+        let _owner1 = TCellOwner::<Marker1>::new();
+        let _owner2 = TCellOwner::<Marker2>::new();
+    }
+
+    #[test]
+    fn tcell_try_new() {
+        struct Marker;
+        let owner1 = TCellOwner::<Marker>::try_new().unwrap();
+        let err = TCellOwner::<Marker>::try_new().err().unwrap();
+        assert!(err.to_string().starts_with(
+            "Illegal to create two TCellOwner instances with the same marker type parameter"
+        ));
+        assert!(err.marker().ends_with("tcell_try_new::Marker"));
+        drop(owner1);
+        let _owner2 = TCellOwner::<Marker>::new();
+    }
+
+    #[test]
+    fn tcell_duplicate_owner_location() {
+        struct Marker;
+        let line = line!() + 1;
+        let owner1 = TCellOwner::<Marker>::new();
+        let err = TCellOwner::<Marker>::try_new().err().unwrap();
+        if cfg!(debug_assertions) {
+            let location = err.first_created_at().unwrap();
+            assert_eq!((location.file(), location.line()), (file!(), line));
+        } else {
+            assert!(err.first_created_at().is_none());
+        }
+        let payload = std::panic::catch_unwind(TCellOwner::<Marker>::new)
+            .err()
+            .unwrap();
+        let msg = payload.downcast_ref::<String>().unwrap();
+        assert!(msg.contains("tcell_duplicate_owner_location::Marker"));
+        if cfg!(debug_assertions) {
+            assert!(msg.contains(&format!("{}:{}:", file!(), line)));
+        }
+        drop(owner1);
+        let owner2 = TCellOwner::<Marker>::try_new().unwrap();
+        assert!(matches!(owner2.claim, super::Claim::Registry));
+    }
+
+    #[test]
+    fn tcell_new_fast() {
+        tcell_marker!(
+            struct Marker;
+        );
+        let mut owner = TCellOwner::<Marker>::new_fast();
+        let c1 = TCell::new(1u32);
+        *owner.rw(&c1) += 1;
+        assert!(TCellOwner::<Marker>::try_new_fast().is_err());
+        assert!(TCellOwner::<Marker>::try_new().is_err());
+        std::thread::spawn(|| assert!(TCellOwner::<Marker>::try_new_fast().is_err()))
+            .join()
+            .unwrap();
+        drop(owner);
+        let owner = TCellOwner::<Marker>::try_new_fast().unwrap();
+        assert_eq!(owner.get(&c1), 2);
+        drop(owner);
+        // Registry-based creation stays unavailable for this marker
+        assert!(TCellOwner::<Marker>::try_new().is_err());
+        assert!(TCellOwner::<Marker>::is_active());
+        let owner = std::thread::spawn(TCellOwner::<Marker>::new_fast)
+            .join()
+            .unwrap();
+        assert!(TCellOwner::<Marker>::try_new_fast().is_err());
+        drop(owner);
+    }
+
+    #[test]
+    fn tcell_new_fast_after_registry_owner() {
+        tcell_marker!(
+            struct Marker;
+        );
+        let owner = TCellOwner::<Marker>::new();
+        assert!(TCellOwner::<Marker>::try_new_fast().is_err());
+        drop(owner);
+        let _owner = TCellOwner::<Marker>::new_fast();
+    }
+
+    #[test]
+    fn tcell_marker_manual_impl() {
+        struct Slot<const N: usize>;
+        static FLAGS: [TCellMarkerFlag; 2] = [TCellMarkerFlag::new(), TCellMarkerFlag::new()];
+        unsafe impl TCellMarker for Slot<0> {
+            fn flag() -> &'static TCellMarkerFlag {
+                &FLAGS[0]
+            }
+        }
+        unsafe impl TCellMarker for Slot<1> {
+            fn flag() -> &'static TCellMarkerFlag {
+                &FLAGS[1]
+            }
+        }
+        let mut owner0 = TCellOwner::<Slot<0>>::new_fast();
+        let c0 = TCell::new(10u32);
+        *owner0.rw(&c0) += 1;
+        // Each slot has a flag of its own
+        let owner1 = TCellOwner::<Slot<1>>::new_fast();
+        assert!(TCellOwner::<Slot<0>>::try_new_fast().is_err());
+        assert!(TCellOwner::<Slot<1>>::try_new().is_err());
+        drop(owner1);
+        let _owner1 = TCellOwner::<Slot<1>>::new_fast();
+        assert_eq!(owner0.get(&c0), 11);
+    }
+
+    #[test]
+    fn tcell_poisoned_registry() {
+        struct Marker;
+        let owner1 = TCellOwner::<Marker>::new();
+        let result = std::thread::spawn(|| {
+            let _guard = super::lock_registry();
+            panic!("Poisoning the registry");
+        })
+        .join();
+        assert!(result.is_err());
+        assert!(super::SINGLETON_CHECK.is_poisoned());
+        assert!(TCellOwner::<Marker>::try_new().is_err());
+        drop(owner1);
+        let owner2 = TCellOwner::<Marker>::new();
+        assert!(TCellOwner::<Marker>::is_active());
+        drop(owner2);
+        assert!(!TCellOwner::<Marker>::is_active());
+    }
+
+    #[test]
+    fn tcell_try_new_threads() {
+        struct Marker;
+        let owner1 = TCellOwner::<Marker>::new();
+        // The check is process-wide, so another thread fails too
+        std::thread::spawn(|| assert!(TCellOwner::<Marker>::try_new().is_err()))
+            .join()
+            .unwrap();
+        drop(owner1);
+        std::thread::spawn(|| assert!(TCellOwner::<Marker>::try_new().is_ok()))
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn tcell_wait_for_new() {
+        struct Marker;
+        let owner = TCellOwner::<Marker>::new();
+        let handle = std::thread::spawn(|| {
+            let _owner = TCellOwner::<Marker>::wait_for_new();
+        });
+        wait_until_queued::<Marker>(1);
+        drop(owner);
+        handle.join().unwrap();
+        let _owner = TCellOwner::<Marker>::new();
+    }
+
+    #[test]
+    fn tcell_wait_for_new_timeout() {
+        struct Marker;
+        let owner = TCellOwner::<Marker>::new();
+        std::thread::spawn(|| {
+            assert!(
+                TCellOwner::<Marker>::wait_for_new_timeout(Duration::from_millis(20)).is_none()
+            );
+        })
+        .join()
+        .unwrap();
+        drop(owner);
+        assert!(TCellOwner::<Marker>::wait_for_new_timeout(Duration::from_millis(20)).is_some());
+    }
+
+    #[test]
+    fn tcell_wait_for_new_handoff() {
+        struct Marker;
+        let owner = TCellOwner::<Marker>::new();
+        let active = Arc::new(AtomicUsize::new(0));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let active = active.clone();
+                std::thread::spawn(move || {
+                    let owner = TCellOwner::<Marker>::wait_for_new();
+                    assert_eq!(active.fetch_add(1, Ordering::SeqCst), 0);
+                    std::thread::sleep(Duration::from_millis(10));
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    drop(owner);
+                })
+            })
+            .collect();
+        wait_until_queued::<Marker>(4);
+        // Queued waiters take priority over non-blocking attempts
+        drop(owner);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert!(TCellOwner::<Marker>::try_new().is_ok());
+    }
+
+    #[test]
+    fn tcell_wait_for_new_fifo() {
+        struct Marker;
+        let owner = TCellOwner::<Marker>::new();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut handles = Vec::new();
+        for i in 0..3 {
+            let order = order.clone();
+            handles.push(std::thread::spawn(move || {
+                let _owner = TCellOwner::<Marker>::wait_for_new();
+                order.lock().unwrap().push(i);
+            }));
+            // Wait for each thread to join the queue in turn
+            wait_until_queued::<Marker>(i + 1);
+        }
+        assert!(!TCellOwner::<Marker>::try_new().unwrap_err().is_reserved());
+        drop(owner);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), vec![0, 1, 2]);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn tcell_new_async_contended() {
+        use futures::channel::oneshot;
+        use futures::executor::LocalPool;
+        use futures::task::LocalSpawnExt;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        struct Marker;
+        let mut pool = LocalPool::new();
+        let spawner = pool.spawner();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let (tx, rx) = oneshot::channel::<()>();
+        let log1 = log.clone();
+        spawner
+            .spawn_local(async move {
+                let owner = TCellOwner::<Marker>::new_async().await;
+                log1.borrow_mut().push("a");
+                rx.await.unwrap();
+                drop(owner);
+                log1.borrow_mut().push("a dropped");
+            })
+            .unwrap();
+        let log2 = log.clone();
+        spawner
+            .spawn_local(async move {
+                let _owner = TCellOwner::<Marker>::new_async().await;
+                log2.borrow_mut().push("b");
+            })
+            .unwrap();
+        pool.run_until_stalled();
+        assert_eq!(*log.borrow(), vec!["a"]);
+        tx.send(()).unwrap();
+        pool.run();
+        assert_eq!(*log.borrow(), vec!["a", "a dropped", "b"]);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn tcell_new_async_cancel() {
+        use futures::task::{noop_waker_ref, ArcWake};
+        use futures::FutureExt;
+        use std::sync::atomic::AtomicBool;
+        use std::task::Context;
+
+        struct Flag(AtomicBool);
+        impl ArcWake for Flag {
+            fn wake_by_ref(arc_self: &Arc<Self>) {
+                arc_self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        struct Marker;
+        let owner = TCellOwner::<Marker>::new();
+        let mut cx = Context::from_waker(noop_waker_ref());
+        let mut fut1 = TCellOwner::<Marker>::new_async();
+        assert!(fut1.poll_unpin(&mut cx).is_pending());
+        let flag = Arc::new(Flag(AtomicBool::new(false)));
+        let waker = futures::task::waker(flag.clone());
+        let mut fut2 = TCellOwner::<Marker>::new_async();
+        assert!(fut2
+            .poll_unpin(&mut Context::from_waker(&waker))
+            .is_pending());
+
+        // fut1 is at the front of the queue, so fut2 is not woken yet
+        drop(owner);
+        assert!(!flag.0.load(Ordering::SeqCst));
+        let err = TCellOwner::<Marker>::try_new().unwrap_err();
+        assert!(err.is_reserved());
+        assert!(err.to_string().contains("reserved for a queued waiter"));
+
+        // Cancelling fut1 passes the slot on to fut2
+        drop(fut1);
+        assert!(flag.0.load(Ordering::SeqCst));
+        let owner = match fut2.poll_unpin(&mut cx) {
+            std::task::Poll::Ready(owner) => owner,
+            std::task::Poll::Pending => panic!("Expected fut2 to be ready"),
+        };
+        drop(fut2);
+        drop(owner);
+        drop(TCellOwner::<Marker>::try_new().unwrap());
+
+        // Cancelling the only waiter leaves nothing behind
+        let owner = TCellOwner::<Marker>::new();
+        let mut fut = TCellOwner::<Marker>::new_async();
+        assert!(fut.poll_unpin(&mut cx).is_pending());
+        drop(fut);
+        drop(owner);
+        let _owner = TCellOwner::<Marker>::try_new().unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn tcell_new_async_with_sync_new() {
+        use futures::executor::block_on;
+
+        struct Marker;
+        let owner = block_on(TCellOwner::<Marker>::new_async());
+        assert!(TCellOwner::<Marker>::try_new().is_err());
+        let handle = std::thread::spawn(|| {
+            let _owner = TCellOwner::<Marker>::wait_for_new();
+        });
+        wait_until_queued::<Marker>(1);
+        drop(owner);
+        handle.join().unwrap();
+
+        let owner = TCellOwner::<Marker>::new();
+        let handle = std::thread::spawn(|| {
+            let _owner = block_on(TCellOwner::<Marker>::new_async());
+        });
+        wait_until_queued::<Marker>(1);
+        drop(owner);
+        handle.join().unwrap();
+        let _owner = TCellOwner::<Marker>::new();
+    }
+
+    #[test]
+    fn tcell_new_unchecked() {
+        struct Marker;
+        let mut owner = unsafe { TCellOwner::<Marker>::new_unchecked() };
+        let c1 = owner.cell(100u32);
+        *owner.rw(&c1) += 1;
+        assert_eq!(*owner.ro(&c1), 101);
+        // Nothing was registered, so a checked owner can be created
+        // once the unchecked one is gone
+        drop(owner);
+        let _owner = TCellOwner::<Marker>::new();
+    }
+
+    #[test]
+    fn tcell_new_unchecked_registry() {
+        struct Marker1;
+        struct Marker2;
+        let owner1 = TCellOwner::<Marker1>::new();
+        // Dropping an unchecked owner must not change the registry.
+        // This is sound since no other `Marker2` owner exists.
+        drop(unsafe { TCellOwner::<Marker2>::new_unchecked() });
+        assert!(TCellOwner::<Marker1>::try_new().is_err());
+        assert!(TCellOwner::<Marker2>::try_new().is_ok());
+        drop(owner1);
+        assert!(TCellOwner::<Marker1>::try_new().is_ok());
+    }
+
+    #[test]
+    fn tcell_scope() {
+        struct Marker1;
+        struct Marker2;
+        let total = TCellOwner::<Marker1>::scope(|owner1| {
+            let c1 = owner1.cell(100u32);
+            assert!(TCellOwner::<Marker1>::try_scope(|_| ()).is_err());
+            TCellOwner::<Marker2>::scope(|owner2| {
+                let c2 = owner2.cell(200u32);
+                *owner1.rw(&c1) += 1;
+                *owner2.rw(&c2) += 2;
+                owner1.ro(&c1) + owner2.ro(&c2)
+            })
+        });
+        assert_eq!(total, 303);
+        assert_eq!(TCellOwner::<Marker1>::try_scope(|_| 1), Ok(1));
+    }
+
+    #[test]
+    fn tcell_scope_panic() {
+        struct Marker;
+        let result = std::panic::catch_unwind(|| {
+            TCellOwner::<Marker>::scope(|_| panic!("Panic in scope"));
+        });
+        assert!(result.is_err());
+        // The owner was dropped during unwinding
+        let _owner = TCellOwner::<Marker>::new();
+    }
+
+    #[test]
+    fn tcell_debug_default() {
+        struct Marker;
+        #[derive(Debug)]
+        struct Context {
+            owner: TCellOwner<Marker>,
+            cell: TCell<Marker, Vec<u8>>,
+        }
+        #[derive(Debug, Default)]
+        struct OwnerOnly {
+            owner: TCellOwner<Marker>,
+        }
+        let ctx = Context {
+            owner: OwnerOnly::default().owner,
+            cell: TCell::new(vec![1, 2, 3]),
+        };
+        assert_eq!(*ctx.owner.ro(&ctx.cell), vec![1, 2, 3]);
+        let text = format!("{:?}", ctx);
+        assert!(text.starts_with("Context { owner: TCellOwner { marker: \""));
+        assert!(text.contains("Marker\" }, cell: TCell { .. } }"));
+    }
+
+    #[test]
+    fn tcell_rw_n() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let c1 = TCell::new(1u32);
+        let c2 = TCell::new(2u64);
+        let c3 = TCell::new(String::from("3"));
+        let c4 = TCell::new(vec![4u8]);
+        let c5 = TCell::new(5i16);
+        let (r1, r2, r3, r4, r5) = owner.rw_n((&c1, &c2, &c3, &c4, &c5));
+        *r1 += 10;
+        *r2 += 20;
+        r3.push('0');
+        r4.push(40);
+        *r5 += 50;
+        assert_eq!(*owner.ro(&c1), 11);
+        assert_eq!(*owner.ro(&c2), 22);
+        assert_eq!(owner.ro(&c3), "30");
+        assert_eq!(*owner.ro(&c4), vec![4, 40]);
+        assert_eq!(*owner.ro(&c5), 55);
+        let (r1,) = owner.rw_n((&c1,));
+        *r1 += 1;
+        assert_eq!(*owner.ro(&c1), 12);
+    }
+
+    #[test]
+    #[should_panic(expected = "arguments 2 and 5 are the same")]
+    fn tcell_rw_n_alias() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let c1 = TCell::new(1u32);
+        let c2 = TCell::new(2u32);
+        let c3 = TCell::new(3u32);
+        let c4 = TCell::new(4u32);
+        let _ = owner.rw_n((&c1, &c2, &c3, &c4, &c2));
+    }
+
+    #[test]
+    fn tcell_try_rw2() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let c1 = TCell::new(1u32);
+        let c2 = TCell::new(2u32);
+        let c3 = TCell::new(3u32);
+        let (r1, r2) = owner.try_rw2(&c1, &c2).unwrap();
+        *r1 += 10;
+        *r2 += 20;
+        assert_eq!(owner.try_rw2(&c1, &c1).err().unwrap().arguments(), (1, 2));
+        let (r1, r2, r3) = owner.try_rw3(&c1, &c2, &c3).unwrap();
+        *r3 += *r1 + *r2;
+        assert_eq!(*owner.ro(&c3), 36);
+        let err = owner.try_rw3(&c1, &c2, &c2).err().unwrap();
+        assert_eq!(err.arguments(), (2, 3));
+        assert_eq!(err.to_string(), "arguments 2 and 3 are the same");
+        assert!(owner.try_rw3(&c3, &c2, &c3).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Illegal to borrow same TCell twice with rw2()")]
+    fn tcell_rw2_alias() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let c1 = TCell::new(1u32);
+        let _ = owner.rw2(&c1, &c1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Illegal to borrow same TCell twice with rw3()")]
+    fn tcell_rw3_alias() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let c1 = TCell::new(1u32);
+        let c2 = TCell::new(2u32);
+        let _ = owner.rw3(&c1, &c2, &c1);
+    }
+
+    #[test]
+    fn tcell_rw_slice() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let cells: Vec<Rc<TCell<Marker, u32>>> = (0..20).map(|i| Rc::new(TCell::new(i))).collect();
+        assert!(owner.rw_slice::<u32>(&[]).is_empty());
+        for r in owner.rw_slice(&[&*cells[3]]) {
+            *r += 100;
+        }
+        let subset: Vec<&TCell<Marker, u32>> = cells.iter().step_by(2).map(|c| &**c).collect();
+        for r in owner.rw_slice(&subset) {
+            *r += 1000;
+        }
+        let values: Vec<u32> = cells.iter().map(|c| *owner.ro(c)).collect();
+        assert_eq!(values[..4], [1000, 1, 1002, 103]);
+        assert_eq!(values[19], 19);
+
+        // Duplicates are detected by both the short and long paths
+        let short = [&*cells[0], &*cells[1], &*cells[0]];
+        assert_eq!(owner.try_rw_slice(&short).err().unwrap().indices(), (0, 2));
+        let mut long: Vec<&TCell<Marker, u32>> = cells.iter().map(|c| &**c).collect();
+        long.push(&*cells[7]);
+        assert_eq!(owner.try_rw_slice(&long).err().unwrap().indices(), (7, 20));
+    }
+
+    #[test]
+    #[should_panic(expected = "Illegal to borrow same TCell twice with rw_slice()")]
+    fn tcell_rw_slice_alias() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let c1 = TCell::new(1u32);
+        let _ = owner.rw_slice(&[&c1, &c1]);
+    }
+
+    #[test]
+    fn tcell_rw_zst_alias() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        crate::LCellOwner::scope(|inner| {
+            // A zero-sized owner held in a cell must not be borrowed
+            // mutably twice
+            let holder = TCell::<Marker, _>::new(inner);
+            assert!(owner.try_rw2(&holder, &holder).is_err());
+            assert!(owner.try_rw_ro1(&holder, &holder).is_err());
+            assert!(owner.try_rw_n((&holder, &holder)).is_err());
+            assert!(owner.try_rw_iter([&holder, &holder]).is_err());
+        });
+    }
+
+    #[test]
+    fn tcell_rw_iter() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let rcs: Vec<Rc<TCell<Marker, u32>>> =
+            (0..10_000).map(|i| Rc::new(TCell::new(i))).collect();
+        for r in owner.rw_iter(&rcs) {
+            *r += 1;
+        }
+        assert_eq!(owner.get(&rcs[9_999]), 10_000);
+        // Mixed `Rc` and plain references, through `AsCellRef`
+        let plain = [TCell::new(7u32), TCell::new(8)];
+        let refs: Vec<&TCell<Marker, u32>> = rcs
+            .iter()
+            .take(3)
+            .map(|rc| &**rc)
+            .chain(plain.iter())
+            .collect();
+        let sum: u32 = owner.rw_iter(&refs).into_iter().map(|r| *r).sum();
+        assert_eq!(sum, 1 + 2 + 3 + 7 + 8);
+
+        // Duplicates anywhere are reported by position
+        let dup = rcs.iter().chain(std::iter::once(&rcs[4_321]));
+        assert_eq!(
+            owner.try_rw_iter(dup).err().unwrap().indices(),
+            (4_321, 10_000)
+        );
+        let dup = std::iter::once(&rcs[0])
+            .chain(rcs.iter().skip(1))
+            .chain(&rcs[..1]);
+        assert_eq!(
+            owner.try_rw_iter(dup).err().unwrap().arguments(),
+            (1, 10_001)
+        );
+        assert!(owner.try_rw_iter(&plain).is_ok());
+    }
+
+    #[test]
+    fn tcell_for_each_rw() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let rcs: Vec<Rc<TCell<Marker, u32>>> = (0..4).map(|i| Rc::new(TCell::new(i))).collect();
+        // Duplicates are visited once per appearance
+        let mut visits: Vec<&Rc<TCell<Marker, u32>>> = rcs.iter().collect();
+        visits.push(&rcs[1]);
+        owner.for_each_rw(visits.iter().copied(), |v| *v += 10);
+        owner.for_each_rw(&rcs, |v| *v *= 2);
+        let values: Vec<u32> = rcs.iter().map(|c| owner.get(c)).collect();
+        assert_eq!(values, [20, 42, 24, 26]);
+        let boxed: Vec<Box<TCell<Marker, [u8]>>> =
+            vec![Box::new(TCell::new([1])), Box::new(TCell::new([2, 3]))];
+        owner.for_each_rw(&boxed, |s| s.reverse());
+        assert_eq!(owner.ro(&*boxed[1]), [3, 2]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Illegal to borrow same TCell twice with rw_iter(): arguments 2 and 3"
+    )]
+    fn tcell_rw_iter_alias() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let c1 = TCell::new(1u32);
+        let c2 = TCell::new(2u32);
+        let _ = owner.rw_iter([&c1, &c2, &c2]);
+    }
+
+    #[test]
+    fn tcell_rw_slice_zst() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        crate::LCellOwner::scope(|inner| {
+            // A zero-sized owner held in a cell must not be borrowed
+            // mutably twice
+            let holder = TCell::<Marker, _>::new(inner);
+            assert!(owner.try_rw_slice(&[&holder, &holder]).is_err());
+        });
+    }
+
+    #[test]
+    fn tcell_iter_ro() {
+        use super::CellIterExt;
+        struct Marker;
+        let owner = TCellOwner::<Marker>::new();
+        let cells: Vec<Rc<TCell<Marker, u32>>> = (1..=10).map(|i| Rc::new(owner.cell(i))).collect();
+        assert_eq!(cells.iter().ro(&owner).sum::<u32>(), 55);
+        let refs: Vec<&TCell<Marker, u32>> = cells.iter().map(|c| &**c).collect();
+        assert_eq!(refs.iter().ro(&owner).max(), Some(&10));
+        let strings = [owner.cell(String::from("a")), owner.cell(String::from("b"))];
+        let joined: Vec<String> = strings.iter().ro_cloned(&owner).collect();
+        assert_eq!(joined, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn tcell_set_replace_take() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let c1 = Rc::new(TCell::new(vec![1u8]));
+        owner.set(&c1, vec![2]);
+        assert_eq!(owner.replace(&c1, vec![3, 4]), vec![2]);
+        assert_eq!(owner.take(&c1), vec![3, 4]);
+        assert!(owner.ro(&c1).is_empty());
+    }
+
+    #[test]
+    fn tcell_try_ro_rw() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let cell = TCell::<Marker, _>::new(1u32);
+        *owner.try_rw(&cell).unwrap_or_else(|e| match e {}) += 1;
+        let Ok(value) = owner.try_ro(&cell);
+        assert_eq!(*value, 2);
+    }
+
+    #[test]
+    fn tcell_update_read() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let c1 = TCell::new(1u32);
+        let c2 = TCell::new(10u32);
+        let old1 = owner.update(&c1, |v| {
+            let old = *v;
+            *v += 1;
+            old
+        });
+        let old2 = owner.update(&c2, |v| std::mem::replace(v, 20));
+        assert_eq!((old1, old2), (1, 10));
+        let total = owner.read(&c1, |v1| owner.read(&c2, |v2| v1 + v2));
+        assert_eq!(total, 22);
+        let v1 = TCell::new(vec![1]);
+        let v2 = TCell::new(vec![2, 3]);
+        let len = owner.update2(&v1, &v2, |a, b| {
+            a.append(b);
+            a.len()
+        });
+        assert_eq!((len, owner.ro(&v2).len()), (3, 0));
+    }
+
+    #[test]
+    fn tcell_read_token_threads() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let cells: Vec<TCell<Marker, u64>> = (1..=100).map(TCell::new).collect();
+        let token = owner.read_token();
+        let total: u64 = std::thread::scope(|s| {
+            let handles: Vec<_> = cells
+                .chunks(25)
+                .map(|chunk| s.spawn(move || chunk.iter().map(|c| *token.ro(c)).sum::<u64>()))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).sum()
+        });
+        assert_eq!(total, 5050);
+        *owner.rw(&cells[0]) = 0;
+        assert_eq!(owner.read_token().ro(&cells[0]), &0);
+    }
+
+    #[test]
+    fn tcell_owner_send_to_thread() {
+        // Marker that is itself neither Send nor Sync
+        struct Marker(std::marker::PhantomData<*const ()>);
+        let mut owner = TCellOwner::<Marker>::new();
+        let c1 = TCell::new(1u32);
+        let c2 = owner.cell(2u32);
+        *owner.rw(&c1) += 10;
+        let (owner, c1, c2) = std::thread::spawn(move || {
+            owner.swap(&c1, &c2);
+            (owner, c1, c2)
+        })
+        .join()
+        .unwrap();
+        assert_eq!((owner.get(&c1), owner.get(&c2)), (2, 11));
+        drop(owner);
+        assert!(TCellOwner::<Marker>::try_new().is_ok());
+    }
+
+    #[test]
+    fn tcell_is_active() {
+        struct Marker;
+        assert!(!TCellOwner::<Marker>::is_active());
+        let owner = TCellOwner::<Marker>::new();
+        assert!(TCellOwner::<Marker>::is_active());
+        assert!(std::thread::spawn(TCellOwner::<Marker>::is_active)
+            .join()
+            .unwrap());
+        drop(owner);
+        assert!(!TCellOwner::<Marker>::is_active());
+        let owner = std::thread::spawn(TCellOwner::<Marker>::new)
+            .join()
+            .unwrap();
+        assert!(TCellOwner::<Marker>::is_active());
+        drop(owner);
+        assert!(!TCellOwner::<Marker>::is_active());
+    }
+
+    #[test]
+    fn tcell_rw_ro() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let nodes: Vec<_> = (0..4u32).map(TCell::new).collect();
+        let (w, r1) = owner.rw_ro1(&nodes[0], &nodes[1]);
+        *w += *r1;
+        let (w, r1, r2) = owner.rw_ro2(&nodes[1], &nodes[0], &nodes[2]);
+        *w += *r1 + *r2;
+        // Reads may alias each other
+        let (w, r1, r2, r3) = owner.rw_ro3(&nodes[3], &nodes[2], &nodes[2], &nodes[0]);
+        *w += *r1 + *r2 + *r3;
+        assert_eq!(owner.ro(&nodes[1]), &4);
+        assert_eq!(owner.ro(&nodes[3]), &8);
+        let err = owner
+            .try_rw_ro3(&nodes[0], &nodes[1], &nodes[2], &nodes[0])
+            .err()
+            .unwrap();
+        assert_eq!(err.arguments(), (1, 4));
+        assert!(owner.try_rw_ro2(&nodes[0], &nodes[0], &nodes[1]).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "read argument r2 is the same as the written argument")]
+    fn tcell_rw_ro2_alias() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let c1 = TCell::new(1u32);
+        let c2 = TCell::new(2u32);
+        owner.rw_ro2(&c1, &c2, &c1); // Panic here
+    }
+
+    #[test]
+    fn tcell_freeze_thaw() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let cells: Arc<Vec<TCell<Marker, u32>>> = Arc::new((0..10).map(TCell::new).collect());
+        *owner.rw(&cells[0]) = 100;
+        let frozen = owner.freeze();
+        assert!(TCellOwner::<Marker>::try_new().is_err());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let frozen = frozen.clone();
+                let cells = cells.clone();
+                std::thread::spawn(move || cells.iter().map(|c| *frozen.ro(c)).sum::<u32>())
+            })
+            .collect();
+        let extra = frozen.clone();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 145);
+        }
+        let frozen = frozen.thaw().err().unwrap();
+        drop(extra);
+        let mut owner = frozen.thaw().unwrap();
+        *owner.rw(&cells[0]) += 1;
+        assert_eq!(owner.get(&cells[0]), 101);
+        assert!(TCellOwner::<Marker>::try_new().is_err());
+        let frozen = owner.freeze();
+        drop(frozen);
+        assert!(TCellOwner::<Marker>::try_new().is_ok());
+    }
+
+    #[test]
+    fn tcell_new_or_share() {
+        struct Marker;
+        let shared1 = TCellOwner::<Marker>::new_or_share();
+        let shared2 = TCellOwner::<Marker>::new_or_share();
+        assert!(TCellOwner::<Marker>::try_new().is_err());
+        let c1 = TCell::new(1u32);
+        *shared1.borrow_mut().rw(&c1) += 1;
+        assert_eq!(*shared2.ro(&c1), 2);
+        {
+            let mut owner = shared1.borrow_mut();
+            assert!(shared2.try_borrow_mut().is_err());
+            *owner.rw(&c1) += 1;
+        }
+        shared2.borrow_mut().set(&c1, 10);
+        let read = shared1.ro(&c1);
+        assert!(shared2.try_borrow_mut().is_err());
+        assert_eq!(*read, 10);
+        drop(read);
+        // Another thread can't share this thread's owner
+        std::thread::spawn(|| {
+            let result = std::panic::catch_unwind(TCellOwner::<Marker>::new_or_share);
+            assert!(result.is_err());
+        })
+        .join()
+        .unwrap();
+        drop(shared1);
+        assert!(TCellOwner::<Marker>::is_active());
+        drop(shared2);
+        assert!(!TCellOwner::<Marker>::is_active());
+        let owner = TCellOwner::<Marker>::new();
+        drop(owner);
+        let shared3 = TCellOwner::<Marker>::new_or_share();
+        assert_eq!(*shared3.ro(&c1), 10);
+    }
+
+    #[test]
+    #[should_panic]
+    fn tcell_new_or_share_after_new() {
+        struct Marker;
+        let _owner = TCellOwner::<Marker>::new();
+        let _shared = TCellOwner::<Marker>::new_or_share(); // Panic here
+    }
+
+    #[test]
+    fn tcell_owner_in_thread_local() {
+        struct Marker;
+        std::thread_local! {
+            static OWNER: TCellOwner<Marker> = TCellOwner::new();
+        }
+        std::thread::spawn(|| {
+            let cell = TCell::new(1u32);
+            OWNER.with(|owner| assert_eq!(owner.get(&cell), 1));
+            assert!(TCellOwner::<Marker>::is_active());
+        })
+        .join()
+        .unwrap();
+        // Released when the thread-local was destroyed
+        assert!(!TCellOwner::<Marker>::is_active());
+    }
+
+    #[test]
+    fn tcell_force_release() {
+        struct Marker;
+        std::mem::forget(TCellOwner::<Marker>::new());
+        assert!(TCellOwner::<Marker>::try_new().is_err());
+        unsafe { TCellOwner::<Marker>::force_release() };
+        let owner = TCellOwner::<Marker>::new();
+        assert!(TCellOwner::<Marker>::try_new().is_err());
+        drop(owner);
+        let _owner = TCellOwner::<Marker>::new();
+    }
+
+    #[test]
+    fn tcell_owner_reservation() {
+        struct Marker;
+        let reservation = OwnerReservation::<Marker>::claim().unwrap();
+        assert!(OwnerReservation::<Marker>::claim().is_err());
+        assert!(TCellOwner::<Marker>::try_new().is_err());
+        let mut owner = std::thread::spawn(move || reservation.into_owner())
+            .join()
+            .unwrap();
+        let c1 = TCell::new(1u32);
+        *owner.rw(&c1) += 1;
+        assert!(OwnerReservation::<Marker>::claim().is_err());
+        drop(owner);
+        let reservation = OwnerReservation::<Marker>::claim().unwrap();
+        drop(reservation);
+        assert!(!TCellOwner::<Marker>::is_active());
+        let _owner = TCellOwner::<Marker>::new();
+    }
+
+    #[test]
+    fn tcell_unsized() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let c1: Rc<TCell<Marker, dyn std::fmt::Display>> = Rc::new(TCell::new(123u32));
+        assert_eq!(owner.ro(&c1).to_string(), "123");
+        let c2: Rc<TCell<Marker, [u8]>> = Rc::new(TCell::new([1u8, 2, 3]));
+        owner.rw(&c2)[0] = 10;
+        let c3: Rc<TCell<Marker, [u8]>> =
+            Rc::from(TCell::from_boxed(vec![4u8, 5].into_boxed_slice()));
+        let (s2, s3) = owner.rw2(&c2, &c3);
+        s2[1] += s3[0];
+        s3[1] += s2[2];
+        assert_eq!(owner.ro(&c2), &[10, 6, 3]);
+        assert_eq!(owner.ro(&c3), &[4, 8]);
+        assert!(owner.try_rw2(&c2, &*c2).is_err());
+        let c4: Box<TCell<Marker, str>> = TCell::from_boxed(String::from("abc").into_boxed_str());
+        owner.rw(&c4).make_ascii_uppercase();
+        assert_eq!(owner.ro(&c4), "ABC");
+    }
+
+    #[test]
+    fn tcell_into_inner() {
+        struct Marker;
+        struct Counted(Rc<std::cell::Cell<u32>>);
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+        let drops = Rc::new(std::cell::Cell::new(0));
+        let cell = Rc::new(TCell::<Marker, _>::new(Counted(drops.clone())));
+        let value = Rc::try_unwrap(cell).ok().unwrap().into_inner();
+        assert_eq!(drops.get(), 0);
+        drop(value);
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn tcell_from_mut() {
+        struct Marker;
+        fn bump(owner: &mut TCellOwner<Marker>, cell: &TCell<Marker, u32>) {
+            *owner.rw(cell) += 1;
+        }
+        let mut owner = TCellOwner::<Marker>::new();
+        let mut value = 10u32;
+        bump(&mut owner, TCell::from_mut(&mut value));
+        assert_eq!(value, 11);
+        let mut slice = [1u8, 2];
+        let cell: &TCell<Marker, [u8]> = TCell::from_mut(&mut slice[..]);
+        owner.rw(cell)[0] = 5;
+        assert_eq!(slice, [5, 2]);
+        let mut unit = ();
+        let cell = TCell::<Marker, ()>::from_mut(&mut unit);
+        *cell.get_mut() = ();
+    }
+
+    #[test]
+    fn tcell_layout() {
+        use std::mem::{align_of, size_of, size_of_val};
+        struct Marker;
+        #[allow(dead_code)]
+        #[repr(align(64))]
+        struct Aligned(u8);
+        macro_rules! check {
+            ($($t:ty),*) => {$(
+                assert_eq!(size_of::<TCell<Marker, $t>>(), size_of::<$t>(), "{}", stringify!($t));
+                assert_eq!(align_of::<TCell<Marker, $t>>(), align_of::<$t>(), "{}", stringify!($t));
+            )*};
+        }
+        check!(
+            (),
+            [u32; 0],
+            u8,
+            u16,
+            u64,
+            u128,
+            String,
+            Option<Box<u8>>,
+            Aligned,
+            [Aligned; 3]
+        );
+
+        let slice: Box<TCell<Marker, [u16]>> = TCell::from_boxed(Box::new([1, 2, 3]));
+        assert_eq!(size_of_val(&*slice), 6);
+        let unsized_str: Box<TCell<Marker, str>> = TCell::from_boxed("abc".into());
+        assert_eq!(size_of_val(&*unsized_str), 3);
+    }
+
+    #[test]
+    fn tcell_from_ref() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let value = String::from("shared");
+        let cell = unsafe { TCell::<Marker, _>::from_ref(&value) };
+        assert!(std::ptr::eq(owner.ro(cell), &value));
+        assert_eq!(owner.ro(cell), "shared");
+        let mut value = [1u32, 2];
+        let cells = TCell::<Marker, [u32]>::from_mut(&mut value[..]).as_slice_of_cells();
+        let (a, b) = owner.rw2(&cells[0], &cells[1]);
+        std::mem::swap(a, b);
+        assert_eq!(value, [2, 1]);
+    }
+
+    #[test]
+    fn tcell_slice_of_cells() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let cells: Rc<TCell<Marker, [u32]>> = Rc::new(TCell::new([1, 2, 3, 4]));
+        let elements = cells.as_slice_of_cells();
+        assert_eq!(elements.len(), 4);
+        for element in elements {
+            *owner.rw(element) *= 10;
+        }
+        let (a, b) = owner.rw2(&elements[0], &elements[3]);
+        std::mem::swap(a, b);
+        assert_eq!(owner.ro(&cells), &[40, 20, 30, 10]);
+        assert!(owner.try_rw2(&elements[1], &elements[1]).is_err());
+        let whole = TCell::from_slice_of_cells(elements);
+        assert_eq!(owner.ro(whole), &[40, 20, 30, 10]);
+        assert_eq!(owner.ro_slice(&elements[1..]), &[20, 30, 10]);
+        assert!(std::ptr::eq(whole, &*cells));
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::{TCell, TCellOwner};
     #[test]
     #[should_panic]
-    fn tcell_singleton_1() {
+    fn tcell_slice_of_cells_rw2_same() {
         struct Marker;
-        let _owner1 = TCellOwner::<Marker>::new();
-        let _owner2 = TCellOwner::<Marker>::new(); // Panic here
+        let mut owner = TCellOwner::<Marker>::new();
+        let cells: Box<TCell<Marker, [u32]>> = TCell::from_boxed(Box::new([1, 2]));
+        let elements = cells.as_slice_of_cells();
+        owner.rw2(&elements[1], &elements[1]); // Panic here
     }
 
     #[test]
-    fn tcell_singleton_2() {
+    fn tcell_unwind_safe() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+        // The marker type's unwind-safety must not matter.  See
+        // `guarantees.rs` for the full set of auto traits.
+        #[allow(dead_code)]
+        struct Marker(std::cell::Cell<u32>);
+
+        // Owner and cell moved in are fine without any assertion
+        let owner = TCellOwner::<Marker>::new();
+        let cell = TCell::<Marker, _>::new(vec![1]);
+        let result = catch_unwind(move || {
+            let mut owner = owner;
+            owner.rw(&cell).push(2);
+            (owner, cell)
+        });
+        let (mut owner, cell) = result.unwrap();
+        assert_eq!(owner.ro(&cell), &[1, 2]);
+
+        // Borrowed, the update made before the panic is visible
+        // afterwards, which is why this needs `AssertUnwindSafe`
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            owner.rw(&cell).push(3);
+            panic!("half-way through update");
+        }));
+        assert!(result.is_err());
+        assert_eq!(owner.ro(&cell), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn tcell_option_ext() {
         struct Marker;
-        let owner1 = TCellOwner::<Marker>::new();
-        drop(owner1);
-        let _owner2 = TCellOwner::<Marker>::new();
+        struct Node {
+            value: u32,
+            next: TCell<Marker, Option<Rc<Node>>>,
+        }
+        struct List {
+            head: TCell<Marker, Option<Rc<Node>>>,
+        }
+        impl List {
+            fn push(&self, owner: &mut TCellOwner<Marker>, value: u32) {
+                let next = TCell::new(self.head.take_inner(owner));
+                self.head
+                    .replace_inner(owner, Rc::new(Node { value, next }));
+            }
+            fn pop(&self, owner: &mut TCellOwner<Marker>) -> Option<u32> {
+                let node = self.head.take_inner(owner)?;
+                if let Some(next) = node.next.take_inner(owner) {
+                    self.head.replace_inner(owner, next);
+                }
+                Some(node.value)
+            }
+            fn values(&self, owner: &TCellOwner<Marker>) -> Vec<u32> {
+                let mut values = Vec::new();
+                let mut link = self.head.as_ref(owner);
+                while let Some(node) = link {
+                    values.push(node.value);
+                    link = node.next.as_ref(owner);
+                }
+                values
+            }
+        }
+
+        let mut owner = TCellOwner::<Marker>::new();
+        let list = List {
+            head: TCell::new(None),
+        };
+        assert!(!list.head.is_some(&owner));
+        for value in 1..=3 {
+            list.push(&mut owner, value);
+        }
+        assert!(list.head.is_some(&owner));
+        assert_eq!(list.values(&owner), [3, 2, 1]);
+        assert_eq!(list.pop(&mut owner), Some(3));
+        assert_eq!(list.values(&owner), [2, 1]);
+        assert_eq!(list.pop(&mut owner), Some(2));
+        assert_eq!(list.pop(&mut owner), Some(1));
+        assert_eq!(list.pop(&mut owner), None);
+        assert!(!list.head.is_some(&owner));
+
+        let tail = TCell::<Marker, Option<Rc<Node>>>::new(None);
+        let node = tail.get_or_insert_with(&mut owner, || {
+            Rc::new(Node {
+                value: 10,
+                next: TCell::new(None),
+            })
+        });
+        assert_eq!(node.value, 10);
+        let node = tail.get_or_insert_with(&mut owner, || unreachable!());
+        assert_eq!(node.value, 10);
     }
 
     #[test]
-    fn tcell_singleton_3() {
-        struct Marker1;
-        struct Marker2;
-        let _owner1 = TCellOwner::<Marker1>::new();
-        let _owner2 = TCellOwner::<Marker2>::new();
+    fn tcell_map() {
+        struct Marker;
+        struct Counted<'a>(&'a AtomicUsize, String);
+        impl Drop for Counted<'_> {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+        let drops = AtomicUsize::new(0);
+        let owner = TCellOwner::<Marker>::new();
+        let cell = TCell::<Marker, _>::new(Counted(&drops, "hello".into()));
+        let len: TCell<Marker, usize> = cell.map(|c| c.1.len());
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+        assert_eq!(owner.get(&len), 5);
+
+        let cell = TCell::<Marker, _>::new(String::from("12"));
+        let parsed = cell.try_map(|s| s.parse::<u32>()).unwrap();
+        assert_eq!(owner.get(&parsed), 12);
+        let cell = TCell::<Marker, _>::new(String::from("x"));
+        assert!(cell.try_map(|s| s.parse::<u32>()).is_err());
+
+        // The contents are dropped exactly once if the closure panics
+        let cell = TCell::<Marker, _>::new(Counted(&drops, "panic".into()));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            cell.map(|c| -> usize { panic!("{}", c.1) })
+        }));
+        assert!(result.is_err());
+        assert_eq!(drops.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn tcell_std_cells() {
+        use std::cell::{Cell, RefCell};
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let cell: TCell<Marker, Vec<u32>> = RefCell::new(vec![1]).into();
+        owner.rw(&cell).push(2);
+        let refcell = cell.into_refcell();
+        refcell.borrow_mut().push(3);
+        let cell = TCell::<Marker, Vec<u32>>::from(refcell);
+        assert_eq!(owner.ro(&cell), &[1, 2, 3]);
+
+        let cell: TCell<Marker, u32> = Cell::new(5).into();
+        *owner.rw(&cell) += 1;
+        let std_cell = cell.into_std_cell();
+        std_cell.set(std_cell.get() * 2);
+        let cell = TCell::<Marker, u32>::from(std_cell);
+        assert_eq!(owner.get(&cell), 12);
+    }
+
+    #[test]
+    fn tcell_copy() {
+        struct Marker;
+        let owner = TCellOwner::<Marker>::new();
+        let name = TCell::<Marker, _>::new(String::from("node"));
+        let data = TCell::<Marker, _>::new(vec![1, 2, 3]);
+        let visits = TCellCopy::<Marker, u32>::default();
+        let dirty = TCellCopy::<Marker, bool>::new(false);
+
+        // Writes only need `&owner`, so can be made whilst other
+        // cells are borrowed through the same owner
+        let name_ref = owner.ro(&name);
+        for value in owner.ro(&data) {
+            visits.update(&owner, |v| v + value);
+            dirty.set(&owner, true);
+        }
+        assert_eq!(name_ref, "node");
+        assert_eq!(visits.get(&owner), 6);
+        assert!(dirty.replace(&owner, false));
+        assert!(!dirty.get(&owner));
+        assert_eq!(visits.take(&owner), 6);
+        assert_eq!(visits.update(&owner, |v| v + 10), 10);
+        assert_eq!(visits.into_inner(), 10);
+
+        let mut flag = TCellCopy::<Marker, bool>::new(false);
+        *flag.get_mut() = true;
+        assert!(flag.get(&owner));
+    }
+
+    #[test]
+    fn tcell_clone_move_into() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let a = TCell::<Marker, _>::new(vec![1, 2]);
+        let b = TCell::<Marker, _>::new(vec![3]);
+        owner.clone_into(&a, &b);
+        assert_eq!((owner.ro(&a), owner.ro(&b)), (&vec![1, 2], &vec![1, 2]));
+        owner.rw(&a).push(3);
+        owner.move_into(&a, &b);
+        assert!(owner.ro(&a).is_empty());
+        assert_eq!(owner.ro(&b), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn tcell_clone_move_into_same() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let a = Rc::new(TCell::<Marker, _>::new(String::from("keep")));
+        let alias = a.clone();
+        owner.clone_into(&a, &alias);
+        assert_eq!(owner.ro(&a), "keep");
+        owner.move_into(&a, &alias);
+        assert_eq!(owner.ro(&a), "keep");
+    }
+
+    #[test]
+    fn tcell_sort_cells() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let values = [(5, 'a'), (2, 'b'), (9, 'c'), (2, 'd'), (7, 'e'), (5, 'f')];
+        let mut cells: Vec<Rc<TCell<Marker, (u32, char)>>> =
+            values.iter().map(|v| Rc::new(TCell::new(*v))).collect();
+        let unsorted = cells.clone();
+
+        // Stable, so equal keys keep their original order
+        owner.sort_cells_by_key(&mut cells, |v| v.0);
+        let tags: String = cells.iter().map(|c| owner.ro(c).1).collect();
+        assert_eq!(tags, "bdafec");
+        assert_eq!(owner.binary_search_cells_by_key(&cells, &7, |v| v.0), Ok(4));
+        assert_eq!(
+            owner.binary_search_cells_by_key(&cells, &6, |v| v.0),
+            Err(4)
+        );
+        assert_eq!(
+            owner.binary_search_cells_by_key(&cells, &1, |v| v.0),
+            Err(0)
+        );
+
+        // Cells are moved, not contents, so other holders see updates
+        owner.rw(&unsorted[2]).0 = 0;
+        owner.sort_cells_by(&mut cells, |a, b| b.0.cmp(&a.0));
+        let tags: String = cells.iter().map(|c| owner.ro(c).1).collect();
+        assert_eq!(tags, "eafbdc");
+
+        // Also works on plain references and on `Arc`
+        let plain: Vec<TCell<Marker, u32>> = [3, 1, 2].iter().map(|v| TCell::new(*v)).collect();
+        let mut refs: Vec<&TCell<Marker, u32>> = plain.iter().collect();
+        owner.sort_cells_by_key(&mut refs, |v| *v);
+        assert_eq!(
+            refs.iter().map(|c| owner.get(c)).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        let mut arcs: Vec<Arc<TCell<Marker, u32>>> =
+            [3, 1, 2].iter().map(|v| Arc::new(TCell::new(*v))).collect();
+        owner.sort_cells_by(&mut arcs, |a, b| a.cmp(b));
+        assert_eq!(owner.binary_search_cells_by_key(&arcs, &3, |v| *v), Ok(2));
+    }
+
+    #[test]
+    fn tcell_static() {
+        use std::cell::Cell;
+        struct Marker;
+        static SYNC: TCell<Marker, u32> = TCell::new(0);
+        static COUNT: StaticTCell<Marker, Cell<u32>> = StaticTCell::new(Cell::new(0));
+        static TOTAL: StaticTCell<Marker, u32> = StaticTCell::new(0);
+        let owner = Arc::new(Mutex::new(TCellOwner::<Marker>::new()));
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let owner = owner.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        let mut owner = owner.lock().unwrap();
+                        let count = COUNT.rw(&mut owner);
+                        count.set(count.get() + 1);
+                        *TOTAL.rw(&mut owner) += 2;
+                        *owner.rw(&SYNC) += 3;
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        let mut owner = owner.lock().unwrap();
+        assert_eq!(COUNT.rw(&mut owner).get(), 400);
+        assert_eq!(*TOTAL.ro(&owner), 800);
+        assert_eq!(*owner.ro(&SYNC), 1200);
+    }
+
+    #[test]
+    fn tcell_batch_cells() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let cells = owner.cells_from_iter((0..10_000u32).map(|i| i + 1));
+        owner.for_each_rw(&cells, |v| *v *= 3);
+        assert!(cells
+            .iter()
+            .enumerate()
+            .all(|(i, c)| *owner.ro(c) == 3 * (i as u32 + 1)));
+
+        let cells = owner.cells_from_vec(vec![vec![1u8], vec![2]]);
+        let (a, b) = owner.rw2(&cells[0], &cells[1]);
+        a.append(b);
+        assert_eq!(owner.ro(&cells[0]), &[1, 2]);
+
+        let array: [TCell<Marker, String>; 4] = owner.cell_array(|i| i.to_string());
+        assert_eq!(owner.ro(&array[3]), "3");
+        let wrapped = TCell::<Marker, _>::wrap_vec(vec![5u16; 3]);
+        assert_eq!(owner.get(&wrapped[2]), 5);
+    }
+
+    #[test]
+    fn tcell_guarded_owner() {
+        use crate::{GuardedCellOwner, GuardedCellReader};
+        fn rotate<O: GuardedCellOwner>(owner: &mut O, cells: &[O::Cell<u32>]) {
+            for pair in cells.windows(2) {
+                owner.swap(&pair[0], &pair[1]);
+            }
+            owner.swap(&cells[0], &cells[0]);
+            owner.for_each_rw(cells, |v| *v += 1);
+        }
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let cells = owner.cells_from_iter(vec![1, 2, 3]);
+        rotate(&mut owner, &cells);
+        assert_eq!(GuardedCellReader::get(&owner, &cells[0]), 3);
+        assert_eq!(owner.get(&cells[2]), 2);
+        drop(owner);
+
+        let mut shared = TCellOwner::<Marker>::new_or_share();
+        let other = shared.clone();
+        rotate(&mut shared, &cells);
+        assert_eq!(GuardedCellReader::get(&shared, &cells[0]), 5);
+
+        // Guards keep the shared owner borrowed until dropped
+        let guard = shared.ro_guard(&cells[0]);
+        assert!(other.try_borrow_mut().is_err());
+        drop(guard);
+        let mut guard = shared.rw_guard(&cells[1]);
+        *guard += 10;
+        assert!(other.try_borrow_mut().is_err());
+        drop(guard);
+        let (mut g1, g2) = shared.rw2_guard(&cells[1], &cells[2]);
+        *g1 += *g2;
+        drop(g1);
+        assert!(other.try_borrow_mut().is_err());
+        drop(g2);
+        assert!(other.try_borrow_mut().is_ok());
+        assert_eq!(*other.ro(&cells[1]), 17);
+        assert_eq!(shared.replace(&cells[2], 0), 4);
+    }
+
+    #[test]
+    fn tcell_array() {
+        struct Marker;
+        static TABLE: TCell<Marker, [u32; 4]> = TCell::from_array([1, 2, 3, 4]);
+        let mut owner = TCellOwner::<Marker>::new();
+        for (i, value) in TABLE.each_mut(&mut owner).iter_mut().enumerate() {
+            **value *= 10 + i as u32;
+        }
+        assert_eq!(TABLE.each_ref(&owner), [&10, &22, &36, &52]);
+        let [a, _, _, d] = TABLE.each_mut(&mut owner);
+        std::mem::swap(a, d);
+        assert_eq!(owner.ro(&TABLE), &[52, 22, 36, 10]);
+
+        let cells: [TCell<Marker, String>; 2] =
+            const { TCell::new_array([String::new(), String::new()]) };
+        owner.rw(&cells[1]).push_str("one");
+        assert_eq!(owner.ro(&cells[0]), "");
+        assert_eq!(owner.ro(&cells[1]), "one");
+
+        let array = TCell::<Marker, _>::from_array([vec![1], vec![2, 3]]).into_array();
+        let (a, b) = owner.rw2(&array[0], &array[1]);
+        a.append(b);
+        assert_eq!(owner.ro(&array[0]), &[1, 2, 3]);
+        assert!(owner.ro(&array[1]).is_empty());
+    }
+
+    #[test]
+    fn tcell_uninit() {
+        use std::mem::MaybeUninit;
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let cells: [TCell<Marker, MaybeUninit<String>>; 3] = [
+            TCell::new_uninit(),
+            TCell::new_uninit(),
+            TCell::new_uninit(),
+        ];
+        for (i, cell) in cells.iter().enumerate() {
+            owner.write(cell, i.to_string()).push('!');
+        }
+        let cells = cells.map(|cell| unsafe { cell.assume_init() });
+        let values: Vec<&str> = cells.iter().map(|c| owner.ro(c).as_str()).collect();
+        assert_eq!(values, ["0!", "1!", "2!"]);
+
+        let shared = Rc::new(TCell::<Marker, MaybeUninit<u32>>::new_uninit());
+        assert_eq!(*owner.write(&shared, 10), 10);
+        let cell = unsafe { shared.assume_init_ref() };
+        *owner.rw(cell) += 1;
+        assert_eq!(owner.get(cell), 11);
+    }
+
+    #[test]
+    fn tcell_split() {
+        struct Marker;
+        // Reverse a slice by swapping the ends and recursing on the
+        // middle, with each level borrowing both halves via `rw2`
+        fn reverse(owner: &mut TCellOwner<Marker>, cells: &TCell<Marker, [u32]>) {
+            if cells.len() < 2 {
+                return;
+            }
+            let (first, rest) = cells.split_first().unwrap();
+            let (middle, last) = rest.split_at(rest.len() - 1);
+            let (first, last) = owner.rw2(first, last);
+            std::mem::swap(first, &mut last[0]);
+            reverse(owner, middle);
+        }
+
+        let mut owner = TCellOwner::<Marker>::new();
+        let cells: Rc<TCell<Marker, [u32]>> = Rc::new(TCell::new([1, 2, 3, 4, 5]));
+        assert_eq!(cells.len(), 5);
+        reverse(&mut owner, &cells);
+        assert_eq!(owner.ro(&cells), &[5, 4, 3, 2, 1]);
+
+        let (left, right) = cells.split_at(2);
+        let (left, right) = owner.rw2(left, right);
+        left.copy_from_slice(&[10, 20]);
+        right[2] = 30;
+        assert_eq!(owner.ro(&cells), &[10, 20, 3, 2, 30]);
+
+        let (all, none) = cells.split_at(cells.len());
+        assert!(none.is_empty());
+        let (all, none) = owner.rw2(all, none);
+        all[0] = 0;
+        assert!(none.is_empty());
+        let (none, all) = cells.split_at(0);
+        assert!(none.is_empty());
+        assert_eq!(all.len(), 5);
+        assert!(owner.try_rw2(&*cells, all).is_err());
+        let (none, all) = owner.rw2(none, all);
+        all[4] = 1;
+        assert!(none.is_empty());
+        assert_eq!(owner.ro(&cells), &[0, 20, 3, 2, 1]);
+
+        let empty: Box<TCell<Marker, [u32]>> = TCell::from_boxed(Box::new([]));
+        assert!(empty.is_empty());
+        assert!(empty.split_first().is_none());
+        let (a, b) = empty.split_at(0);
+        assert!(a.is_empty() && b.is_empty());
+        reverse(&mut owner, &empty);
+    }
+
+    #[test]
+    #[should_panic]
+    fn tcell_split_at_out_of_bounds() {
+        struct Marker;
+        let cells: Box<TCell<Marker, [u32]>> = TCell::from_boxed(Box::new([1, 2]));
+        let _ = cells.split_at(3); // Panic here
+    }
+
+    #[test]
+    fn tcell_rw_idx() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let cells: Rc<TCell<Marker, [u32]>> = Rc::new(TCell::new([1, 2, 3, 4]));
+        let (a, b) = owner.rw_idx2(&cells, 3, 0);
+        std::mem::swap(a, b);
+        let (a, b, c) = owner.rw_idx3(&cells, 1, 2, 0);
+        *a += 10;
+        *b += 20;
+        *c += 30;
+        assert_eq!(owner.ro(&cells), &[34, 12, 23, 1]);
+        assert_eq!(
+            owner.try_rw_idx2(&cells, 2, 2).unwrap_err(),
+            IndexError::Duplicate { index: 2 }
+        );
+        assert_eq!(
+            owner.try_rw_idx3(&cells, 0, 1, 0).unwrap_err(),
+            IndexError::Duplicate { index: 0 }
+        );
+        assert_eq!(
+            owner.try_rw_idx2(&cells, 1, 4).unwrap_err(),
+            IndexError::OutOfBounds { index: 4, len: 4 }
+        );
+        assert_eq!(
+            owner.try_rw_idx3(&cells, 9, 1, 2).unwrap_err(),
+            IndexError::OutOfBounds { index: 9, len: 4 }
+        );
+    }
+
+    #[test]
+    fn tcell_rw_idx_vec() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let cell = TCell::<Marker, Vec<u32>>::new(vec![1, 2, 3]);
+        let (left, right) = owner.rw(&cell).split_at_mut(2);
+        std::mem::swap(&mut left[0], &mut right[0]);
+        assert_eq!(owner.ro(&cell), &[3, 2, 1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn tcell_rw_idx2_same() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let cells: Box<TCell<Marker, [u32]>> = TCell::from_boxed(Box::new([1, 2]));
+        owner.rw_idx2(&cells, 1, 1); // Panic here
+    }
+
+    #[test]
+    #[should_panic]
+    fn tcell_rw_idx3_out_of_bounds() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let cells: Box<TCell<Marker, [u32]>> = TCell::from_boxed(Box::new([1, 2, 3]));
+        owner.rw_idx3(&cells, 0, 1, 3); // Panic here
+    }
+
+    #[test]
+    fn tcell_project() {
+        struct Marker;
+        struct Inner {
+            a: u32,
+            b: u64,
+        }
+        struct Outer {
+            name: String,
+            inner: Inner,
+            pair: (u8, u16),
+        }
+        let mut owner = TCellOwner::<Marker>::new();
+        let outer = Rc::new(TCell::<Marker, _>::new(Outer {
+            name: "outer".into(),
+            inner: Inner { a: 1, b: 2 },
+            pair: (3, 4),
+        }));
+        let a = cell_project!(&outer, Outer, inner.a);
+        let b = cell_project!(&outer, Outer, inner.b);
+        let name = cell_project!(&outer, Outer, name);
+        let second = cell_project!(&outer, Outer, pair.1);
+        assert!(!std::ptr::addr_eq(a, b));
+        let (a, b) = owner.rw2(a, b);
+        *a += 10;
+        *b += 20;
+        owner.rw(name).push('!');
+        *owner.rw(second) += 40;
+        let outer = owner.ro(&outer);
+        assert_eq!((outer.inner.a, outer.inner.b), (11, 22));
+        assert_eq!(outer.name, "outer!");
+        assert_eq!(outer.pair, (3, 44));
+    }
+
+    #[test]
+    fn tcell_project_alias() {
+        struct Marker;
+        struct Outer {
+            a: u32,
+            b: u32,
+        }
+        let mut owner = TCellOwner::<Marker>::new();
+        let outer = TCell::<Marker, _>::new(Outer { a: 1, b: 2 });
+        // Projecting the same field twice gives the same cell
+        let b1 = cell_project!(&outer, Outer, b);
+        let b2 = cell_project!(&outer, Outer, b);
+        assert!(std::ptr::eq(b1, b2));
+        assert!(owner.try_rw2(b1, b2).is_err());
+        // The outer cell overlaps all of its fields, even those not
+        // at the same address
+        assert!(owner.try_rw2(&outer, b1).is_err());
+        assert!(owner.try_rw_ro1(&outer, b1).is_err());
+        assert!(owner.try_rw_n((b1, &outer)).is_err());
+        let a = cell_project!(&outer, Outer, a);
+        assert!(owner.try_rw_ro1(a, b1).is_ok());
+    }
+
+    #[test]
+    #[should_panic]
+    fn tcell_project_rw2_same() {
+        struct Marker;
+        struct Outer {
+            a: u32,
+        }
+        let mut owner = TCellOwner::<Marker>::new();
+        let outer = TCell::<Marker, _>::new(Outer { a: 1 });
+        let a1 = cell_project!(&outer, Outer, a);
+        let a2 = cell_project!(&outer, Outer, a);
+        owner.rw2(a1, a2); // Panic here
+    }
+
+    #[test]
+    fn tcell_default_from() {
+        struct Marker;
+        #[derive(Default)]
+        struct Node {
+            name: TCell<Marker, String>,
+            data: TCell<Marker, Vec<u8>>,
+            count: TCell<Marker, u32>,
+        }
+        let owner = TCellOwner::<Marker>::new();
+        let node = Node::default();
+        assert!(owner.ro(&node.name).is_empty());
+        assert!(owner.ro(&node.data).is_empty());
+        assert_eq!(owner.get(&node.count), 0);
+        let cell: TCell<Marker, _> = TCell::from(vec![1u8, 2]);
+        assert_eq!(owner.ro(&cell), &[1, 2]);
+        let cell: TCell<Marker, u32> = 5.into();
+        assert_eq!(owner.get(&cell), 5);
+    }
+
+    #[test]
+    fn tcell_debug_display() {
+        struct Marker;
+        let owner = TCellOwner::<Marker>::new();
+        let c1 = TCell::new(vec![1u8, 2]);
+        let c2 = TCell::new(String::from("text"));
+        assert_eq!(format!("{:?}", owner.debug(&c1)), "[1, 2]");
+        assert_eq!(format!("{}", owner.display(&c2)), "text");
+        assert_eq!(format!("{:?}", owner.debug(&c2)), "\"text\"");
+        // Nested in other output, with formatting options passed on
+        let nested = vec![owner.debug(&c1), owner.debug(&c1)];
+        assert_eq!(format!("{:?}", nested), "[[1, 2], [1, 2]]");
+        let value = TCell::new(1.23456f32);
+        assert_eq!(
+            format!("{}", format_args!("<{:.2}>", owner.display(&value))),
+            "<1.23>"
+        );
+    }
+
+    #[test]
+    fn tcell_unsize() {
+        trait Draw {
+            fn draw(&self) -> String;
+        }
+        struct Widget(u32);
+        impl Draw for Widget {
+            fn draw(&self) -> String {
+                format!("widget {}", self.0)
+            }
+        }
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let boxed: Box<TCell<Marker, dyn Draw>> = Box::new(TCell::new(Widget(1)));
+        assert_eq!(owner.ro(&*boxed).draw(), "widget 1");
+        let slice: Rc<TCell<Marker, [u8]>> = Rc::new(TCell::new([0u8; 16]));
+        owner.rw(&*slice)[15] = 1;
+        assert_eq!(owner.ro(&*slice).len(), 16);
+        assert_eq!(owner.ro(&*slice)[15], 1);
+    }
+
+    #[cfg(feature = "nightly")]
+    #[test]
+    fn tcell_coerce_unsized() {
+        struct Marker;
+        let owner = TCellOwner::<Marker>::new();
+        let array = [1u8, 2, 3];
+        let cell = TCell::<Marker, &[u8; 3]>::new(&array);
+        let cell: TCell<Marker, &[u8]> = cell;
+        assert_eq!(owner.ro(&cell).len(), 3);
+        let cell = TCell::<Marker, Box<i32>>::new(Box::new(5));
+        let cell: TCell<Marker, Box<dyn std::fmt::Debug>> = cell;
+        assert_eq!(format!("{:?}", owner.ro(&cell)), "5");
+    }
+
+    #[test]
+    fn tcell_rw_pin() {
+        use std::future::Future;
+        use std::pin::Pin;
+        use std::task::{Context, Poll, Waker};
+
+        struct YieldOnce(bool);
+        impl Future for YieldOnce {
+            type Output = ();
+            fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+                if self.0 {
+                    Poll::Ready(())
+                } else {
+                    self.0 = true;
+                    Poll::Pending
+                }
+            }
+        }
+
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let count = Rc::new(TCell::<Marker, u32>::new(0));
+        let count2 = count.clone();
+        let future = Rc::pin(TCell::new(async move {
+            for _ in 0..3 {
+                YieldOnce(false).await;
+            }
+            count2
+        }));
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut polls = 0;
+        let result = loop {
+            polls += 1;
+            // Safe because the future is never moved out of the cell
+            let pinned = unsafe { owner.rw_pin(future.as_ref()) };
+            if let Poll::Ready(v) = pinned.poll(&mut cx) {
+                break v;
+            }
+        };
+        assert_eq!(polls, 4);
+        *owner.rw(&result) += 1;
+        assert_eq!(owner.get(&count), 1);
+        let _ = unsafe { owner.ro_pin(future.as_ref()) };
+    }
+
+    #[test]
+    fn once_tcell_set() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let cell = OnceTCell::<Marker, String>::new();
+        assert_eq!(cell.get(&owner), None);
+        assert_eq!(cell.set(&mut owner, "first".into()).unwrap(), "first");
+        assert_eq!(cell.set(&mut owner, "second".into()), Err("second".into()));
+        let value = cell.get(&owner).unwrap();
+        drop(owner);
+        assert_eq!(value, "first");
+        assert_eq!(cell.into_inner().unwrap(), "first");
+    }
+
+    #[test]
+    fn once_tcell_get_or_init() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let cell = OnceTCell::<Marker, u32>::default();
+        let mut calls = 0;
+        for _ in 0..3 {
+            let value = cell.get_or_init(&mut owner, || {
+                calls += 1;
+                42
+            });
+            assert_eq!(*value, 42);
+        }
+        assert_eq!(calls, 1);
+        assert_eq!(cell.set(&mut owner, 1), Err(1));
+    }
+
+    #[test]
+    fn once_tcell_threads() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let cell = OnceTCell::<Marker, Vec<u32>>::new();
+        cell.set(&mut owner, vec![1, 2, 3]).unwrap();
+        let owner = &owner;
+        let cell = &cell;
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(move || assert_eq!(cell.get(owner).unwrap(), &[1, 2, 3]));
+            }
+        });
+    }
+
+    #[test]
+    fn tcell_clone_with() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let original = Rc::new(TCell::new(vec![1, 2]));
+        let copy = Rc::new(original.clone_with(&owner));
+        owner.rw(&copy).push(3);
+        assert_eq!(owner.ro(&original), &[1, 2]);
+        assert_eq!(owner.ro(&copy), &[1, 2, 3]);
+
+        // Cloning only needs a shared borrow of the owner
+        let r1 = owner.ro(&original);
+        let r2 = owner.ro(&copy);
+        let copy2 = owner.clone_cell(&copy);
+        assert_eq!((r1.len(), r2.len()), (2, 3));
+        assert_eq!(owner.ro(&copy2), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn tcell_compare() {
+        use std::cmp::Ordering;
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+        struct Marker;
+        let owner = TCellOwner::<Marker>::new();
+        let mut cells: Vec<Rc<TCell<Marker, u32>>> = [3, 1, 2, 1]
+            .iter()
+            .map(|v| Rc::new(TCell::new(*v)))
+            .collect();
+        cells.sort_by(|a, b| owner.cmp(a, b));
+        let values: Vec<u32> = cells.iter().map(|c| owner.get(c)).collect();
+        assert_eq!(values, [1, 1, 2, 3]);
+        cells.dedup_by(|a, b| owner.eq(a, b));
+        assert_eq!(cells.len(), 3);
+        assert!(owner.eq(&cells[0], &cells[0]));
+        assert_eq!(owner.cmp(&cells[2], &cells[2]), Ordering::Equal);
+
+        let f1 = TCell::<Marker, f64>::new(1.5);
+        let f2 = TCell::<Marker, f64>::new(f64::NAN);
+        assert_eq!(owner.partial_cmp(&f1, &f1), Some(Ordering::Equal));
+        assert_eq!(owner.partial_cmp(&f1, &f2), None);
+
+        let cell = TCell::<Marker, str>::from_boxed("hash".into());
+        let mut h1 = DefaultHasher::new();
+        owner.hash_cell(&*cell, &mut h1);
+        let mut h2 = DefaultHasher::new();
+        "hash".hash(&mut h2);
+        assert_eq!(h1.finish(), h2.finish());
+    }
+
+    #[test]
+    fn tcell_swap() {
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let c1 = TCell::new(String::from("one"));
+        let c2 = TCell::new(String::from("two"));
+        owner.swap(&c1, &c2);
+        assert_eq!(
+            (owner.ro(&c1).as_str(), owner.ro(&c2).as_str()),
+            ("two", "one")
+        );
+        owner.swap(&c1, &c1);
+        assert_eq!(owner.ro(&c1), "two");
+        let z = TCell::new(());
+        owner.swap(&z, &z);
     }
 
     #[test]
@@ -220,4 +5086,109 @@ mod tests {
         let mut _owner = ACellOwner::new(); // Panics here
         let _ = rx.recv();
     }
+
+    // Tests aimed at running under Miri (`cargo +nightly miri test
+    // miri`), which checks that the references handed out by the
+    // multi-borrow calls really don't overlap
+    mod miri {
+        use super::super::{TCell, TCellOwner};
+
+        #[test]
+        fn miri_rw2_rw3() {
+            struct Marker;
+            let mut owner = TCellOwner::<Marker>::new();
+            let c1 = TCell::new(1u32);
+            let c2 = TCell::new(2u32);
+            let c3 = TCell::new(3u32);
+            let (r1, r2) = owner.rw2(&c1, &c2);
+            std::mem::swap(r1, r2);
+            let (r1, r2, r3) = owner.rw3(&c1, &c2, &c3);
+            *r1 += *r2 + *r3;
+            assert_eq!((owner.get(&c1), owner.get(&c2)), (6, 1));
+        }
+
+        #[test]
+        fn miri_rw2_struct_fields() {
+            struct Marker;
+            struct Fields {
+                a: TCell<Marker, u8>,
+                b: TCell<Marker, u64>,
+                c: TCell<Marker, [u16; 3]>,
+            }
+            let mut owner = TCellOwner::<Marker>::new();
+            let fields = Fields {
+                a: TCell::new(1),
+                b: TCell::new(2),
+                c: TCell::new([3; 3]),
+            };
+            let (a, b) = owner.rw2(&fields.a, &fields.b);
+            *a += 1;
+            *b += u64::from(*a);
+            let (c, a, b) = owner.rw3(&fields.c, &fields.a, &fields.b);
+            c[0] += u16::from(*a) + *b as u16;
+            assert_eq!(owner.ro(&fields.c), &[9, 3, 3]);
+        }
+
+        #[test]
+        fn miri_as_ptr() {
+            struct Marker;
+            let mut owner = TCellOwner::<Marker>::new();
+            let cell = TCell::new(1u32);
+            let ptr = cell.as_ptr();
+            // Reads are fine alongside `ro` borrows
+            let r = owner.ro(&cell);
+            assert_eq!(unsafe { *ptr }, 1);
+            assert_eq!(*r, 1);
+            // Writes are fine whilst nothing else is borrowed
+            unsafe { *ptr = 2 };
+            *owner.rw(&cell) += 1;
+            assert_eq!(unsafe { *ptr }, 3);
+            // The pointer stays valid across owner borrows
+            let owner_ref = &mut owner;
+            unsafe { *ptr += 1 };
+            assert_eq!(owner_ref.get(&cell), 4);
+        }
+
+        #[test]
+        fn miri_uninit() {
+            use std::mem::MaybeUninit;
+            struct Marker;
+            let mut owner = TCellOwner::<Marker>::new();
+            let cells: Vec<TCell<Marker, MaybeUninit<Box<u32>>>> =
+                (0..3).map(|_| TCell::new_uninit()).collect();
+            for (i, cell) in cells.iter().enumerate() {
+                **owner.write(cell, Box::new(0)) += i as u32;
+            }
+            let refs: Vec<&TCell<Marker, Box<u32>>> = cells
+                .iter()
+                .map(|c| unsafe { c.assume_init_ref() })
+                .collect();
+            let (a, b) = owner.rw2(refs[0], refs[2]);
+            std::mem::swap(a, b);
+            assert_eq!(**owner.ro(refs[0]), 2);
+            // The contents must be dropped through the initialized
+            // type, or the boxes would leak
+            let cells: Vec<TCell<Marker, Box<u32>>> = cells
+                .into_iter()
+                .map(|c| unsafe { c.assume_init() })
+                .collect();
+            assert_eq!(**owner.ro(&cells[1]), 1);
+        }
+
+        #[test]
+        fn miri_raw_get() {
+            use std::mem::MaybeUninit;
+            struct Marker;
+            let owner = TCellOwner::<Marker>::new();
+            let mut slot = MaybeUninit::<TCell<Marker, [u16; 4]>>::uninit();
+            let ptr = TCell::raw_get(slot.as_mut_ptr());
+            unsafe { ptr.write([5; 4]) };
+            let cell = unsafe { slot.assume_init_ref() };
+            assert_eq!(owner.ro(cell), &[5; 4]);
+            let slice: Box<TCell<Marker, [u16]>> = Box::new(TCell::new([1, 2, 3]));
+            let ptr = TCell::raw_get(&*slice);
+            assert_eq!(ptr.len(), 3);
+            assert!(std::ptr::eq(ptr, TCell::as_ptr(&slice)));
+        }
+    }
 }
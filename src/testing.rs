@@ -0,0 +1,457 @@
+//! Checks for hand-written implementations of [`TCellMarker`] and
+//! [`TLCellMarker`], for use in the test suites of crates that
+//! implement those traits without the [`tcell_marker!`] or
+//! [`tlcell_marker!`] macros, and for code that creates
+//! [`QCellOwner`] instances.  Also a [`MockOwner`] for exercising the
+//! error paths of code that uses `try_ro` and `try_rw`.  Requires the
+//! `testing` cargo feature.
+//!
+//! Each check panics with a description of the problem if the marker
+//! type doesn't behave as its safety contract requires.  The checks
+//! create owners for the marker type, and link its flag, so they
+//! must be run at a point where no owner for it exists, and
+//! registry-based owners (`new`, `try_new` and so on) won't be
+//! available for the marker type afterwards.  For example, in a crate
+//! that enables `qcell/testing` in its `[dev-dependencies]`:
+//!
+//! ```
+//!# use qcell::{TCellMarker, TCellMarkerFlag};
+//! struct Slot<const N: usize>;
+//! static FLAGS: [TCellMarkerFlag; 2] = [TCellMarkerFlag::new(), TCellMarkerFlag::new()];
+//! unsafe impl TCellMarker for Slot<0> {
+//!     fn flag() -> &'static TCellMarkerFlag { &FLAGS[0] }
+//! }
+//! unsafe impl TCellMarker for Slot<1> {
+//!     fn flag() -> &'static TCellMarkerFlag { &FLAGS[1] }
+//! }
+//!
+//! // In a `#[test]` function:
+//! qcell::testing::check_tcell_marker::<Slot<0>>();
+//! qcell::testing::check_tcell_marker::<Slot<1>>();
+//! qcell::testing::check_distinct_tcell_markers::<Slot<0>, Slot<1>>();
+//! ```
+//!
+//! Passing the checks doesn't prove that an implementation is sound,
+//! since for example a flag shared with some marker type that isn't
+//! checked can't be detected.  However they catch the common
+//! mistakes.
+//!
+//! [`TCellMarker`]: ../trait.TCellMarker.html
+//! [`TLCellMarker`]: ../trait.TLCellMarker.html
+//! [`tcell_marker!`]: ../macro.tcell_marker.html
+//! [`tlcell_marker!`]: ../macro.tlcell_marker.html
+//! [`QCellOwner`]: ../struct.QCellOwner.html
+//! [`MockOwner`]: struct.MockOwner.html
+
+use crate::{
+    OwnerMismatchError, QCell, QCellOwner, TCell, TCellMarker, TCellOwner, TLCell, TLCellMarker,
+    TLCellOwner,
+};
+use std::cell::Cell;
+use std::panic::{catch_unwind, AssertUnwindSafe, Location};
+use std::rc::Rc;
+
+// Counts drops, to check that cell contents are dropped exactly once
+struct DropCount(Rc<Cell<u32>>);
+
+impl Drop for DropCount {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+/// Check that marker type `Q` behaves correctly with
+/// [`TCellOwner::new_fast`]: its flag is stable, only one owner may
+/// exist at a time in the whole process, dropping the owner allows
+/// another to be created, and cells can be borrowed through the
+/// owner.
+///
+/// [`TCellOwner::new_fast`]: ../struct.TCellOwner.html#method.new_fast
+#[track_caller]
+pub fn check_tcell_marker<Q: TCellMarker>() {
+    let name = std::any::type_name::<Q>();
+    assert!(
+        std::ptr::eq(Q::flag(), Q::flag()),
+        "TCellMarker::flag() returned different flags for `{}`",
+        name
+    );
+
+    let mut owner = match TCellOwner::<Q>::try_new_fast() {
+        Ok(owner) => owner,
+        Err(e) => panic!("Failed to create first owner: {}", e),
+    };
+    assert!(
+        TCellOwner::<Q>::try_new_fast().is_err(),
+        "Second owner for `{}` was created in the same thread",
+        name
+    );
+    assert!(
+        std::thread::spawn(|| TCellOwner::<Q>::try_new_fast().is_err())
+            .join()
+            .unwrap(),
+        "Second owner for `{}` was created in another thread",
+        name
+    );
+    assert!(
+        TCellOwner::<Q>::try_new().is_err(),
+        "Registry-based owner for `{}` was created alongside a fast owner",
+        name
+    );
+
+    let count = Rc::new(Cell::new(0));
+    {
+        let c1 = TCell::<Q, _>::new((1u32, DropCount(count.clone())));
+        let c2 = TCell::<Q, _>::new(2u32);
+        owner.rw(&c1).0 += 10;
+        assert_eq!(owner.ro(&c1).0, 11, "Mutation through rw() was lost");
+        assert!(
+            owner.try_rw2(&c1, &c1).is_err(),
+            "try_rw2() allowed aliasing"
+        );
+        let (a, b) = owner.rw2(&c1, &c2);
+        std::mem::swap(&mut a.0, b);
+        assert_eq!((owner.ro(&c1).0, *owner.ro(&c2)), (2, 11));
+    }
+    assert_eq!(count.get(), 1, "Cell contents not dropped exactly once");
+
+    drop(owner);
+    assert!(
+        TCellOwner::<Q>::try_new_fast().is_ok(),
+        "Owner for `{}` could not be created again after the first was dropped",
+        name
+    );
+}
+
+/// Check that marker types `Q1` and `Q2` don't share a flag, so that
+/// owners of each can exist at the same time.  This requires that no
+/// owner of either type exists when called.
+#[track_caller]
+pub fn check_distinct_tcell_markers<Q1: TCellMarker, Q2: TCellMarker>() {
+    assert!(
+        !std::ptr::eq(Q1::flag(), Q2::flag()),
+        "`{}` and `{}` share a TCellMarkerFlag",
+        std::any::type_name::<Q1>(),
+        std::any::type_name::<Q2>()
+    );
+    let _owner1 = TCellOwner::<Q1>::new_fast();
+    let _owner2 = TCellOwner::<Q2>::new_fast();
+}
+
+/// Check that marker type `Q` behaves correctly with
+/// [`TLCellOwner::new_fast`]: its flag is stable, only one owner may
+/// exist at a time in each thread, other threads have a flag of their
+/// own, dropping the owner allows another to be created, and cells can
+/// be borrowed through the owner.
+///
+/// [`TLCellOwner::new_fast`]: ../struct.TLCellOwner.html#method.new_fast
+#[track_caller]
+pub fn check_tlcell_marker<Q: TLCellMarker>() {
+    let name = std::any::type_name::<Q>();
+    assert!(
+        std::ptr::eq(Q::flag(), Q::flag()),
+        "TLCellMarker::flag() returned different keys for `{}`",
+        name
+    );
+
+    let mut owner = match TLCellOwner::<Q>::try_new_fast() {
+        Ok(owner) => owner,
+        Err(e) => panic!("Failed to create first owner: {}", e),
+    };
+    assert!(
+        TLCellOwner::<Q>::try_new_fast().is_err(),
+        "Second owner for `{}` was created in the same thread",
+        name
+    );
+    assert!(
+        std::thread::spawn(|| TLCellOwner::<Q>::try_new_fast().is_ok())
+            .join()
+            .unwrap(),
+        "Owner for `{}` could not be created in another thread",
+        name
+    );
+    assert!(
+        TLCellOwner::<Q>::try_new().is_err(),
+        "Set-based owner for `{}` was created alongside a fast owner",
+        name
+    );
+
+    let count = Rc::new(Cell::new(0));
+    {
+        let c1 = TLCell::<Q, _>::new((1u32, DropCount(count.clone())));
+        let c2 = TLCell::<Q, _>::new(2u32);
+        owner.rw(&c1).0 += 10;
+        assert_eq!(owner.ro(&c1).0, 11, "Mutation through rw() was lost");
+        assert!(
+            owner.try_rw_n((&c1, &c1)).is_err(),
+            "try_rw_n() allowed aliasing"
+        );
+        let (a, b) = owner.rw2(&c1, &c2);
+        std::mem::swap(&mut a.0, b);
+        assert_eq!((owner.ro(&c1).0, *owner.ro(&c2)), (2, 11));
+    }
+    assert_eq!(count.get(), 1, "Cell contents not dropped exactly once");
+
+    drop(owner);
+    assert!(
+        TLCellOwner::<Q>::try_new_fast().is_ok(),
+        "Owner for `{}` could not be created again after the first was dropped",
+        name
+    );
+}
+
+/// Check that marker types `Q1` and `Q2` don't share a thread-local
+/// flag, so that owners of each can exist at the same time.  This
+/// requires that no owner of either type exists in the current thread
+/// when called.
+#[track_caller]
+pub fn check_distinct_tlcell_markers<Q1: TLCellMarker, Q2: TLCellMarker>() {
+    assert!(
+        !std::ptr::eq(Q1::flag(), Q2::flag()),
+        "`{}` and `{}` share a TLCellMarkerFlag",
+        std::any::type_name::<Q1>(),
+        std::any::type_name::<Q2>()
+    );
+    let _owner1 = TLCellOwner::<Q1>::new_fast();
+    let _owner2 = TLCellOwner::<Q2>::new_fast();
+}
+
+/// Check that owners returned by `make` behave as [`QCellOwner`]
+/// instances should: each has its own ID, `ro` after `cell` returns
+/// the stored value, mutations through `rw` are kept, cells of one
+/// owner are rejected by another, `rw2` and `rw3` reject aliased
+/// cells, and cell contents are dropped exactly once.  `make` is
+/// called twice, for example `check_qcell_owner(QCellOwner::new)`.
+///
+/// The aliasing checks rely on `rw2` and `rw3` panicking, so their
+/// panic messages appear in the test output.
+///
+/// [`QCellOwner`]: ../struct.QCellOwner.html
+#[track_caller]
+pub fn check_qcell_owner(mut make: impl FnMut() -> QCellOwner) {
+    let mut owner = make();
+    let mut other = make();
+    assert_ne!(owner.id(), other.id(), "Two owners have the same ID");
+
+    let count = Rc::new(Cell::new(0));
+    {
+        let c1 = owner.cell((1u32, DropCount(count.clone())));
+        let c2 = owner.cell(2u32);
+        let c3 = owner.cell(3u32);
+        assert_eq!(owner.ro(&c1).0, 1, "ro() didn't return the stored value");
+        owner.rw(&c1).0 += 10;
+        assert_eq!(owner.ro(&c1).0, 11, "Mutation through rw() was lost");
+        assert!(
+            owner.owns(&c1) && !other.owns(&c1),
+            "owns() gave the wrong answer"
+        );
+        assert!(
+            other.try_ro(&c1).is_err() && other.try_rw(&c1).is_err(),
+            "Cell was borrowed through an owner that doesn't own it"
+        );
+        assert!(
+            catch_unwind(AssertUnwindSafe(|| {
+                owner.rw2(&c1, &c1);
+            }))
+            .is_err(),
+            "rw2() allowed aliasing"
+        );
+        assert!(
+            catch_unwind(AssertUnwindSafe(|| {
+                owner.rw3(&c1, &c2, &c1);
+            }))
+            .is_err(),
+            "rw3() allowed aliasing"
+        );
+        let (a, b, c) = owner.rw3(&c1, &c2, &c3);
+        std::mem::swap(&mut a.0, b);
+        *c += 1;
+        assert_eq!(
+            (owner.ro(&c1).0, *owner.ro(&c2), *owner.ro(&c3)),
+            (2, 11, 4)
+        );
+    }
+    assert_eq!(count.get(), 1, "Cell contents not dropped exactly once");
+}
+
+/// Stand-in for a [`QCellOwner`] whose ownership check can be
+/// switched off, so that the error paths of code using `try_ro` and
+/// `try_rw` can be exercised.  Whilst validation is enabled, which is
+/// the default, it behaves like the `QCellOwner` it wraps.  Once
+/// disabled with [`set_validate`], every access fails with an
+/// [`OwnerMismatchError`], and `ro` and `rw` panic.
+///
+/// ```
+///# use qcell::testing::MockOwner;
+/// let mut owner = MockOwner::new();
+/// let cell = owner.cell(1);
+/// assert_eq!(owner.try_ro(&cell).copied(), Ok(1));
+/// owner.set_validate(false);
+/// assert!(owner.try_rw(&cell).is_err());
+/// ```
+///
+/// [`QCellOwner`]: ../struct.QCellOwner.html
+/// [`OwnerMismatchError`]: ../struct.OwnerMismatchError.html
+/// [`set_validate`]: #method.set_validate
+#[derive(Debug, Default)]
+pub struct MockOwner {
+    owner: QCellOwner,
+    invalid: bool,
+}
+
+impl MockOwner {
+    /// Create a new owner, with validation enabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable or disable validation.  Whilst disabled, every access
+    /// reports that the cell is not owned by this owner.
+    pub fn set_validate(&mut self, validate: bool) {
+        self.invalid = !validate;
+    }
+
+    /// Create a new cell owned by this owner.
+    pub fn cell<T>(&self, value: T) -> QCell<T> {
+        self.owner.cell(value)
+    }
+
+    /// Borrow contents of a `QCell` immutably, as for
+    /// `QCellOwner::try_ro`.
+    #[track_caller]
+    pub fn try_ro<'a, T: ?Sized>(&'a self, qc: &'a QCell<T>) -> Result<&'a T, OwnerMismatchError> {
+        self.check(qc)?;
+        self.owner.try_ro(qc)
+    }
+
+    /// Borrow contents of a `QCell` mutably, as for
+    /// `QCellOwner::try_rw`.
+    #[track_caller]
+    pub fn try_rw<'a, T: ?Sized>(
+        &'a mut self,
+        qc: &'a QCell<T>,
+    ) -> Result<&'a mut T, OwnerMismatchError> {
+        self.check(qc)?;
+        self.owner.try_rw(qc)
+    }
+
+    /// Borrow contents of a `QCell` immutably, panicking on failure.
+    #[track_caller]
+    pub fn ro<'a, T: ?Sized>(&'a self, qc: &'a QCell<T>) -> &'a T {
+        match self.try_ro(qc) {
+            Ok(value) => value,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Borrow contents of a `QCell` mutably, panicking on failure.
+    #[track_caller]
+    pub fn rw<'a, T: ?Sized>(&'a mut self, qc: &'a QCell<T>) -> &'a mut T {
+        match self.try_rw(qc) {
+            Ok(value) => value,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[track_caller]
+    fn check<T: ?Sized>(&self, qc: &QCell<T>) -> Result<(), OwnerMismatchError> {
+        if self.invalid {
+            Err(OwnerMismatchError::new(
+                qc,
+                self.owner.id(),
+                Location::caller(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tcell_marker, tlcell_marker, TCellMarkerFlag, TLCellMarkerFlag};
+
+    #[test]
+    fn testing_macro_markers() {
+        tcell_marker!(
+            struct Marker1;
+        );
+        tcell_marker!(
+            struct Marker2;
+        );
+        tlcell_marker!(
+            struct TLMarker1;
+        );
+        tlcell_marker!(
+            struct TLMarker2;
+        );
+        check_tcell_marker::<Marker1>();
+        check_tcell_marker::<Marker2>();
+        check_distinct_tcell_markers::<Marker1, Marker2>();
+        check_tlcell_marker::<TLMarker1>();
+        check_tlcell_marker::<TLMarker2>();
+        check_distinct_tlcell_markers::<TLMarker1, TLMarker2>();
+    }
+
+    #[test]
+    fn testing_qcell_owner() {
+        check_qcell_owner(QCellOwner::new);
+    }
+
+    #[test]
+    fn testing_mock_owner() {
+        let mut owner = MockOwner::new();
+        let cell = owner.cell(1);
+        *owner.rw(&cell) += 1;
+        assert_eq!(*owner.ro(&cell), 2);
+
+        owner.set_validate(false);
+        let err = owner.try_ro(&cell).unwrap_err();
+        assert_eq!(err.cell_owner_id(), cell.owner_id());
+        assert_eq!(err.location().line(), line!() - 2);
+        assert!(owner.try_rw(&cell).is_err());
+        assert!(catch_unwind(AssertUnwindSafe(|| *owner.ro(&cell))).is_err());
+
+        owner.set_validate(true);
+        assert_eq!(owner.try_ro(&cell).copied(), Ok(2));
+        // Cells of other owners are still rejected
+        let other = QCellOwner::new().cell(3);
+        assert_eq!(
+            owner.try_ro(&other).unwrap_err().cell_owner_id(),
+            other.owner_id()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "returned different flags")]
+    fn testing_unstable_flag() {
+        struct Marker;
+        unsafe impl TCellMarker for Marker {
+            fn flag() -> &'static TCellMarkerFlag {
+                Box::leak(Box::new(TCellMarkerFlag::new()))
+            }
+        }
+        check_tcell_marker::<Marker>();
+    }
+
+    #[test]
+    #[should_panic(expected = "share a TLCellMarkerFlag")]
+    fn testing_shared_flag() {
+        std::thread_local! {
+            static FLAG: TLCellMarkerFlag = const { TLCellMarkerFlag::new() };
+        }
+        struct Marker1;
+        struct Marker2;
+        unsafe impl TLCellMarker for Marker1 {
+            fn flag() -> &'static std::thread::LocalKey<TLCellMarkerFlag> {
+                &FLAG
+            }
+        }
+        unsafe impl TLCellMarker for Marker2 {
+            fn flag() -> &'static std::thread::LocalKey<TLCellMarkerFlag> {
+                &FLAG
+            }
+        }
+        check_distinct_tlcell_markers::<Marker1, Marker2>();
+    }
+}
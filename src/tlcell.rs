@@ -1,33 +1,183 @@
-use std::any::TypeId;
-use std::cell::UnsafeCell;
-use std::collections::HashSet;
-use std::marker::PhantomData;
+use core::cell::{Cell, RefCell, UnsafeCell};
+use core::convert::Infallible;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::panic::{RefUnwindSafe, UnwindSafe};
 
+#[cfg(feature = "std")]
+use core::any::TypeId;
+#[cfg(feature = "std")]
+use std::thread::LocalKey;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::tcell::private::Sealed;
+use crate::tcell::AliasError;
+#[cfg(feature = "std")]
+use crate::tcell::{DuplicateOwnerError, FLAG_FREE, FLAG_OWNED, FLAG_UNLINKED};
+use crate::traits::{GuardedCellOwner, GuardedCellReader, OptionCellExt};
+
+#[cfg(feature = "std")]
 std::thread_local! {
-    static SINGLETON_CHECK: std::cell::RefCell<HashSet<TypeId>> = std::cell::RefCell::new(HashSet::new());
+    static SINGLETON_CHECK: RefCell<MarkerSet> = const { RefCell::new(MarkerSet::new()) };
+}
+
+#[cfg(feature = "std")]
+const INLINE_MARKERS: usize = 8;
+
+// Entry in the set for one marker type.  The name is only kept for
+// `diagnostics::thread_owner_names()`.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy)]
+struct MarkerEntry {
+    id: TypeId,
+    #[cfg(feature = "debug")]
+    name: &'static str,
+}
+
+#[cfg(feature = "std")]
+impl MarkerEntry {
+    fn of<Q: 'static>() -> Self {
+        Self {
+            id: TypeId::of::<Q>(),
+            #[cfg(feature = "debug")]
+            name: core::any::type_name::<Q>(),
+        }
+    }
+}
+
+// Set of the marker types that have an owner in the current thread.
+// Usually only a few marker types are in use at a time, so these are
+// kept in a small inline array, which needs no allocation and no
+// hashing.  Only if that fills up do further entries spill over into
+// a `Vec`.
+#[cfg(feature = "std")]
+struct MarkerSet {
+    inline: [Option<MarkerEntry>; INLINE_MARKERS],
+    spill: Vec<MarkerEntry>,
+}
+
+#[cfg(feature = "std")]
+impl MarkerSet {
+    const fn new() -> Self {
+        Self {
+            inline: [None; INLINE_MARKERS],
+            spill: Vec::new(),
+        }
+    }
+
+    fn contains(&self, id: TypeId) -> bool {
+        self.entries().any(|e| e.id == id)
+    }
+
+    // Returns false if already present
+    fn insert(&mut self, entry: MarkerEntry) -> bool {
+        if self.contains(entry.id) {
+            return false;
+        }
+        match self.inline.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => *slot = Some(entry),
+            None => self.spill.push(entry),
+        }
+        true
+    }
+
+    fn remove(&mut self, id: TypeId) {
+        let is_id = |slot: &Option<MarkerEntry>| slot.map(|e| e.id) == Some(id);
+        if let Some(slot) = self.inline.iter_mut().find(|slot| is_id(slot)) {
+            *slot = None;
+        } else if let Some(pos) = self.spill.iter().position(|e| e.id == id) {
+            self.spill.swap_remove(pos);
+        }
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &MarkerEntry> {
+        self.inline.iter().flatten().chain(self.spill.iter())
+    }
+}
+
+// Number of marker types with an owner in the current thread, for
+// `diagnostics::thread_owner_count()`
+#[cfg(feature = "std")]
+pub(crate) fn thread_owner_count() -> usize {
+    SINGLETON_CHECK.with(|set| set.borrow().entries().count())
+}
+
+// Names of the marker types with an owner in the current thread, for
+// `diagnostics::thread_owner_names()`
+#[cfg(feature = "debug")]
+pub(crate) fn thread_owner_names() -> Vec<&'static str> {
+    SINGLETON_CHECK.with(|set| set.borrow().entries().map(|e| e.name).collect())
 }
 
 /// Borrowing-owner of zero or more [`TLCell`](struct.TLCell.html)
 /// instances.
 ///
+/// Auto traits: `Unpin`, `UnwindSafe` and `RefUnwindSafe`, but
+/// neither `Send` nor `Sync`, whatever the marker type.
+///
 /// See [crate documentation](index.html).
 pub struct TLCellOwner<Q: 'static> {
     // Use *const to disable Send and Sync
     typ: PhantomData<*const Q>,
+    // What to release on drop
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    claim: Claim,
+}
+
+enum Claim {
+    // Created with `new_unchecked()`, so nothing to release
+    Unchecked,
+    // Entry in the per-thread set
+    #[cfg(feature = "std")]
+    Set,
+    // Flag of a `TLCellMarker` type, claimed by `new_fast()`
+    #[cfg(feature = "std")]
+    Flag(&'static LocalKey<TLCellMarkerFlag>),
 }
 
 impl<Q: 'static> Drop for TLCellOwner<Q> {
     fn drop(&mut self) {
-        SINGLETON_CHECK.with(|set| set.borrow_mut().remove(&TypeId::of::<Q>()));
+        // If the owner is itself stored in a thread-local, this may run
+        // after SINGLETON_CHECK has been destroyed during thread exit.
+        // In that case there is nothing to deregister from, and the
+        // thread can't create any more owners anyway.  (Without `std`,
+        // only unregistered owners exist.)
+        #[cfg(feature = "std")]
+        match self.claim {
+            Claim::Unchecked => (),
+            Claim::Set => {
+                let _ = SINGLETON_CHECK.try_with(|set| set.borrow_mut().remove(TypeId::of::<Q>()));
+            }
+            Claim::Flag(flag) => {
+                let _ = flag.try_with(|flag| flag.state.set(FLAG_FREE));
+            }
+        }
     }
 }
 
+/// Creates the owner with [`TLCellOwner::new`], so panics if another
+/// owner with the same marker type already exists in this thread.
+///
+/// [`TLCellOwner::new`]: struct.TLCellOwner.html#method.new
+#[cfg(feature = "std")]
 impl<Q: 'static> Default for TLCellOwner<Q> {
+    #[track_caller]
     fn default() -> Self {
         TLCellOwner::new()
     }
 }
 
+impl<Q: 'static> fmt::Debug for TLCellOwner<Q> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TLCellOwner")
+            .field("marker", &core::any::type_name::<Q>())
+            .finish()
+    }
+}
+
 impl<Q: 'static> TLCellOwner<Q> {
     /// Create the singleton owner instance.  Each owner may be used
     /// to create many `TLCell` instances.  There may be only one
@@ -35,13 +185,178 @@ impl<Q: 'static> TLCellOwner<Q> {
     /// different marker type `Q`.  This call panics if a second
     /// simultaneous instance is created.  Since the owner is only
     /// valid to use in the thread it is created in, it does not
-    /// support `Send` or `Sync`.
+    /// support `Send` or `Sync`.  The panic message names the marker
+    /// type.
+    #[cfg(feature = "std")]
+    #[track_caller]
     pub fn new() -> Self {
-        SINGLETON_CHECK.with(|set| {
-            assert!(set.borrow_mut().insert(TypeId::of::<Q>()),
-                    "Illegal to create two TLCellOwner instances within the same thread with the same marker type parameter");
-        });
-        Self { typ: PhantomData }
+        match Self::try_new() {
+            Ok(owner) => owner,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Attempt to create the singleton owner instance.  This works
+    /// the same as [`new`](#method.new), except that if another
+    /// instance with the same marker type `Q` already exists in the
+    /// current thread, a [`DuplicateOwnerError`] is returned instead
+    /// of panicking.  In that case nothing is registered, so a later
+    /// call may succeed once the other owner is dropped.
+    ///
+    /// [`DuplicateOwnerError`]: struct.DuplicateOwnerError.html
+    #[cfg(feature = "std")]
+    pub fn try_new() -> Result<Self, DuplicateOwnerError> {
+        if SINGLETON_CHECK.with(|set| set.borrow_mut().insert(MarkerEntry::of::<Q>())) {
+            Ok(Self {
+                typ: PhantomData,
+                claim: Claim::Set,
+            })
+        } else {
+            Err(DuplicateOwnerError::thread_local::<Q>())
+        }
+    }
+
+    /// Create the singleton owner instance and pass it to the given
+    /// closure, returning the closure's result.  The owner is dropped
+    /// when the closure returns or panics, so the marker type `Q` is
+    /// always released again for the current thread afterwards.
+    /// Panics if another owner with the same marker type already
+    /// exists in this thread, like [`new`](#method.new).
+    #[cfg(feature = "std")]
+    #[track_caller]
+    pub fn scope<R, F>(f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let mut owner = Self::new();
+        f(&mut owner)
+    }
+
+    /// Works the same as [`scope`](#method.scope), except that if
+    /// another owner with the same marker type `Q` already exists in
+    /// this thread, the closure is not called and an error is
+    /// returned instead.
+    #[cfg(feature = "std")]
+    pub fn try_scope<R, F>(f: F) -> Result<R, DuplicateOwnerError>
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let mut owner = Self::try_new()?;
+        Ok(f(&mut owner))
+    }
+
+    /// Create an owner instance without checking or updating the
+    /// per-thread singleton set.  The returned owner leaves the set
+    /// untouched when it is dropped, so any owner created with
+    /// [`new`](#method.new) is still tracked correctly.
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee that no other owner with the same
+    /// marker type `Q` exists in the current thread for as long as
+    /// the returned owner exists, whether created through this call
+    /// or through [`new`](#method.new).
+    #[inline]
+    pub const unsafe fn new_unchecked() -> Self {
+        Self {
+            typ: PhantomData,
+            claim: Claim::Unchecked,
+        }
+    }
+
+    /// Create the singleton owner instance for a marker type defined
+    /// with [`tlcell_marker!`], panicking if another owner with the
+    /// same marker type already exists in the current thread.  This
+    /// claims a thread-local flag belonging to the marker type,
+    /// avoiding the search of the per-thread set used by
+    /// [`new`](#method.new).
+    ///
+    /// Each marker type should be used with either the fast calls or
+    /// the set-based calls, not both.  The first call to `new_fast`
+    /// or `try_new_fast` for a marker type in a thread reserves it in
+    /// that thread's set permanently, so from then on `new`,
+    /// `try_new` and so on will always fail for that marker type in
+    /// that thread, even when no fast owner currently exists.
+    ///
+    /// [`tlcell_marker!`]: macro.tlcell_marker.html
+    #[cfg(feature = "std")]
+    #[inline]
+    #[track_caller]
+    pub fn new_fast() -> Self
+    where
+        Q: TLCellMarker,
+    {
+        match Self::try_new_fast() {
+            Ok(owner) => owner,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Works the same as [`new_fast`](#method.new_fast), except that
+    /// an error is returned instead of panicking if another owner
+    /// with the same marker type already exists in the current
+    /// thread.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn try_new_fast() -> Result<Self, DuplicateOwnerError>
+    where
+        Q: TLCellMarker,
+    {
+        let key = Q::flag();
+        key.with(|flag| loop {
+            match flag.state.get() {
+                FLAG_FREE => {
+                    flag.state.set(FLAG_OWNED);
+                    return Ok(Self {
+                        typ: PhantomData,
+                        claim: Claim::Flag(key),
+                    });
+                }
+                FLAG_UNLINKED => Self::link_flag(flag)?,
+                _ => return Err(DuplicateOwnerError::thread_local::<Q>()),
+            }
+        })
+    }
+
+    // First use of a flag in this thread: reserve the marker type in
+    // the per-thread set for good, so that set-based owners can't
+    // co-exist with flag-based ones
+    #[cfg(feature = "std")]
+    #[cold]
+    fn link_flag(flag: &TLCellMarkerFlag) -> Result<(), DuplicateOwnerError> {
+        if SINGLETON_CHECK.with(|set| set.borrow_mut().insert(MarkerEntry::of::<Q>())) {
+            flag.state.set(FLAG_FREE);
+            Ok(())
+        } else {
+            Err(DuplicateOwnerError::thread_local::<Q>())
+        }
+    }
+
+    /// Test whether an owner with marker type `Q` currently exists
+    /// in the current thread.  Owners in other threads are not
+    /// considered, since they don't conflict with creating one here.
+    /// Once [`new_fast`](#method.new_fast) has been used for `Q` in
+    /// this thread, this always returns `true`.
+    #[cfg(feature = "std")]
+    pub fn is_active() -> bool {
+        SINGLETON_CHECK.with(|set| set.borrow().contains(TypeId::of::<Q>()))
+    }
+
+    /// Release the registration of marker type `Q` in the current
+    /// thread, so that a new owner can be created even though the old
+    /// one was never dropped, for example because it was passed to
+    /// `mem::forget` or leaked.
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee that any existing owner with marker
+    /// type `Q` in the current thread will never be used again, and
+    /// that its drop will never run.  This must not be used at all
+    /// with marker types used with [`new_fast`](#method.new_fast),
+    /// since the set then holds a permanent reservation.
+    #[cfg(feature = "std")]
+    pub unsafe fn force_release() {
+        SINGLETON_CHECK.with(|set| set.borrow_mut().remove(TypeId::of::<Q>()));
     }
 
     /// Create a new cell owned by this owner instance.  See also
@@ -52,63 +367,335 @@ impl<Q: 'static> TLCellOwner<Q> {
         TLCell::<Q, T>::new(value)
     }
 
+    /// Create a vector of new cells owned by this owner instance, one
+    /// for each value returned by the iterator.
+    #[cfg(feature = "alloc")]
+    pub fn cells_from_iter<T>(&self, values: impl IntoIterator<Item = T>) -> Vec<TLCell<Q, T>> {
+        values.into_iter().map(TLCell::new).collect()
+    }
+
+    /// Convert a vector of values into a vector of new cells owned by
+    /// this owner instance, without copying.  See
+    /// [`TLCell::wrap_vec`].
+    ///
+    /// [`TLCell::wrap_vec`]: struct.TLCell.html#method.wrap_vec
+    #[cfg(feature = "alloc")]
+    pub fn cells_from_vec<T>(&self, values: Vec<T>) -> Vec<TLCell<Q, T>> {
+        TLCell::wrap_vec(values)
+    }
+
+    /// Create an array of new cells owned by this owner instance,
+    /// containing the results of calling the closure with each index
+    /// in turn, as for `std::array::from_fn`.
+    pub fn cell_array<T, const N: usize>(&self, f: impl FnMut(usize) -> T) -> [TLCell<Q, T>; N] {
+        TLCell::new_array(core::array::from_fn(f))
+    }
+
     /// Borrow contents of a `TLCell` immutably (read-only).  Many
     /// `TLCell` instances can be borrowed immutably at the same time
     /// from the same owner.
     #[inline]
-    pub fn ro<'a, T>(&'a self, tc: &'a TLCell<Q, T>) -> &'a T {
+    pub fn ro<'a, T: ?Sized>(&'a self, tc: &'a TLCell<Q, T>) -> &'a T {
         unsafe { &*tc.value.get() }
     }
 
+    /// Get a copy of the contents of a `TLCell`.  This is a shorthand
+    /// for `*owner.ro(&cell)`, and also works on `Rc<TLCell<..>>` and
+    /// similar by auto-deref, e.g. `owner.get(&rc)`.
+    #[inline]
+    pub fn get<T: Copy>(&self, tc: &TLCell<Q, T>) -> T {
+        *self.ro(tc)
+    }
+
+    /// Get a clone of the contents of a `TLCell`.  This is a
+    /// shorthand for `owner.ro(&cell).clone()`.
+    #[inline]
+    pub fn cloned<T: Clone>(&self, tc: &TLCell<Q, T>) -> T {
+        self.ro(tc).clone()
+    }
+
+    /// Pass a shared reference to the contents of a `TLCell` to the
+    /// given closure, returning the closure's result.  The borrow is
+    /// scoped to the closure, so the owner is free again as soon as
+    /// the call returns.
+    #[inline]
+    pub fn read<T: ?Sized, R>(&self, tc: &TLCell<Q, T>, f: impl FnOnce(&T) -> R) -> R {
+        f(self.ro(tc))
+    }
+
+    /// Create a new `TLCell` containing a clone of the contents of the
+    /// given cell.  See [`TLCell::clone_with`].
+    ///
+    /// [`TLCell::clone_with`]: struct.TLCell.html#method.clone_with
+    #[inline]
+    pub fn clone_cell<T: Clone>(&self, tc: &TLCell<Q, T>) -> TLCell<Q, T> {
+        TLCell::new(self.cloned(tc))
+    }
+
+    /// Get an adapter that formats the contents of a `TLCell` with
+    /// `Debug`, for example `println!("{:?}", owner.debug(&cell))`.
+    /// The adapter borrows the owner immutably until it is dropped,
+    /// and can be embedded in other formatted output.
+    #[inline]
+    pub fn debug<'a, T: fmt::Debug>(&'a self, tc: &'a TLCell<Q, T>) -> impl fmt::Debug + 'a {
+        Formatted { owner: self, tc }
+    }
+
+    /// Get an adapter that formats the contents of a `TLCell` with
+    /// `Display`, for example `println!("{}", owner.display(&cell))`.
+    /// See [`debug`](#method.debug).
+    #[inline]
+    pub fn display<'a, T: fmt::Display>(&'a self, tc: &'a TLCell<Q, T>) -> impl fmt::Display + 'a {
+        Formatted { owner: self, tc }
+    }
+
     /// Borrow contents of a `TLCell` mutably (read-write).  Only one
     /// `TLCell` at a time can be borrowed from the owner using this
     /// call.  The returned reference must go out of scope before
     /// another can be borrowed.
     #[inline]
-    pub fn rw<'a, T>(&'a mut self, tc: &'a TLCell<Q, T>) -> &'a mut T {
+    pub fn rw<'a, T: ?Sized>(&'a mut self, tc: &'a TLCell<Q, T>) -> &'a mut T {
         unsafe { &mut *tc.value.get() }
     }
 
+    /// Borrow contents of a `TLCell` immutably, as for
+    /// [`ro`](#method.ro).  The marker type guarantees at compile
+    /// time that the owner matches the cell, so this can't fail, and
+    /// the error type is uninhabited.  It exists so that code written
+    /// against `QCellOwner::try_ro` can be switched over unchanged.
+    #[inline]
+    pub fn try_ro<'a, T: ?Sized>(&'a self, tc: &'a TLCell<Q, T>) -> Result<&'a T, Infallible> {
+        Ok(self.ro(tc))
+    }
+
+    /// Borrow contents of a `TLCell` mutably, as for
+    /// [`rw`](#method.rw).  This can't fail.  See
+    /// [`try_ro`](#method.try_ro).
+    #[inline]
+    pub fn try_rw<'a, T: ?Sized>(
+        &'a mut self,
+        tc: &'a TLCell<Q, T>,
+    ) -> Result<&'a mut T, Infallible> {
+        Ok(self.rw(tc))
+    }
+
+    /// Set the contents of a `TLCell`, dropping the old value.  This
+    /// is a shorthand for `*owner.rw(&cell) = value`.
+    #[inline]
+    pub fn set<T>(&mut self, tc: &TLCell<Q, T>, value: T) {
+        *self.rw(tc) = value;
+    }
+
+    /// Replace the contents of a `TLCell`, returning the old value.
+    #[inline]
+    pub fn replace<T>(&mut self, tc: &TLCell<Q, T>, value: T) -> T {
+        core::mem::replace(self.rw(tc), value)
+    }
+
+    /// Take the contents of a `TLCell`, leaving `Default::default()`
+    /// in its place.
+    #[inline]
+    pub fn take<T: Default>(&mut self, tc: &TLCell<Q, T>) -> T {
+        core::mem::take(self.rw(tc))
+    }
+
+    /// Pass a mutable reference to the contents of a `TLCell` to the
+    /// given closure, returning the closure's result.  The borrow is
+    /// scoped to the closure, so several updates can be chained one
+    /// after another without needing to drop `rw` references
+    /// explicitly.
+    #[inline]
+    pub fn update<T: ?Sized, R>(&mut self, tc: &TLCell<Q, T>, f: impl FnOnce(&mut T) -> R) -> R {
+        f(self.rw(tc))
+    }
+
+    /// Pass mutable references to the contents of two `TLCell`
+    /// instances to the given closure, returning the closure's
+    /// result.  As for [`update`](#method.update), the borrows end
+    /// when the closure returns.  Panics if the two `TLCell` instances
+    /// point to the same memory, as for [`rw2`](#method.rw2).
+    #[inline]
+    pub fn update2<T: ?Sized, U: ?Sized, R>(
+        &mut self,
+        tc1: &TLCell<Q, T>,
+        tc2: &TLCell<Q, U>,
+        f: impl FnOnce(&mut T, &mut U) -> R,
+    ) -> R {
+        let (v1, v2) = self.rw2(tc1, tc2);
+        f(v1, v2)
+    }
+
+    /// Pass a mutable reference to the contents of each `TLCell`
+    /// returned by an iterator to the given closure in turn.  Only one
+    /// cell is borrowed at a time, so no check for duplicates is
+    /// needed, and a cell that appears more than once is simply
+    /// visited more than once.
+    #[inline]
+    pub fn for_each_rw<'a, T, I>(&mut self, cells: I, mut f: impl FnMut(&mut T))
+    where
+        I: IntoIterator<Item = &'a TLCell<Q, T>>,
+        T: ?Sized + 'a,
+        Q: 'a,
+    {
+        for cell in cells {
+            f(self.rw(cell));
+        }
+    }
+
+    /// Replace the contents of `dst` with a clone of the contents of
+    /// `src`, using `clone_from` so that existing allocations in
+    /// `dst` may be reused.  If both references are to the same
+    /// `TLCell`, this does nothing.  Panics if the two cells otherwise
+    /// overlap.
+    #[inline]
+    pub fn clone_into<T: Clone>(&mut self, src: &TLCell<Q, T>, dst: &TLCell<Q, T>) {
+        if !core::ptr::eq(src, dst) {
+            let (src, dst) = self.rw2(src, dst);
+            dst.clone_from(src);
+        }
+    }
+
+    /// Move the contents of `src` into `dst`, leaving
+    /// `Default::default()` in `src`.  If both references are to the
+    /// same `TLCell`, this does nothing, rather than resetting the
+    /// contents.  Panics if the two cells otherwise overlap.
+    #[inline]
+    pub fn move_into<T: Default>(&mut self, src: &TLCell<Q, T>, dst: &TLCell<Q, T>) {
+        if !core::ptr::eq(src, dst) {
+            let (src, dst) = self.rw2(src, dst);
+            *dst = core::mem::take(src);
+        }
+    }
+
+    /// Swap the contents of two `TLCell` instances.  If both
+    /// references are to the same `TLCell`, this does nothing, as for
+    /// `Cell::swap`.  Panics if the two cells otherwise overlap.
+    #[inline]
+    pub fn swap<T>(&mut self, tc1: &TLCell<Q, T>, tc2: &TLCell<Q, T>) {
+        if !core::ptr::eq(tc1, tc2) {
+            let (v1, v2) = self.rw2(tc1, tc2);
+            core::mem::swap(v1, v2);
+        }
+    }
+
+    /// Swap the contents of a `TLCell` with a value borrowed from
+    /// elsewhere.  This is the building block for moving values
+    /// between cells with different owners, including owners of a
+    /// different kind, without needing `T: Default`.  Since two
+    /// different owners can be borrowed mutably at the same time, the
+    /// destination is simply borrowed through its own owner:
+    ///
+    /// ```
+    ///# use qcell::{TCell, TCellOwner, TLCell, TLCellOwner};
+    ///# struct Old;
+    ///# struct New;
+    /// let mut old_owner = TLCellOwner::<Old>::new();
+    /// let mut new_owner = TCellOwner::<New>::new();
+    /// let src = TLCell::<Old, _>::new(String::from("data"));
+    /// let dst = TCell::<New, _>::new(String::new());
+    ///
+    /// // Move, leaving the previous contents of `dst` in `src`
+    /// old_owner.swap_with(&src, new_owner.rw(&dst));
+    /// assert_eq!(new_owner.ro(&dst), "data");
+    ///
+    /// // Move, leaving a caller-supplied filler in the source
+    /// let moved = old_owner.replace(&src, String::from("filler"));
+    /// new_owner.set(&dst, moved);
+    ///
+    /// // Clone across, reusing the destination's allocation
+    /// new_owner.rw(&dst).clone_from(old_owner.ro(&src));
+    /// assert_eq!(new_owner.ro(&dst), "filler");
+    /// ```
+    ///
+    /// To move a cell that is owned by value to a different marker
+    /// type, see [`TLCell::rebind`].
+    ///
+    /// [`TLCell::rebind`]: struct.TLCell.html#method.rebind
+    #[inline]
+    pub fn swap_with<T>(&mut self, tc: &TLCell<Q, T>, value: &mut T) {
+        core::mem::swap(self.rw(tc), value);
+    }
+
     /// Borrow contents of two `TLCell` instances mutably.  Panics if
     /// the two `TLCell` instances point to the same memory.
     #[inline]
-    pub fn rw2<'a, T, U>(
+    pub fn rw2<'a, T: ?Sized, U: ?Sized>(
         &'a mut self,
         tc1: &'a TLCell<Q, T>,
         tc2: &'a TLCell<Q, U>,
     ) -> (&'a mut T, &'a mut U) {
-        assert!(
-            tc1 as *const _ as usize != tc2 as *const _ as usize,
-            "Illegal to borrow same TLCell twice with rw2()"
-        );
-        unsafe { (&mut *tc1.value.get(), &mut *tc2.value.get()) }
+        match self.try_rw_n((tc1, tc2)) {
+            Ok(refs) => refs,
+            Err(_) => panic!("Illegal to borrow same TLCell twice with rw2()"),
+        }
     }
 
     /// Borrow contents of three `TLCell` instances mutably.  Panics if
     /// any pair of `TLCell` instances point to the same memory.
     #[inline]
-    pub fn rw3<'a, T, U, V>(
+    pub fn rw3<'a, T: ?Sized, U: ?Sized, V: ?Sized>(
         &'a mut self,
         tc1: &'a TLCell<Q, T>,
         tc2: &'a TLCell<Q, U>,
         tc3: &'a TLCell<Q, V>,
     ) -> (&'a mut T, &'a mut U, &'a mut V) {
-        assert!(
-            (tc1 as *const _ as usize != tc2 as *const _ as usize)
-                && (tc2 as *const _ as usize != tc3 as *const _ as usize)
-                && (tc3 as *const _ as usize != tc1 as *const _ as usize),
-            "Illegal to borrow same TLCell twice with rw3()"
-        );
-        unsafe {
-            (
-                &mut *tc1.value.get(),
-                &mut *tc2.value.get(),
-                &mut *tc3.value.get(),
-            )
+        match self.try_rw_n((tc1, tc2, tc3)) {
+            Ok(refs) => refs,
+            Err(_) => panic!("Illegal to borrow same TLCell twice with rw3()"),
+        }
+    }
+
+    /// Borrow contents of a tuple of `TLCell` instances mutably, for
+    /// example `owner.rw_n((&c1, &c2, &c3, &c4))`.  Tuples of up to
+    /// 8 cells are supported, and the cells may have different
+    /// contained types, including unsized ones such as slices and
+    /// trait objects.  Panics if any pair of `TLCell` instances point
+    /// to the same memory, naming the pair of positions that clash.
+    #[inline]
+    pub fn rw_n<'a, C>(&'a mut self, cells: C) -> C::Output
+    where
+        C: TupleOfTLCells<'a, Q>,
+    {
+        match self.try_rw_n(cells) {
+            Ok(refs) => refs,
+            Err(e) => panic!("Illegal to borrow same TLCell twice with rw_n(): {}", e),
+        }
+    }
+
+    /// Borrow contents of a tuple of `TLCell` instances mutably.
+    /// Returns an error naming the first pair of positions that clash
+    /// if any pair of `TLCell` instances point to the same memory.
+    /// See [`rw_n`](#method.rw_n).
+    #[inline]
+    pub fn try_rw_n<'a, C>(&'a mut self, cells: C) -> Result<C::Output, AliasError>
+    where
+        C: TupleOfTLCells<'a, Q>,
+    {
+        match cells.find_alias() {
+            Some((first, second)) => Err(AliasError::new(first, second)),
+            None => Ok(unsafe { cells.rw_unchecked() }),
         }
     }
 }
 
+// Adapter returned by `TLCellOwner::debug()` and `display()`
+struct Formatted<'a, Q: 'static, T> {
+    owner: &'a TLCellOwner<Q>,
+    tc: &'a TLCell<Q, T>,
+}
+
+impl<'a, Q: 'static, T: fmt::Debug> fmt::Debug for Formatted<'a, Q, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.owner.ro(self.tc), f)
+    }
+}
+
+impl<'a, Q: 'static, T: fmt::Display> fmt::Display for Formatted<'a, Q, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.owner.ro(self.tc), f)
+    }
+}
+
 /// Cell whose contents is owned (for borrowing purposes) by a
 /// [`TLCellOwner`].
 ///
@@ -119,10 +706,28 @@ impl<Q: 'static> TLCellOwner<Q> {
 /// possible to send the cell to another thread, which then allows its
 /// contents to be borrowed using the owner in that thread.
 ///
+/// `TLCell<Q, T>` is `repr(transparent)`, so is guaranteed to have the
+/// same memory layout as `T`, with no space overhead.  So for example
+/// an array of `TLCell<Q, u8>` takes exactly as much memory as an
+/// array of `u8`.  This is part of the public API, and won't change
+/// without a major version bump.
+///
+/// Pointers to cells coerce to pointers to unsized cells on stable
+/// Rust, in the same way as for the contained types, so for example
+/// `Rc<TLCell<Q, [u8; 16]>>` coerces to `Rc<TLCell<Q, [u8]>>` and
+/// `Box<TLCell<Q, Widget>>` to `Box<TLCell<Q, dyn Draw>>`.  With the
+/// `nightly` cargo feature, a cell containing a pointer also coerces
+/// by value, for example `TLCell<Q, &[u8; 16]>` to `TLCell<Q, &[u8]>`.
+///
+/// Auto traits: `Send` if `T: Send`, never `Sync`, `Unpin` if
+/// `T: Unpin`, `UnwindSafe` if `T: UnwindSafe`, and never
+/// `RefUnwindSafe`.  The marker type doesn't affect any of these.
+///
 /// See also [crate documentation](index.html).
 ///
 /// [`TLCellOwner`]: struct.TLCellOwner.html
-pub struct TLCell<Q, T> {
+#[repr(transparent)]
+pub struct TLCell<Q, T: ?Sized> {
     // Use *const to disable Send and Sync
     owner: PhantomData<*const Q>,
     value: UnsafeCell<T>,
@@ -130,7 +735,20 @@ pub struct TLCell<Q, T> {
 
 impl<Q, T> TLCell<Q, T> {
     /// Create a new `TLCell` owned for borrowing purposes by the
-    /// `TLCellOwner` derived from the same marker type `Q`.
+    /// `TLCellOwner` derived from the same marker type `Q`.  No owner
+    /// is needed, so this can be used in `const` contexts, for
+    /// example to initialize a thread-local:
+    ///
+    /// ```
+    ///# use qcell::{TLCell, TLCellOwner};
+    /// struct Marker;
+    /// thread_local! {
+    ///     static COUNT: TLCell<Marker, u32> = const { TLCell::new(0) };
+    /// }
+    /// let mut owner = TLCellOwner::<Marker>::new();
+    /// COUNT.with(|c| *owner.rw(c) += 1);
+    /// assert_eq!(COUNT.with(|c| *owner.ro(c)), 1);
+    /// ```
     #[inline]
     pub const fn new(value: T) -> TLCell<Q, T> {
         TLCell {
@@ -138,6 +756,234 @@ impl<Q, T> TLCell<Q, T> {
             value: UnsafeCell::new(value),
         }
     }
+
+    /// Consume the cell, returning its contents.  No owner is needed,
+    /// since owning the cell means that nothing else can access it.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Consume the cell, returning a cell with the same contents that
+    /// belongs to the owner for marker type `R` instead.  No owner is
+    /// needed, as for [`into_inner`](#method.into_inner).  To convert
+    /// to a different kind of cell, use `into_inner` and create the
+    /// new cell from the contents.
+    #[inline]
+    pub fn rebind<R>(self) -> TLCell<R, T> {
+        TLCell::new(self.into_inner())
+    }
+
+    /// Consume the cell, returning a new cell belonging to the same
+    /// owner that contains the result of passing the contents to the
+    /// given closure.  No owner is needed, as for
+    /// [`into_inner`](#method.into_inner).  If the closure panics,
+    /// the contents have already been passed to it, so they are
+    /// dropped during unwinding along with its other locals.
+    #[inline]
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> TLCell<Q, U> {
+        TLCell::new(f(self.into_inner()))
+    }
+
+    /// Consume the cell, passing the contents to the given fallible
+    /// closure, and return a new cell containing the result, or the
+    /// closure's error.  See [`map`](#method.map).
+    #[inline]
+    pub fn try_map<U, E>(self, f: impl FnOnce(T) -> Result<U, E>) -> Result<TLCell<Q, U>, E> {
+        f(self.into_inner()).map(TLCell::new)
+    }
+
+    /// Convert the cell into a `RefCell`, for example for passing to
+    /// code that hasn't yet been converted to use `TLCell`.  No owner
+    /// is needed, since the cell is consumed.
+    #[inline]
+    pub fn into_refcell(self) -> RefCell<T> {
+        RefCell::new(self.into_inner())
+    }
+
+    /// Convert the cell into a `Cell`.  No owner is needed, since the
+    /// cell is consumed.
+    #[inline]
+    pub fn into_std_cell(self) -> Cell<T> {
+        Cell::new(self.into_inner())
+    }
+
+    /// Create a new cell containing a clone of the contents of this
+    /// one, belonging to the same owner.  `TLCell` can't implement
+    /// `Clone`, since reading the contents needs the owner.
+    #[inline]
+    pub fn clone_with(&self, owner: &TLCellOwner<Q>) -> TLCell<Q, T>
+    where
+        Q: 'static,
+        T: Clone,
+    {
+        owner.clone_cell(self)
+    }
+
+    // Used by `cell_project!`.  The macro guarantees that `offset` is
+    // the offset of a field of type `F` directly within `T`, and
+    // `field` is only there to let the compiler infer `F`.
+    #[doc(hidden)]
+    #[inline]
+    pub unsafe fn __project<F>(
+        &self,
+        offset: usize,
+        _field: impl FnOnce(&T) -> *const F,
+    ) -> &TLCell<Q, F> {
+        debug_assert!(offset + core::mem::size_of::<F>() <= core::mem::size_of::<T>());
+        &*((self.value.get() as *const u8).add(offset) as *const TLCell<Q, F>)
+    }
+
+    /// View a mutable reference as a cell, so that code written
+    /// against `&TLCell<Q, T>` and an owner can also be used on data
+    /// borrowed from elsewhere.
+    #[inline]
+    pub fn from_mut(value: &mut T) -> &mut TLCell<Q, T> {
+        // Safe because `TLCell` is `repr(transparent)` over
+        // `UnsafeCell<T>`, which has the same layout as `T`, and the
+        // exclusive borrow of `value` is kept for the lifetime
+        unsafe { &mut *(value as *mut T as *mut TLCell<Q, T>) }
+    }
+
+    /// Create an array of cells from an array of values.  This is a
+    /// `const fn`, so can be used to initialise a `thread_local!`
+    /// table of cells.
+    #[inline]
+    pub const fn new_array<const N: usize>(values: [T; N]) -> [TLCell<Q, T>; N] {
+        let values = ManuallyDrop::new(values);
+        // Safe because `TLCell<Q, T>` has the same layout as `T`, so
+        // the arrays have the same layout, and `values` is not
+        // dropped
+        unsafe {
+            core::ptr::read(&values as *const ManuallyDrop<[T; N]> as *const [TLCell<Q, T>; N])
+        }
+    }
+
+    /// Convert a vector of values into a vector of cells, reusing
+    /// the same allocation.  No owner is needed, since the marker type
+    /// alone identifies the owner.
+    #[cfg(feature = "alloc")]
+    pub fn wrap_vec(values: Vec<T>) -> Vec<TLCell<Q, T>> {
+        let mut values = ManuallyDrop::new(values);
+        let (ptr, len, cap) = (values.as_mut_ptr(), values.len(), values.capacity());
+        // Safe because `TLCell<Q, T>` has the same layout as `T`, so
+        // the allocation is valid for the same number of cells, and
+        // `values` is not dropped
+        unsafe { Vec::from_raw_parts(ptr as *mut TLCell<Q, T>, len, cap) }
+    }
+}
+
+impl<Q, T: ?Sized> TLCell<Q, T> {
+    /// Borrow the contents mutably without an owner.  This is safe
+    /// because the `&mut` reference to the cell proves that nothing
+    /// else can currently access it.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// Get a raw pointer to the contents, as for `UnsafeCell::get`.
+    /// This is intended for FFI and for building other unsafe
+    /// abstractions on top of `TLCell`.  The same rules apply to
+    /// accesses through the pointer as for `TCell::as_ptr`, with the
+    /// current thread's `TLCellOwner` standing in for the
+    /// `TCellOwner`.
+    #[inline]
+    pub const fn as_ptr(&self) -> *mut T {
+        self.value.get()
+    }
+
+    // Get the byte range of the contents, for alias checks with
+    // `ranges_overlap`.  Cells projected with `cell_project!` may
+    // overlap without starting at the same address, so the whole
+    // range is compared.  For unsized contents only the data address
+    // and the size are used, not the pointer metadata.  Zero-sized
+    // contents are included, see `ranges_overlap`.
+    #[inline]
+    fn byte_range(&self) -> (usize, usize) {
+        (
+            self.value.get() as *const u8 as usize,
+            core::mem::size_of_val(self),
+        )
+    }
+}
+
+impl<Q, T> TLCell<Q, [T]> {
+    /// View a cell containing a slice as a slice of cells, one per
+    /// element, like `Cell::as_slice_of_cells`.  The elements can then
+    /// be borrowed individually, for example two different elements
+    /// with `rw2`.
+    #[inline]
+    pub fn as_slice_of_cells(&self) -> &[TLCell<Q, T>] {
+        // Safe because `TLCell<Q, T>` is `repr(transparent)` over `T`
+        // (the marker is zero-sized), so the element stride is the
+        // same and `TLCell<Q, [T]>` has the same layout as
+        // `[TLCell<Q, T>]`.  The element cells belong to the same
+        // owner, so access still requires the same owner borrow.
+        unsafe { &*(self as *const TLCell<Q, [T]> as *const [TLCell<Q, T>]) }
+    }
+
+    /// View a slice of cells as a single cell containing a slice.
+    /// This is the reverse of
+    /// [`as_slice_of_cells`](#method.as_slice_of_cells).
+    #[inline]
+    pub fn from_slice_of_cells(cells: &[TLCell<Q, T>]) -> &TLCell<Q, [T]> {
+        // Safe for the same reason as `as_slice_of_cells`
+        unsafe { &*(cells as *const [TLCell<Q, T>] as *const TLCell<Q, [T]>) }
+    }
+
+    /// Get the number of elements in the slice.  This doesn't need
+    /// the owner, since the length is not part of the contents.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.as_slice_of_cells().len()
+    }
+
+    /// Test whether the slice is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<Q, T: core::ops::CoerceUnsized<U>, U> core::ops::CoerceUnsized<TLCell<Q, U>> for TLCell<Q, T> {}
+
+impl<Q, T: Default> Default for TLCell<Q, T> {
+    fn default() -> Self {
+        TLCell::new(T::default())
+    }
+}
+
+impl<Q, T> From<T> for TLCell<Q, T> {
+    fn from(value: T) -> Self {
+        TLCell::new(value)
+    }
+}
+
+/// Convert a `RefCell` into a `TLCell`, to allow a structure to be
+/// migrated from `RefCell` a piece at a time.  Since `From<T>` also
+/// applies, the type of the contents must be known, for example
+/// `TLCell::<Q, u32>::from(refcell)`.
+impl<Q, T> From<RefCell<T>> for TLCell<Q, T> {
+    fn from(cell: RefCell<T>) -> Self {
+        TLCell::new(cell.into_inner())
+    }
+}
+
+/// Convert a `Cell` into a `TLCell`.
+impl<Q, T> From<Cell<T>> for TLCell<Q, T> {
+    fn from(cell: Cell<T>) -> Self {
+        TLCell::new(cell.into_inner())
+    }
+}
+
+/// The contents are not shown, since they can only be accessed
+/// through the owner.
+impl<Q, T: ?Sized> fmt::Debug for TLCell<Q, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TLCell").finish_non_exhaustive()
+    }
 }
 
 // TLCell absolutely cannot be Sync, since otherwise you could send
@@ -151,19 +997,286 @@ impl<Q, T> TLCell<Q, T> {
 // TLCellOwner can no longer give access to the TLCell's contents since
 // TLCellOwner is !Send + !Sync. Only the TLCellOwner of the new thread
 // can give access to this TLCell's contents now.
-unsafe impl<Q, T: Send> Send for TLCell<Q, T> {}
+unsafe impl<Q, T: Send + ?Sized> Send for TLCell<Q, T> {}
 
-#[cfg(test)]
-mod tests {
-    use super::{TLCell, TLCellOwner};
+// Same as for `TCell` and `TCellOwner`: the owner has no state that
+// a panic could leave half-updated, and the cell follows `RefCell`.
+impl<Q: 'static> UnwindSafe for TLCellOwner<Q> {}
+impl<Q: 'static> RefUnwindSafe for TLCellOwner<Q> {}
+impl<Q, T: UnwindSafe + ?Sized> UnwindSafe for TLCell<Q, T> {}
 
-    #[test]
-    #[should_panic]
-    fn tlcell_singleton_1() {
-        struct Marker;
-        let _owner1 = TLCellOwner::<Marker>::new();
-        let _owner2 = TLCellOwner::<Marker>::new(); // Panic here
-    }
+impl<Q: 'static> GuardedCellReader for TLCellOwner<Q> {
+    type Cell<T> = TLCell<Q, T>;
+    type Ro<'a, T: 'a> = &'a T;
+
+    #[inline]
+    fn ro_guard<'a, T>(&'a self, cell: &'a TLCell<Q, T>) -> &'a T {
+        self.ro(cell)
+    }
+}
+
+impl<Q: 'static> GuardedCellOwner for TLCellOwner<Q> {
+    type Rw<'a, T: 'a> = &'a mut T;
+
+    #[inline]
+    fn rw_guard<'a, T>(&'a mut self, cell: &'a TLCell<Q, T>) -> &'a mut T {
+        self.rw(cell)
+    }
+
+    #[inline]
+    fn rw2_guard<'a, T, U>(
+        &'a mut self,
+        cell1: &'a TLCell<Q, T>,
+        cell2: &'a TLCell<Q, U>,
+    ) -> (&'a mut T, &'a mut U) {
+        self.rw2(cell1, cell2)
+    }
+}
+
+#[cfg(feature = "alloc")]
+crate::traits::rc_cell_ext!([Q: 'static, T: ?Sized] TLCell<Q, T>, TLCellOwner<Q>, T);
+
+impl<Q, T> Sealed for TLCell<Q, Option<T>> {}
+
+impl<Q: 'static, T> OptionCellExt<T> for TLCell<Q, Option<T>> {
+    type Owner = TLCellOwner<Q>;
+
+    #[inline]
+    fn ro_option<'a>(&'a self, owner: &'a TLCellOwner<Q>) -> &'a Option<T> {
+        owner.ro(self)
+    }
+
+    #[inline]
+    fn rw_option<'a>(&'a self, owner: &'a mut TLCellOwner<Q>) -> &'a mut Option<T> {
+        owner.rw(self)
+    }
+}
+
+/// Per-thread flag used by [`TLCellOwner::new_fast`].  A thread-local
+/// instance of this is created for each marker type defined with
+/// [`tlcell_marker!`].
+///
+/// [`TLCellOwner::new_fast`]: struct.TLCellOwner.html#method.new_fast
+/// [`tlcell_marker!`]: macro.tlcell_marker.html
+#[cfg(feature = "std")]
+pub struct TLCellMarkerFlag {
+    state: Cell<u8>,
+}
+
+#[cfg(feature = "std")]
+impl TLCellMarkerFlag {
+    /// Create a new flag, which initially has no owner.
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            state: Cell::new(FLAG_UNLINKED),
+        }
+    }
+}
+
+/// Marker type with its own thread-local flag, allowing owners to be
+/// created with [`TLCellOwner::new_fast`].  Use [`tlcell_marker!`] to
+/// define marker types that implement this trait.
+///
+/// This trait is public API and may also be implemented by hand.  As
+/// for [`TCellMarker`], the implementation only provides storage for
+/// the flag, and all claiming and releasing is done by `TLCellOwner`.
+///
+/// # Safety
+///
+/// The implementation must ensure that:
+///
+/// - `flag` always returns the same `LocalKey` for a given marker
+///   type, and the key is declared with `thread_local!`, so that each
+///   thread has exactly one flag for the marker type.
+///
+/// - That key is never returned by `flag` for any other marker type.
+///
+/// - `flag` does not panic and does not create owners itself.
+///
+/// Otherwise two owners could exist in the same thread for the same
+/// marker type.
+///
+/// [`TCellMarker`]: trait.TCellMarker.html
+///
+/// [`TLCellOwner::new_fast`]: struct.TLCellOwner.html#method.new_fast
+/// [`tlcell_marker!`]: macro.tlcell_marker.html
+#[cfg(feature = "std")]
+pub unsafe trait TLCellMarker: 'static {
+    /// Get the thread-local flag belonging to this marker type.
+    fn flag() -> &'static LocalKey<TLCellMarkerFlag>;
+}
+
+/// Define a marker type that implements [`TLCellMarker`], so that
+/// owners for it can be created with [`TLCellOwner::new_fast`].
+///
+/// ```
+///# use qcell::{tlcell_marker, TLCell, TLCellOwner};
+/// tlcell_marker!(pub struct Marker;);
+/// let mut owner = TLCellOwner::<Marker>::new_fast();
+/// let cell = TLCell::<Marker, u32>::new(1);
+/// *owner.rw(&cell) += 1;
+/// assert_eq!(*owner.ro(&cell), 2);
+/// ```
+///
+/// [`TLCellMarker`]: trait.TLCellMarker.html
+/// [`TLCellOwner::new_fast`]: struct.TLCellOwner.html#method.new_fast
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! tlcell_marker {
+    ($(#[$attr:meta])* $vis:vis struct $name:ident;) => {
+        $(#[$attr])*
+        $vis struct $name;
+
+        unsafe impl $crate::TLCellMarker for $name {
+            #[inline]
+            fn flag() -> &'static ::std::thread::LocalKey<$crate::TLCellMarkerFlag> {
+                ::std::thread_local! {
+                    static FLAG: $crate::TLCellMarkerFlag = const { $crate::TLCellMarkerFlag::new() };
+                }
+                &FLAG
+            }
+        }
+    };
+}
+
+/// Declare a set of `TLCell` instances held in a `thread_local!`
+/// static, along with an owner for them that is created lazily in
+/// each thread on first use.  This expands to a struct with the given
+/// name holding the cells as public fields, and a value with the
+/// given static name whose `with` call passes the current thread's
+/// owner and cells to a closure:
+///
+/// ```
+///# use qcell::tl_static_cells;
+/// struct Marker;
+/// tl_static_cells! {
+///     static STATE: State<Marker> {
+///         count: u32 = 0,
+///         log: Vec<String> = Vec::new(),
+///     }
+/// }
+///
+/// STATE.with(|owner, cells| {
+///     *owner.rw(&cells.count) += 1;
+///     owner.rw(&cells.log).push(String::from("started"));
+/// });
+/// std::thread::spawn(|| {
+///     // Each thread has its own cells and owner
+///     STATE.with(|owner, cells| assert_eq!(*owner.ro(&cells.count), 0));
+/// }).join().unwrap();
+/// STATE.with(|owner, cells| assert_eq!(*owner.ro(&cells.count), 1));
+/// ```
+///
+/// The initial values must be constant expressions, since the cells
+/// are created with the `const` constructor.  The owner is created
+/// with [`TLCellOwner::new`], so this panics if another owner for the
+/// marker type already exists in the thread.  For that reason each
+/// invocation should have a marker type of its own.  The owner is
+/// mutably borrowed for the duration of the closure, so calling
+/// `with` again from within the closure panics.
+///
+/// [`TLCellOwner::new`]: struct.TLCellOwner.html#method.new
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! tl_static_cells {
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $cells:ident<$marker:ty> {
+        $($(#[$fattr:meta])* $field:ident: $ty:ty = $init:expr),* $(,)?
+    }) => {
+        $vis struct $cells {
+            $($(#[$fattr])* pub $field: $crate::TLCell<$marker, $ty>,)*
+        }
+
+        $(#[$attr])*
+        #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+        #[derive(Clone, Copy)]
+        $vis struct $name;
+
+        impl $name {
+            /// Pass the owner and cells for the current thread to the
+            /// given closure, creating the owner if this is the first
+            /// call in this thread.
+            #[allow(dead_code)]
+            $vis fn with<R>(
+                self,
+                f: impl FnOnce(&mut $crate::TLCellOwner<$marker>, &$cells) -> R,
+            ) -> R {
+                ::std::thread_local! {
+                    static CELLS: $crate::__TLStaticCells<$marker, $cells> = const {
+                        $crate::__TLStaticCells::new($cells {
+                            $($field: $crate::TLCell::new($init),)*
+                        })
+                    };
+                }
+                CELLS.with(|cells| cells.with(stringify!($name), f))
+            }
+        }
+    };
+}
+
+// Used by `tl_static_cells!`: the cells for one thread, plus the owner
+// for them, which is created on first use
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub struct __TLStaticCells<Q: 'static, C> {
+    owner: RefCell<Option<TLCellOwner<Q>>>,
+    cells: C,
+}
+
+#[cfg(feature = "std")]
+impl<Q: 'static, C> __TLStaticCells<Q, C> {
+    #[inline]
+    pub const fn new(cells: C) -> Self {
+        Self {
+            owner: RefCell::new(None),
+            cells,
+        }
+    }
+
+    #[track_caller]
+    pub fn with<R>(&self, name: &str, f: impl FnOnce(&mut TLCellOwner<Q>, &C) -> R) -> R {
+        let mut owner = match self.owner.try_borrow_mut() {
+            Ok(owner) => owner,
+            Err(_) => panic!(
+                "Illegal re-entrant call to with() on thread-local cells `{}`",
+                name
+            ),
+        };
+        f(owner.get_or_insert_with(TLCellOwner::new), &self.cells)
+    }
+}
+
+/// Tuple of `TLCell` references that can be borrowed all at once
+/// with [`TLCellOwner::rw_n`].  This is implemented for tuples of
+/// between 1 and 8 `&TLCell<Q, _>` references, and cannot be
+/// implemented outside this crate.
+///
+/// [`TLCellOwner::rw_n`]: struct.TLCellOwner.html#method.rw_n
+pub trait TupleOfTLCells<'a, Q>: Sealed {
+    /// Tuple of mutable references to the cell contents
+    type Output;
+
+    #[doc(hidden)]
+    fn find_alias(&self) -> Option<(usize, usize)>;
+
+    #[doc(hidden)]
+    unsafe fn rw_unchecked(self) -> Self::Output;
+}
+
+crate::tcell::tuple_of_cells!(TupleOfTLCells, TLCell);
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::{TLCell, TLCellOwner};
+    use crate::cell_project;
+
+    #[test]
+    #[should_panic]
+    fn tlcell_singleton_1() {
+        struct Marker;
+        let _owner1 = TLCellOwner::<Marker>::new();
+        let _owner2 = TLCellOwner::<Marker>::new(); // Panic here
+    }
 
     #[test]
     fn tlcell_singleton_2() {
@@ -181,6 +1294,375 @@ mod tests {
         let _owner2 = TLCellOwner::<Marker2>::new();
     }
 
+    #[test]
+    fn tlcell_try_new() {
+        struct Marker;
+        let owner1 = TLCellOwner::<Marker>::try_new().unwrap();
+        let err = TLCellOwner::<Marker>::try_new().err().unwrap();
+        assert!(err.marker().ends_with("Marker"));
+        assert!(err.to_string().contains("TLCellOwner"));
+        assert!(err.to_string().contains(err.marker()));
+        // The failed call must not have taken or released the slot
+        assert!(TLCellOwner::<Marker>::is_active());
+        drop(owner1);
+        assert!(!TLCellOwner::<Marker>::is_active());
+        let _owner2 = TLCellOwner::<Marker>::try_new().unwrap();
+    }
+
+    #[test]
+    fn tlcell_scope() {
+        struct Marker1;
+        struct Marker2;
+        let total = TLCellOwner::<Marker1>::scope(|owner1| {
+            let c1 = owner1.cell(100u32);
+            assert!(TLCellOwner::<Marker1>::try_scope(|_| ()).is_err());
+            TLCellOwner::<Marker2>::scope(|owner2| {
+                let c2 = owner2.cell(200u32);
+                *owner1.rw(&c1) += 1;
+                *owner2.rw(&c2) += 2;
+                owner1.ro(&c1) + owner2.ro(&c2)
+            })
+        });
+        assert_eq!(total, 303);
+        assert!(!TLCellOwner::<Marker1>::is_active());
+        assert_eq!(TLCellOwner::<Marker1>::try_scope(|_| 1), Ok(1));
+    }
+
+    #[test]
+    fn tlcell_scope_panic() {
+        struct Marker;
+        let result = std::panic::catch_unwind(|| {
+            TLCellOwner::<Marker>::scope(|_| panic!("Panic in scope"));
+        });
+        assert!(result.is_err());
+        // The owner was dropped during unwinding
+        assert!(!TLCellOwner::<Marker>::is_active());
+        let _owner = TLCellOwner::<Marker>::new();
+    }
+
+    #[test]
+    fn tlcell_scope_async() {
+        use futures::task::noop_waker_ref;
+        use std::future::Future;
+        use std::pin::Pin;
+        use std::task::{Context, Poll};
+        struct Marker;
+        // Returns `Pending` once, to let the task move between threads
+        struct YieldOnce(bool);
+        impl Future for YieldOnce {
+            type Output = ();
+            fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+                if self.0 {
+                    Poll::Ready(())
+                } else {
+                    self.0 = true;
+                    Poll::Pending
+                }
+            }
+        }
+        type Task = Pin<Box<dyn Future<Output = u32> + Send>>;
+        fn task(start: u32) -> Task {
+            Box::pin(async move {
+                let cell = TLCell::<Marker, u32>::new(start);
+                TLCellOwner::<Marker>::scope(|owner| *owner.rw(&cell) += 1);
+                YieldOnce(false).await;
+                TLCellOwner::<Marker>::scope(|owner| *owner.rw(&cell) += 1);
+                cell.into_inner()
+            })
+        }
+        // Start both tasks in one thread, then finish each in a thread
+        // of its own, both using the same marker type concurrently
+        let mut tasks = vec![task(100), task(200)];
+        let mut cx = Context::from_waker(noop_waker_ref());
+        for t in &mut tasks {
+            assert!(t.as_mut().poll(&mut cx).is_pending());
+        }
+        let handles: Vec<_> = tasks
+            .into_iter()
+            .map(|t| std::thread::spawn(move || futures::executor::block_on(t)))
+            .collect();
+        let results: Vec<u32> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert_eq!(results, vec![102, 202]);
+    }
+
+    #[test]
+    fn tlcell_new_fast() {
+        crate::tlcell_marker!(
+            struct Marker;
+        );
+        let mut owner = TLCellOwner::<Marker>::new_fast();
+        let c1 = TLCell::<Marker, _>::new(100u32);
+        *owner.rw(&c1) += 1;
+        assert!(TLCellOwner::<Marker>::try_new_fast().is_err());
+        drop(owner);
+        let owner = TLCellOwner::<Marker>::new_fast();
+        assert_eq!(*owner.ro(&c1), 101);
+        // Other threads have flags of their own
+        std::thread::spawn(|| {
+            let _owner = TLCellOwner::<Marker>::new_fast();
+            assert!(TLCellOwner::<Marker>::try_new_fast().is_err());
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Illegal to create two TLCellOwner instances")]
+    fn tlcell_new_fast_duplicate() {
+        crate::tlcell_marker!(
+            struct Marker;
+        );
+        let _owner1 = TLCellOwner::<Marker>::new_fast();
+        let _owner2 = TLCellOwner::<Marker>::new_fast(); // Panic here
+    }
+
+    #[test]
+    fn tlcell_new_fast_mixed() {
+        crate::tlcell_marker!(
+            struct Marker;
+        );
+        // Not yet used with the flag in this thread, so a set-based
+        // owner blocks the first fast owner
+        let owner = TLCellOwner::<Marker>::new();
+        assert!(TLCellOwner::<Marker>::try_new_fast().is_err());
+        drop(owner);
+        // Once the flag has been used, the marker type stays reserved
+        // in the set, so set-based owners are always refused
+        drop(TLCellOwner::<Marker>::new_fast());
+        assert!(TLCellOwner::<Marker>::try_new().is_err());
+        let _owner = TLCellOwner::<Marker>::new_fast();
+        assert!(TLCellOwner::<Marker>::try_new().is_err());
+    }
+
+    #[test]
+    fn tlcell_new_unchecked() {
+        struct Marker;
+        let mut owner = unsafe { TLCellOwner::<Marker>::new_unchecked() };
+        let c1 = owner.cell(100u32);
+        *owner.rw(&c1) += 1;
+        assert_eq!(*owner.ro(&c1), 101);
+        assert!(!TLCellOwner::<Marker>::is_active());
+        drop(owner);
+        let _owner = TLCellOwner::<Marker>::new();
+    }
+
+    #[test]
+    fn tlcell_new_unchecked_registry() {
+        struct Marker1;
+        struct Marker2;
+        let owner1 = TLCellOwner::<Marker1>::new();
+        // Dropping an unchecked owner must not change the set.  This
+        // is sound since no other `Marker2` owner exists.
+        drop(unsafe { TLCellOwner::<Marker2>::new_unchecked() });
+        assert!(TLCellOwner::<Marker1>::is_active());
+        assert!(!TLCellOwner::<Marker2>::is_active());
+        let _owner2 = TLCellOwner::<Marker2>::new();
+        drop(owner1);
+        assert!(!TLCellOwner::<Marker1>::is_active());
+    }
+
+    #[test]
+    fn tlcell_is_active() {
+        struct Marker;
+        assert!(!TLCellOwner::<Marker>::is_active());
+        let owner = TLCellOwner::<Marker>::new();
+        assert!(TLCellOwner::<Marker>::is_active());
+        std::thread::spawn(|| {
+            assert!(!TLCellOwner::<Marker>::is_active());
+            let _owner = TLCellOwner::<Marker>::new();
+            assert!(TLCellOwner::<Marker>::is_active());
+        })
+        .join()
+        .unwrap();
+        assert!(TLCellOwner::<Marker>::is_active());
+        drop(owner);
+        assert!(!TLCellOwner::<Marker>::is_active());
+    }
+
+    #[test]
+    fn tlcell_many_markers() {
+        struct Marker<const N: usize>;
+        macro_rules! owners {
+            ($($n:literal)*) => {
+                vec![$(Box::new(TLCellOwner::<Marker<$n>>::new()) as Box<dyn std::any::Any>),*]
+            };
+        }
+        let mut owners = owners!(
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19
+            20 21 22 23 24 25 26 27 28 29 30 31 32 33 34 35 36 37 38 39
+        );
+        assert!(TLCellOwner::<Marker<0>>::is_active());
+        assert!(TLCellOwner::<Marker<39>>::is_active());
+        // Free some inline and some spilled entries
+        owners.drain(5..15);
+        assert!(owners[5].is::<TLCellOwner<Marker<15>>>());
+        assert!(!TLCellOwner::<Marker<5>>::is_active());
+        assert!(!TLCellOwner::<Marker<14>>::is_active());
+        assert!(TLCellOwner::<Marker<15>>::is_active());
+        let _owner5 = TLCellOwner::<Marker<5>>::new();
+        let _owner14 = TLCellOwner::<Marker<14>>::new();
+        drop(owners);
+        assert!(!TLCellOwner::<Marker<0>>::is_active());
+        assert!(!TLCellOwner::<Marker<39>>::is_active());
+        assert!(TLCellOwner::<Marker<5>>::is_active());
+        let _owner39 = TLCellOwner::<Marker<39>>::new();
+    }
+
+    #[test]
+    fn tlcell_many_markers_threads() {
+        struct Marker<const N: usize>;
+        macro_rules! owners {
+            ($($n:literal)*) => {
+                vec![$(Box::new(TLCellOwner::<Marker<$n>>::new()) as Box<dyn std::any::Any>),*]
+            };
+        }
+        macro_rules! spawn_owners {
+            () => {
+                std::thread::spawn(|| {
+                    // No other thread's owners are visible here
+                    assert!(!TLCellOwner::<Marker<63>>::is_active());
+                    let owners = owners!(
+                        0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+                        16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+                        32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+                        48 49 50 51 52 53 54 55 56 57 58 59 60 61 62 63
+                    );
+                    assert!(TLCellOwner::<Marker<63>>::try_new().is_err());
+                    drop(owners);
+                    assert!(!TLCellOwner::<Marker<63>>::is_active());
+                })
+            };
+        }
+        // Several threads each fill their own set well past the inline
+        // storage at the same time, whilst this thread holds some too
+        let _ours = owners!(0 10 20 63);
+        let threads = vec![spawn_owners!(), spawn_owners!(), spawn_owners!()];
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert!(TLCellOwner::<Marker<63>>::is_active());
+        assert!(!TLCellOwner::<Marker<62>>::is_active());
+    }
+
+    #[test]
+    #[should_panic]
+    fn tlcell_many_markers_duplicate() {
+        struct Marker<const N: usize>;
+        let _owners = (
+            TLCellOwner::<Marker<0>>::new(),
+            TLCellOwner::<Marker<1>>::new(),
+            TLCellOwner::<Marker<2>>::new(),
+            TLCellOwner::<Marker<3>>::new(),
+            TLCellOwner::<Marker<4>>::new(),
+            TLCellOwner::<Marker<5>>::new(),
+            TLCellOwner::<Marker<6>>::new(),
+            TLCellOwner::<Marker<7>>::new(),
+            TLCellOwner::<Marker<8>>::new(),
+            TLCellOwner::<Marker<9>>::new(),
+        );
+        let _owner2 = TLCellOwner::<Marker<9>>::new(); // Panic here
+    }
+
+    #[test]
+    fn tlcell_owner_in_thread_local() {
+        struct Marker;
+        std::thread_local! {
+            static OWNER: TLCellOwner<Marker> = TLCellOwner::new();
+        }
+        std::thread::spawn(|| {
+            let cell = TLCell::new(1u32);
+            OWNER.with(|owner| assert_eq!(*owner.ro(&cell), 1));
+            assert!(TLCellOwner::<Marker>::is_active());
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn tlcell_force_release() {
+        struct Marker;
+        std::mem::forget(TLCellOwner::<Marker>::new());
+        assert!(TLCellOwner::<Marker>::is_active());
+        unsafe { TLCellOwner::<Marker>::force_release() };
+        let owner = TLCellOwner::<Marker>::new();
+        drop(owner);
+        let _owner = TLCellOwner::<Marker>::new();
+    }
+
+    #[test]
+    fn tlcell_get_mut() {
+        struct Marker;
+        let mut c1 = TLCell::<Marker, _>::new(vec![1u8]);
+        c1.get_mut().push(2);
+        let owner = TLCellOwner::<Marker>::new();
+        assert_eq!(owner.ro(&c1), &[1, 2]);
+    }
+
+    #[test]
+    fn tlcell_into_inner() {
+        struct Marker;
+        let cell = std::rc::Rc::new(TLCell::<Marker, _>::new(vec![1u8]));
+        let value = std::rc::Rc::try_unwrap(cell).ok().unwrap().into_inner();
+        assert_eq!(value, [1]);
+    }
+
+    #[test]
+    fn tlcell_from_mut() {
+        struct Marker;
+        let mut owner = TLCellOwner::<Marker>::new();
+        let mut value = 10u32;
+        *owner.rw(TLCell::from_mut(&mut value)) += 1;
+        assert_eq!(value, 11);
+    }
+
+    #[test]
+    fn tlcell_default_from() {
+        struct Marker;
+        #[derive(Default)]
+        struct Node {
+            name: TLCell<Marker, String>,
+            count: TLCell<Marker, u32>,
+        }
+        let owner = TLCellOwner::<Marker>::new();
+        let node = Node::default();
+        assert!(owner.ro(&node.name).is_empty());
+        assert_eq!(*owner.ro(&node.count), 0);
+        let cell: TLCell<Marker, u32> = 5.into();
+        assert_eq!(*owner.ro(&cell), 5);
+    }
+
+    #[test]
+    fn tlcell_debug_display() {
+        struct Marker;
+        let owner = TLCellOwner::<Marker>::new();
+        let c1 = TLCell::new(vec![1u8, 2]);
+        let c2 = TLCell::new(String::from("text"));
+        assert_eq!(format!("{:?}", owner.debug(&c1)), "[1, 2]");
+        assert_eq!(format!("{}", owner.display(&c2)), "text");
+        assert_eq!(format!("{:?}", owner.debug(&c2)), "\"text\"");
+        // Nested in other output, with formatting options passed on
+        let nested = vec![owner.debug(&c1), owner.debug(&c1)];
+        assert_eq!(format!("{:?}", nested), "[[1, 2], [1, 2]]");
+        let value = TLCell::new(1.23456f32);
+        assert_eq!(
+            format!("{}", format_args!("<{:.2}>", owner.display(&value))),
+            "<1.23>"
+        );
+    }
+
+    #[test]
+    fn tlcell_clone_with() {
+        struct Marker;
+        let mut owner = TLCellOwner::<Marker>::new();
+        let original = std::rc::Rc::new(TLCell::new(String::from("a")));
+        let copy = std::rc::Rc::new(original.clone_with(&owner));
+        owner.rw(&copy).push('b');
+        let r = owner.ro(&original);
+        let copy2 = owner.clone_cell(&copy);
+        assert_eq!(r, "a");
+        assert_eq!(owner.ro(&copy2), "ab");
+    }
+
     #[test]
     fn tlcell() {
         struct Marker;
@@ -197,6 +1679,531 @@ mod tests {
         assert_eq!(total, 303);
     }
 
+    #[test]
+    fn tlcell_unwind_safe() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+        struct Marker;
+        let mut owner = TLCellOwner::<Marker>::new();
+        let cell = TLCell::<Marker, _>::new(vec![1]);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            owner.rw(&cell).push(2);
+            panic!("half-way through update");
+        }));
+        assert!(result.is_err());
+        assert_eq!(owner.ro(&cell), &[1, 2]);
+    }
+
+    #[test]
+    fn tlcell_option_ext() {
+        use crate::OptionCellExt;
+        struct Marker;
+        let mut owner = TLCellOwner::<Marker>::new();
+        let cell = TLCell::<Marker, Option<String>>::new(None);
+        assert!(!cell.is_some(&owner));
+        cell.get_or_insert_with(&mut owner, String::new).push('a');
+        assert_eq!(cell.as_ref(&owner).map(String::as_str), Some("a"));
+        assert_eq!(cell.replace_inner(&mut owner, "b".into()).unwrap(), "a");
+        assert_eq!(cell.take_inner(&mut owner).unwrap(), "b");
+        assert!(cell.as_ref(&owner).is_none());
+    }
+
+    #[test]
+    fn tlcell_std_cells() {
+        use std::cell::{Cell, RefCell};
+        struct Marker;
+        let mut owner = TLCellOwner::<Marker>::new();
+        let cell: TLCell<Marker, String> = RefCell::new(String::from("a")).into();
+        owner.rw(&cell).push('b');
+        let refcell = cell.into_refcell();
+        assert_eq!(*refcell.borrow(), "ab");
+        let cell: TLCell<Marker, u8> = Cell::new(1).into();
+        *owner.rw(&cell) += 1;
+        assert_eq!(cell.into_std_cell().get(), 2);
+    }
+
+    #[test]
+    fn tlcell_clone_move_into() {
+        struct Marker;
+        let mut owner = TLCellOwner::<Marker>::new();
+        let a = TLCell::<Marker, _>::new(String::from("a"));
+        let b = TLCell::<Marker, _>::new(String::new());
+        owner.clone_into(&a, &b);
+        assert_eq!(owner.ro(&b), "a");
+        owner.clone_into(&a, &a);
+        owner.move_into(&a, &a);
+        assert_eq!(owner.ro(&a), "a");
+        owner.rw(&b).push('b');
+        owner.move_into(&b, &a);
+        assert_eq!((owner.ro(&a).as_str(), owner.ro(&b).as_str()), ("ab", ""));
+    }
+
+    #[test]
+    fn tlcell_rw_n() {
+        struct Marker;
+        let mut owner = TLCellOwner::<Marker>::new();
+        let c1 = TLCell::new(1u32);
+        let c2 = TLCell::new(String::from("2"));
+        let c3: Box<TLCell<Marker, [u8]>> = Box::new(TLCell::new([3u8, 30]));
+        let c4: Box<TLCell<Marker, dyn std::fmt::Write>> = Box::new(TLCell::new(String::new()));
+        let c5 = TLCell::new(5i16);
+        let (r1, r2, r3, r4, r5) = owner.rw_n((&c1, &c2, &*c3, &*c4, &c5));
+        *r1 += 10;
+        r2.push('0');
+        r3[1] += 1;
+        r4.write_str("4").unwrap();
+        *r5 += 50;
+        assert_eq!(*owner.ro(&c1), 11);
+        assert_eq!(owner.ro(&c2), "20");
+        assert_eq!(owner.ro(&*c3), [3, 31]);
+        assert_eq!(*owner.ro(&c5), 55);
+        let (r1,) = owner.rw_n((&c1,));
+        *r1 += 1;
+        assert_eq!(*owner.ro(&c1), 12);
+    }
+
+    #[test]
+    fn tlcell_try_rw_n_alias() {
+        struct Marker;
+        let mut owner = TLCellOwner::<Marker>::new();
+        let c1 = TLCell::new(1u32);
+        let c2: Box<TLCell<Marker, [u32]>> = Box::new(TLCell::new([2, 3]));
+        let c3: &TLCell<Marker, dyn std::any::Any> = &c1;
+        let err = owner.try_rw_n((&*c2, &c1, c3)).err().unwrap();
+        assert_eq!(err.arguments(), (2, 3));
+        // Zero-length slices are still checked
+        let empty: Box<TLCell<Marker, [u32]>> = Box::new(TLCell::new([]));
+        assert!(owner.try_rw_n((&*empty, &*empty, &c1)).is_err());
+    }
+
+    #[test]
+    fn tlcell_try_rw_n_alias_positions() {
+        struct Marker;
+        let mut owner = TLCellOwner::<Marker>::new();
+        let c1 = TLCell::new(1u32);
+        let c2: Box<TLCell<Marker, [u8]>> = Box::new(TLCell::new([2, 20]));
+        let c3: Box<TLCell<Marker, dyn std::any::Any>> = Box::new(TLCell::new(3u64));
+        let c4 = TLCell::new(String::from("4"));
+        let c5 = TLCell::new(());
+        let c6 = TLCell::new(6i8);
+        {
+            let (r1, r2, r3, r4, r5, r6) = owner.rw_n((&c1, &*c2, &*c3, &c4, &c5, &c6));
+            *r1 += 10;
+            r2[0] += 10;
+            *r3.downcast_mut::<u64>().unwrap() += 10;
+            r4.push('0');
+            *r5 = ();
+            *r6 += 10;
+        }
+        assert_eq!(*owner.ro(&c1), 11);
+        assert_eq!(owner.ro(&*c2), [12, 20]);
+        assert_eq!(owner.ro(&c4), "40");
+        // Put a duplicate of the first cell at each later position
+        macro_rules! check_at {
+            ($pos:expr, $($c:expr),*) => {
+                let err = owner.try_rw_n(($($c,)*)).err().unwrap();
+                assert_eq!(err.arguments(), (1, $pos));
+            };
+        }
+        check_at!(2, &c1, &c1, &*c3, &c4, &c5, &c6);
+        check_at!(3, &c1, &*c2, &c1, &c4, &c5, &c6);
+        check_at!(4, &c1, &*c2, &*c3, &c1, &c5, &c6);
+        check_at!(5, &c1, &*c2, &*c3, &c4, &c1, &c6);
+        check_at!(6, &c1, &*c2, &*c3, &c4, &c5, &c1);
+        // Zero-sized contents are checked too
+        assert!(owner.try_rw_n((&c5, &c5, &c1)).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "arguments 1 and 3 are the same")]
+    fn tlcell_rw_n_alias() {
+        struct Marker;
+        let mut owner = TLCellOwner::<Marker>::new();
+        let c1 = TLCell::new(1u32);
+        let c2 = TLCell::new(2u32);
+        let c3 = TLCell::new(3u32);
+        let c4 = TLCell::new(4u32);
+        let _ = owner.rw_n((&c1, &c2, &c1, &c3, &c4));
+    }
+
+    #[test]
+    fn tlcell_static_cells() {
+        struct Marker;
+        crate::tl_static_cells! {
+            static STATE: State<Marker> {
+                count: u32 = 0,
+                name: String = String::new(),
+            }
+        }
+        let bump = |by| {
+            STATE.with(|owner, cells| {
+                let (count, name) = owner.rw2(&cells.count, &cells.name);
+                *count += by;
+                name.push('x');
+                (*count, owner as *const TLCellOwner<Marker>)
+            })
+        };
+        assert!(!TLCellOwner::<Marker>::is_active());
+        let (count, owner1) = bump(1);
+        assert_eq!(count, 1);
+        assert!(TLCellOwner::<Marker>::is_active());
+        let (count, owner2) = bump(2);
+        assert_eq!(count, 3);
+        // The same owner is reused within the thread
+        assert_eq!(owner1, owner2);
+        let other = std::thread::spawn(move || {
+            assert!(!TLCellOwner::<Marker>::is_active());
+            let first = bump(10).0;
+            let second = bump(10).0;
+            (
+                first,
+                second,
+                STATE.with(|owner, cells| owner.ro(&cells.name).clone()),
+            )
+        });
+        assert_eq!(other.join().unwrap(), (10, 20, String::from("xx")));
+        STATE.with(|owner, cells| {
+            assert_eq!(*owner.ro(&cells.count), 3);
+            assert_eq!(owner.ro(&cells.name), "xx");
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Illegal re-entrant call to with() on thread-local cells `STATE`")]
+    fn tlcell_static_cells_reentrant() {
+        struct Marker;
+        crate::tl_static_cells! {
+            static STATE: State<Marker> {
+                count: u32 = 0,
+            }
+        }
+        STATE.with(|owner, cells| {
+            *owner.rw(&cells.count) += 1;
+            STATE.with(|_, _| ());
+        });
+    }
+
+    #[test]
+    fn tlcell_const_new() {
+        struct Marker;
+        #[allow(clippy::declare_interior_mutable_const)]
+        const CELL: TLCell<Marker, u32> = TLCell::new(100);
+        let mut owner = TLCellOwner::<Marker>::new();
+        let c1 = CELL;
+        let c2 = CELL;
+        *owner.rw(&c1) += 1;
+        assert_eq!(*owner.ro(&c1), 101);
+        assert_eq!(*owner.ro(&c2), 100);
+    }
+
+    #[test]
+    fn tlcell_get_set() {
+        struct Marker;
+        let mut owner = TLCellOwner::<Marker>::new();
+        let c1 = std::rc::Rc::new(TLCell::<Marker, _>::new(1u32));
+        let c2 = TLCell::<Marker, _>::new(String::from("a"));
+        let c3 = TLCell::<Marker, _>::new(String::from("b"));
+        assert_eq!(owner.get(&c1), 1);
+        owner.set(&c1, 2);
+        assert_eq!(owner.replace(&c1, 3), 2);
+        assert_eq!(owner.take(&*c1), 3);
+        assert_eq!(owner.get(&c1), 0);
+        assert_eq!(owner.cloned(&c2), "a");
+        owner.swap(&c2, &c3);
+        assert_eq!((owner.ro(&c2).as_str(), owner.ro(&c3).as_str()), ("b", "a"));
+        owner.swap(&c2, &c2);
+        assert_eq!(owner.ro(&c2), "b");
+    }
+
+    #[test]
+    fn tlcell_try_ro_rw() {
+        struct Marker;
+        std::thread_local! {
+            static OWNER: TLCellOwner<Marker> = TLCellOwner::new();
+        }
+        // Shared access to a thread-local owner is enough for `try_ro`
+        let cell = TLCell::<Marker, _>::new(2u32);
+        let Ok(value) = OWNER.with(|owner| owner.try_ro(&cell).copied());
+        assert_eq!(value, 2);
+        // Another thread has its own owner for `try_rw`.  The error
+        // type is uninhabited, so it can be matched away.
+        std::thread::spawn(|| {
+            let mut owner = TLCellOwner::<Marker>::new();
+            let cell = TLCell::<Marker, _>::new(1u32);
+            *owner.try_rw(&cell).unwrap_or_else(|e| match e {}) += 1;
+            assert_eq!(owner.get(&cell), 2);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn tlcell_read_update() {
+        // Each thread has its own owner for the marker type, so
+        // `update` and `read` can run in several threads at once
+        struct Marker;
+        let handles: Vec<_> = (0..4u32)
+            .map(|n| {
+                std::thread::spawn(move || {
+                    let mut owner = TLCellOwner::<Marker>::new();
+                    let c1 = TLCell::new(n);
+                    let c2 = TLCell::new(10u32);
+                    let old = owner.update(&c1, |v| std::mem::replace(v, n + 1));
+                    assert_eq!(old, n);
+                    owner.update2(&c1, &c2, |a, b| *b += *a);
+                    owner.read(&c1, |v1| owner.read(&c2, |v2| v1 + v2))
+                })
+            })
+            .collect();
+        let totals: Vec<u32> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert_eq!(totals, [12, 14, 16, 18]);
+    }
+
+    #[test]
+    fn tlcell_slice_of_cells() {
+        struct Marker;
+        let mut owner = TLCellOwner::<Marker>::new();
+        let cells: Box<TLCell<Marker, [u32]>> = Box::new(TLCell::new([1, 2, 3, 4]));
+        assert_eq!((cells.len(), cells.is_empty()), (4, false));
+        let elements = cells.as_slice_of_cells();
+        for element in elements {
+            *owner.rw(element) *= 10;
+        }
+        let (a, b) = owner.rw2(&elements[0], &elements[3]);
+        std::mem::swap(a, b);
+        assert_eq!(owner.ro(&*cells), &[40, 20, 30, 10]);
+        assert!(owner.try_rw_n((&elements[1], &elements[1])).is_err());
+        let whole = TLCell::from_slice_of_cells(&elements[1..3]);
+        assert_eq!(owner.ro(whole), &[20, 30]);
+        assert!(TLCell::from_slice_of_cells(&elements[..0]).is_empty());
+    }
+
+    #[test]
+    fn tlcell_as_ptr() {
+        struct Marker;
+        let mut owner = TLCellOwner::<Marker>::new();
+        let cell = TLCell::<Marker, _>::new(1u32);
+        let ptr = cell.as_ptr();
+        *owner.rw(&cell) += 1;
+        unsafe { *ptr += 10 };
+        assert_eq!(*owner.ro(&cell), 12);
+        let slice: Box<TLCell<Marker, [u8]>> = Box::new(TLCell::new([1, 2]));
+        assert_eq!(unsafe { (&*slice.as_ptr()).len() }, 2);
+    }
+
+    #[test]
+    fn tlcell_unsize() {
+        use std::rc::Rc;
+        struct Marker;
+        trait Draw {
+            fn draw(&self) -> String;
+            fn grow(&mut self);
+        }
+        struct Widget(u32);
+        impl Draw for Widget {
+            fn draw(&self) -> String {
+                format!("widget {}", self.0)
+            }
+            fn grow(&mut self) {
+                self.0 += 1;
+            }
+        }
+        let mut owner = TLCellOwner::<Marker>::new();
+        let rc: Rc<TLCell<Marker, dyn Draw>> = Rc::new(TLCell::new(Widget(1)));
+        let boxed: Box<TLCell<Marker, dyn Draw>> = Box::new(TLCell::new(Widget(10)));
+        owner.rw(&*rc).grow();
+        assert_eq!(owner.ro(&*rc).draw(), "widget 2");
+        assert_eq!(owner.ro(&*boxed).draw(), "widget 10");
+        let array: Rc<TLCell<Marker, [u8; 4]>> = Rc::new(TLCell::new([1, 2, 3, 4]));
+        let slice: Rc<TLCell<Marker, [u8]>> = array.clone();
+        owner.rw(&*slice)[0] = 10;
+        assert_eq!(owner.ro(&*array), &[10, 2, 3, 4]);
+        assert_eq!(slice.len(), 4);
+    }
+
+    #[test]
+    fn tlcell_for_each_rw() {
+        struct Marker;
+        let mut owner = TLCellOwner::<Marker>::new();
+        let cells: Vec<TLCell<Marker, u32>> = (0..4).map(TLCell::new).collect();
+        // Duplicates are visited once per appearance
+        let visits = cells.iter().chain(std::iter::once(&cells[1]));
+        owner.for_each_rw(visits, |v| *v += 10);
+        owner.for_each_rw(&cells, |v| *v *= 2);
+        let values: Vec<u32> = cells.iter().map(|c| owner.get(c)).collect();
+        assert_eq!(values, [20, 42, 24, 26]);
+        let rcs: Vec<std::rc::Rc<TLCell<Marker, u32>>> =
+            (0..3).map(|i| std::rc::Rc::new(TLCell::new(i))).collect();
+        owner.for_each_rw(rcs.iter().map(|rc| &**rc), |v| *v += 1);
+        assert_eq!(owner.get(&rcs[2]), 3);
+    }
+
+    #[test]
+    fn tlcell_transfer() {
+        use crate::{TCell, TCellOwner};
+        use std::cell::Cell;
+        use std::rc::Rc;
+        struct Marker;
+        struct Other;
+        struct Drops(Rc<Cell<u32>>, u32);
+        impl Drop for Drops {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+        let drops = Rc::new(Cell::new(0));
+        let mut owner = TLCellOwner::<Marker>::new();
+        let mut other = TLCellOwner::<Other>::new();
+        let mut towner = TCellOwner::<Other>::new();
+
+        // Swap across owners of the same kind
+        let src = TLCell::<Marker, _>::new(Drops(drops.clone(), 1));
+        let dst = TLCell::<Other, _>::new(Drops(drops.clone(), 2));
+        owner.swap_with(&src, other.rw(&dst));
+        assert_eq!((owner.ro(&src).1, other.ro(&dst).1), (2, 1));
+        assert_eq!(drops.get(), 0);
+
+        // Move into a different kind of cell with a filler
+        let tdst = TCell::<Other, _>::new(Drops(drops.clone(), 3));
+        let moved = owner.replace(&src, Drops(drops.clone(), 4));
+        assert_eq!(drops.get(), 0);
+        let old = std::mem::replace(towner.rw(&tdst), moved);
+        assert_eq!((old.1, towner.ro(&tdst).1), (3, 2));
+        drop(old);
+        assert_eq!(drops.get(), 1);
+
+        // Rebind a cell owned by value
+        let rebound: TLCell<Other, _> = src.rebind();
+        assert_eq!(other.ro(&rebound).1, 4);
+        assert_eq!(drops.get(), 1);
+        drop((rebound, dst, tdst));
+        assert_eq!(drops.get(), 4);
+
+        // Cloning variant
+        let src = TLCell::<Marker, _>::new(vec![1, 2, 3]);
+        let dst = TCell::<Other, Vec<i32>>::new(Vec::with_capacity(8));
+        towner.rw(&dst).clone_from(owner.ro(&src));
+        assert_eq!(towner.ro(&dst), &[1, 2, 3]);
+        assert_eq!(owner.ro(&src), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn tlcell_batch_cells() {
+        // Cells created in one thread can be moved to another, and
+        // borrowed there through that thread's own owner
+        struct Marker;
+        let owner = TLCellOwner::<Marker>::new();
+        let values = vec![String::from("a"); 100];
+        let ptr = values.as_ptr() as usize;
+        let cells = owner.cells_from_vec(values);
+        assert_eq!(
+            cells.as_ptr() as usize,
+            ptr,
+            "Expected allocation to be reused"
+        );
+        let array: [TLCell<Marker, usize>; 8] = owner.cell_array(|i| i * i);
+        let counts = owner.cells_from_iter(0..3u32);
+        let (cells, array, counts) = std::thread::spawn(move || {
+            let mut owner = TLCellOwner::<Marker>::new();
+            owner.for_each_rw(&cells, |s| s.push('b'));
+            owner.for_each_rw(&counts, |n| *n += 10);
+            *owner.rw(&array[7]) += 1;
+            (cells, array, counts)
+        })
+        .join()
+        .unwrap();
+        assert!(cells.iter().all(|c| owner.ro(c) == "ab"));
+        assert_eq!(owner.get(&array[7]), 50);
+        assert_eq!(owner.get(&counts[2]), 12);
+    }
+
+    #[test]
+    fn tlcell_guarded_owner() {
+        use crate::{GuardedCellOwner, GuardedCellReader};
+        fn total<O: GuardedCellOwner>(owner: &mut O, cells: &[O::Cell<u32>]) -> u32 {
+            let (mut first, last) = owner.rw2_guard(&cells[0], &cells[cells.len() - 1]);
+            *first += *last;
+            drop((first, last));
+            cells.iter().map(|c| *owner.ro_guard(c)).sum()
+        }
+        struct Marker;
+        let mut owner = TLCellOwner::<Marker>::new();
+        let cells = owner.cells_from_iter(1..=4);
+        assert_eq!(total(&mut owner, &cells), 14);
+        // The inherent methods are still used where they exist
+        let (a, b) = owner.rw2(&cells[0], &cells[1]);
+        std::mem::swap(a, b);
+        assert_eq!(owner.get(&cells[1]), 5);
+        GuardedCellOwner::set(&mut owner, &cells[3], 0);
+        assert_eq!(owner.ro(&cells[3]), &0);
+    }
+
+    #[test]
+    fn tlcell_debug_default() {
+        struct Marker;
+        #[derive(Debug)]
+        struct Context {
+            owner: TLCellOwner<Marker>,
+            cell: TLCell<Marker, Vec<u8>>,
+        }
+        #[derive(Debug, Default)]
+        struct OwnerOnly {
+            owner: TLCellOwner<Marker>,
+        }
+        #[derive(Debug, Default)]
+        struct Cells {
+            count: TLCell<Marker, u32>,
+            name: TLCell<Marker, String>,
+        }
+        let ctx = Context {
+            owner: OwnerOnly::default().owner,
+            cell: vec![1, 2, 3].into(),
+        };
+        assert_eq!(*ctx.owner.ro(&ctx.cell), vec![1, 2, 3]);
+        let text = format!("{:?}", ctx);
+        assert!(text.starts_with("Context { owner: TLCellOwner { marker: \""));
+        assert!(text.contains("Marker\" }, cell: TLCell { .. } }"));
+        let cells = Cells::default();
+        assert_eq!(*ctx.owner.ro(&cells.count), 0);
+        assert_eq!(*ctx.owner.ro(&cells.name), "");
+        assert_eq!(
+            format!("{:?}", cells),
+            "Cells { count: TLCell { .. }, name: TLCell { .. } }"
+        );
+    }
+
+    #[test]
+    fn tlcell_project() {
+        struct Marker;
+        struct Outer {
+            a: u32,
+            b: (u8, u64),
+        }
+        let mut owner = TLCellOwner::<Marker>::new();
+        let outer = TLCell::<Marker, _>::new(Outer { a: 1, b: (2, 3) });
+        let a = cell_project!(&outer, Outer, a);
+        let b = cell_project!(&outer, Outer, b.1);
+        let (a, b) = owner.rw2(a, b);
+        *a += 10;
+        *b += 30;
+        assert_eq!(owner.ro(&outer).a, 11);
+        assert_eq!(owner.ro(&outer).b, (2, 33));
+    }
+
+    #[test]
+    #[should_panic]
+    fn tlcell_project_rw2_outer() {
+        struct Marker;
+        struct Outer {
+            a: u32,
+            b: u32,
+        }
+        let mut owner = TLCellOwner::<Marker>::new();
+        let outer = TLCell::<Marker, _>::new(Outer { a: 1, b: 2 });
+        let b = cell_project!(&outer, Outer, b);
+        assert_eq!(owner.ro(&outer).a, 1);
+        owner.rw2(&outer, b); // Panic here
+    }
+
     #[test]
     fn tlcell_threads() {
         struct Marker;
@@ -0,0 +1,541 @@
+use core::ops::{Deref, DerefMut};
+
+use crate::tcell::private;
+
+/// Extension trait for cells containing an `Option`, such as the
+/// links of a linked list, implemented for both [`TCell`] and
+/// [`TLCell`].  This saves spelling out for example
+/// `owner.rw(&cell).take()` each time.
+///
+/// Note that `as_ref` is also the name of the `AsRef` method, so
+/// when the cell is held in an `Rc` or `Box`, dereference it first,
+/// as in `(*rc).as_ref(&owner)`.
+///
+/// [`TCell`]: struct.TCell.html
+/// [`TLCell`]: struct.TLCell.html
+pub trait OptionCellExt<T>: private::Sealed {
+    /// Type of the owner needed to access the cell
+    type Owner;
+
+    #[doc(hidden)]
+    fn ro_option<'a>(&'a self, owner: &'a Self::Owner) -> &'a Option<T>;
+
+    #[doc(hidden)]
+    fn rw_option<'a>(&'a self, owner: &'a mut Self::Owner) -> &'a mut Option<T>;
+
+    /// Test whether the cell contains a value.
+    #[inline]
+    fn is_some(&self, owner: &Self::Owner) -> bool {
+        self.ro_option(owner).is_some()
+    }
+
+    /// Borrow the value immutably, if there is one.
+    #[inline]
+    fn as_ref<'a>(&'a self, owner: &'a Self::Owner) -> Option<&'a T> {
+        self.ro_option(owner).as_ref()
+    }
+
+    /// Borrow the value mutably, first inserting the result of the
+    /// given closure if the cell is empty, as for
+    /// `Option::get_or_insert_with`.
+    #[inline]
+    fn get_or_insert_with<'a>(
+        &'a self,
+        owner: &'a mut Self::Owner,
+        f: impl FnOnce() -> T,
+    ) -> &'a mut T {
+        self.rw_option(owner).get_or_insert_with(f)
+    }
+
+    /// Take the value out of the cell, leaving it empty.
+    #[inline]
+    fn take_inner(&self, owner: &mut Self::Owner) -> Option<T> {
+        self.rw_option(owner).take()
+    }
+
+    /// Put a value into the cell, returning the old value if there
+    /// was one.
+    #[inline]
+    fn replace_inner(&self, owner: &mut Self::Owner, value: T) -> Option<T> {
+        self.rw_option(owner).replace(value)
+    }
+}
+
+/// Borrowing through guards, for writing code that is generic over
+/// the kind of owner.  Most owners hand out plain references, but an
+/// owner that tracks borrows at runtime, such as
+/// [`SharedTCellOwner`], hands out guards that release the borrow
+/// when dropped.  The associated `Ro` and `Rw` types cover both, so
+/// code written against these traits works with either.
+/// `GuardedCellReader` covers immutable access, and
+/// [`GuardedCellOwner`] adds mutable access.  They are implemented
+/// for [`TCellOwner`], [`TLCellOwner`], [`QCellOwner`],
+/// [`LCellOwner`] and [`SharedTCellOwner`], and may be implemented
+/// for other owners outside this crate.
+///
+/// For the owners that hand out plain references, `Ro` and `Rw` are
+/// `&T` and `&mut T`, and the inherent methods of the same name take
+/// priority, so existing code is unaffected.  Only sized contents are
+/// supported through these traits, so that slices of cells can be
+/// handled generically.  Use the inherent methods for unsized
+/// contents.
+///
+/// `&O` implements `GuardedCellReader` and `&mut O` implements both
+/// traits whenever `O` does, so a generic function may take the owner
+/// handle by value, and callers can pass `&owner` or `&mut owner` as
+/// many times as they like:
+///
+/// ```
+///# use qcell::{GuardedCellOwner, GuardedCellReader, TLCell, TLCellOwner};
+///# use std::cell::{Ref, RefCell, RefMut};
+/// fn sum<O: GuardedCellReader>(owner: O, cells: &[O::Cell<u32>]) -> u32 {
+///     cells.iter().map(|c| owner.get(c)).sum()
+/// }
+///
+/// fn bump<O: GuardedCellOwner>(mut owner: O, cells: &[O::Cell<u32>]) {
+///     owner.for_each_rw(cells, |v| *v += 1);
+/// }
+///
+/// fn bump_twice<O: GuardedCellOwner>(mut owner: O, cells: &[O::Cell<u32>]) -> u32 {
+///     bump(&mut owner, cells);
+///     bump(&mut owner, cells);
+///     assert_eq!(sum(&owner, cells), sum(&owner, cells));
+///     sum(owner, cells)
+/// }
+///
+/// struct Marker;
+/// let mut owner = TLCellOwner::<Marker>::new();
+/// let cells = [TLCell::new(1), TLCell::new(2)];
+/// assert_eq!(bump_twice(&mut owner, &cells), 7);
+/// assert_eq!(bump_twice(&mut owner, &cells), 11);
+///
+/// // An owner whose cells are `RefCell`s, and whose guards are
+/// // `Ref` and `RefMut`
+/// struct MockOwner;
+/// impl GuardedCellReader for MockOwner {
+///     type Cell<T> = RefCell<T>;
+///     type Ro<'a, T: 'a> = Ref<'a, T>;
+///     fn ro_guard<'a, T>(&'a self, cell: &'a RefCell<T>) -> Ref<'a, T> {
+///         cell.borrow()
+///     }
+/// }
+/// impl GuardedCellOwner for MockOwner {
+///     type Rw<'a, T: 'a> = RefMut<'a, T>;
+///     fn rw_guard<'a, T>(&'a mut self, cell: &'a RefCell<T>) -> RefMut<'a, T> {
+///         cell.borrow_mut()
+///     }
+///     fn rw2_guard<'a, T, U>(
+///         &'a mut self,
+///         cell1: &'a RefCell<T>,
+///         cell2: &'a RefCell<U>,
+///     ) -> (RefMut<'a, T>, RefMut<'a, U>) {
+///         (cell1.borrow_mut(), cell2.borrow_mut())
+///     }
+/// }
+/// let cells = [RefCell::new(1), RefCell::new(2)];
+/// let mut owner = MockOwner;
+/// assert_eq!(bump_twice(&mut owner, &cells), 7);
+/// assert_eq!(bump_twice(owner, &cells), 11);
+/// ```
+///
+/// [`GuardedCellOwner`]: trait.GuardedCellOwner.html
+/// [`TCellOwner`]: struct.TCellOwner.html
+/// [`TLCellOwner`]: struct.TLCellOwner.html
+/// [`QCellOwner`]: struct.QCellOwner.html
+/// [`LCellOwner`]: struct.LCellOwner.html
+/// [`SharedTCellOwner`]: struct.SharedTCellOwner.html
+pub trait GuardedCellReader {
+    /// Type of cell owned by this owner
+    type Cell<T>;
+
+    /// Immutable borrow of the contents of a cell
+    type Ro<'a, T: 'a>: Deref<Target = T>
+    where
+        Self: 'a;
+
+    /// Borrow contents of a cell immutably.
+    fn ro_guard<'a, T>(&'a self, cell: &'a Self::Cell<T>) -> Self::Ro<'a, T>;
+
+    /// Get a copy of the contents of a cell.
+    #[inline]
+    fn get<T: Copy>(&self, cell: &Self::Cell<T>) -> T {
+        *self.ro_guard(cell)
+    }
+}
+
+/// Mutable borrowing through guards.  See [`GuardedCellReader`].
+///
+/// ```
+///# use qcell::{GuardedCellOwner, TCellOwner, TLCell, TLCellOwner};
+/// fn bump<O: GuardedCellOwner>(owner: &mut O, cells: &[O::Cell<u32>]) {
+///     for cell in cells {
+///         *owner.rw_guard(cell) += 1;
+///     }
+/// }
+///
+/// struct Marker;
+/// let mut owner = TLCellOwner::<Marker>::new();
+/// let cells = [TLCell::new(1), TLCell::new(2)];
+/// bump(&mut owner, &cells);
+/// assert_eq!(owner.get(&cells[1]), 3);
+///
+/// let shared = TCellOwner::<Marker>::new_or_share();
+/// let cell = shared.borrow_mut().cell(5);
+/// bump(&mut shared.clone(), std::slice::from_ref(&cell));
+/// assert_eq!(*shared.ro(&cell), 6);
+/// ```
+///
+/// [`GuardedCellReader`]: trait.GuardedCellReader.html
+pub trait GuardedCellOwner: GuardedCellReader {
+    /// Mutable borrow of the contents of a cell
+    type Rw<'a, T: 'a>: DerefMut<Target = T>
+    where
+        Self: 'a;
+
+    /// Borrow contents of a cell mutably.
+    fn rw_guard<'a, T>(&'a mut self, cell: &'a Self::Cell<T>) -> Self::Rw<'a, T>;
+
+    /// Borrow contents of two cells mutably.  Panics if both
+    /// references are to the same cell.
+    fn rw2_guard<'a, T, U>(
+        &'a mut self,
+        cell1: &'a Self::Cell<T>,
+        cell2: &'a Self::Cell<U>,
+    ) -> (Self::Rw<'a, T>, Self::Rw<'a, U>);
+
+    /// Set the contents of a cell, dropping the old value.
+    #[inline]
+    fn set<T>(&mut self, cell: &Self::Cell<T>, value: T) {
+        *self.rw_guard(cell) = value;
+    }
+
+    /// Replace the contents of a cell, returning the old value.
+    #[inline]
+    fn replace<T>(&mut self, cell: &Self::Cell<T>, value: T) -> T {
+        core::mem::replace(&mut *self.rw_guard(cell), value)
+    }
+
+    /// Swap the contents of two cells.  If both references are to
+    /// the same cell, this does nothing.
+    #[inline]
+    fn swap<T>(&mut self, cell1: &Self::Cell<T>, cell2: &Self::Cell<T>) {
+        if !core::ptr::eq(cell1, cell2) {
+            let (mut v1, mut v2) = self.rw2_guard(cell1, cell2);
+            core::mem::swap(&mut *v1, &mut *v2);
+        }
+    }
+
+    /// Pass a mutable reference to the contents of each cell returned
+    /// by an iterator to the given closure in turn.  Only one cell is
+    /// borrowed at a time.
+    #[inline]
+    fn for_each_rw<'a, T, I>(&mut self, cells: I, mut f: impl FnMut(&mut T))
+    where
+        I: IntoIterator<Item = &'a Self::Cell<T>>,
+        Self::Cell<T>: 'a,
+        T: 'a,
+    {
+        for cell in cells {
+            f(&mut *self.rw_guard(cell));
+        }
+    }
+}
+
+/// Borrow one cell mutably from each of two owners in a single call,
+/// for code that uses more than one owner, for example a `TLCell`
+/// family for one part of the state and a `TCell` family for
+/// another.  No aliasing check is needed, since both owners are
+/// borrowed mutably and so must be different owners, and a cell only
+/// belongs to one owner.  To borrow two cells of the same owner, use
+/// `rw2` on the owner.
+///
+/// ```
+///# use qcell::{rw_each_owner, TCell, TCellOwner, TLCell, TLCellOwner};
+/// struct Physics;
+/// struct Render;
+/// let mut physics = TLCellOwner::<Physics>::new();
+/// let mut render = TCellOwner::<Render>::new();
+/// let position = TLCell::<Physics, _>::new(1.5f32);
+/// let sprite_x = TCell::<Render, _>::new(0.0f32);
+///
+/// let (pos, x) = rw_each_owner((&mut physics, &position), (&mut render, &sprite_x));
+/// *pos += 1.0;
+/// *x = *pos * 2.0;
+/// assert_eq!(render.get(&sprite_x), 5.0);
+/// ```
+#[inline]
+pub fn rw_each_owner<'a, A, B, OA, OB>(
+    (owner_a, cell_a): (&'a mut OA, &'a OA::Cell<A>),
+    (owner_b, cell_b): (&'a mut OB, &'a OB::Cell<B>),
+) -> (OA::Rw<'a, A>, OB::Rw<'a, B>)
+where
+    OA: GuardedCellOwner,
+    OB: GuardedCellOwner,
+{
+    (owner_a.rw_guard(cell_a), owner_b.rw_guard(cell_b))
+}
+
+/// Borrow one cell mutably from each of three owners in a single
+/// call.  See [`rw_each_owner`].
+///
+/// [`rw_each_owner`]: fn.rw_each_owner.html
+#[inline]
+pub fn rw_each_owner3<'a, A, B, C, OA, OB, OC>(
+    (owner_a, cell_a): (&'a mut OA, &'a OA::Cell<A>),
+    (owner_b, cell_b): (&'a mut OB, &'a OB::Cell<B>),
+    (owner_c, cell_c): (&'a mut OC, &'a OC::Cell<C>),
+) -> (OA::Rw<'a, A>, OB::Rw<'a, B>, OC::Rw<'a, C>)
+where
+    OA: GuardedCellOwner,
+    OB: GuardedCellOwner,
+    OC: GuardedCellOwner,
+{
+    (
+        owner_a.rw_guard(cell_a),
+        owner_b.rw_guard(cell_b),
+        owner_c.rw_guard(cell_c),
+    )
+}
+
+/// Borrow one cell immutably from each of two owners in a single
+/// call.  This is the read-only counterpart of [`rw_each_owner`].
+///
+/// [`rw_each_owner`]: fn.rw_each_owner.html
+#[inline]
+pub fn ro_each_owner<'a, A, B, OA, OB>(
+    (owner_a, cell_a): (&'a OA, &'a OA::Cell<A>),
+    (owner_b, cell_b): (&'a OB, &'a OB::Cell<B>),
+) -> (OA::Ro<'a, A>, OB::Ro<'a, B>)
+where
+    OA: GuardedCellReader,
+    OB: GuardedCellReader,
+{
+    (owner_a.ro_guard(cell_a), owner_b.ro_guard(cell_b))
+}
+
+impl<O: GuardedCellReader + ?Sized> GuardedCellReader for &O {
+    type Cell<T> = O::Cell<T>;
+    type Ro<'a, T: 'a>
+        = O::Ro<'a, T>
+    where
+        Self: 'a;
+
+    #[inline]
+    fn ro_guard<'a, T>(&'a self, cell: &'a O::Cell<T>) -> O::Ro<'a, T> {
+        (**self).ro_guard(cell)
+    }
+}
+
+impl<O: GuardedCellReader + ?Sized> GuardedCellReader for &mut O {
+    type Cell<T> = O::Cell<T>;
+    type Ro<'a, T: 'a>
+        = O::Ro<'a, T>
+    where
+        Self: 'a;
+
+    #[inline]
+    fn ro_guard<'a, T>(&'a self, cell: &'a O::Cell<T>) -> O::Ro<'a, T> {
+        (**self).ro_guard(cell)
+    }
+}
+
+impl<O: GuardedCellOwner + ?Sized> GuardedCellOwner for &mut O {
+    type Rw<'a, T: 'a>
+        = O::Rw<'a, T>
+    where
+        Self: 'a;
+
+    #[inline]
+    fn rw_guard<'a, T>(&'a mut self, cell: &'a O::Cell<T>) -> O::Rw<'a, T> {
+        (**self).rw_guard(cell)
+    }
+
+    #[inline]
+    fn rw2_guard<'a, T, U>(
+        &'a mut self,
+        cell1: &'a O::Cell<T>,
+        cell2: &'a O::Cell<U>,
+    ) -> (O::Rw<'a, T>, O::Rw<'a, U>) {
+        (**self).rw2_guard(cell1, cell2)
+    }
+}
+
+/// Extension trait for cells held in an `Rc` or `Arc`, implemented
+/// for `Rc` and `Arc` of [`TCell`], [`TLCell`] and [`QCell`], and for
+/// references to those.  This allows `rc.ro(&owner)` in place of
+/// `owner.ro(&rc)`, which reads better in chains such as
+/// `node.next.ro(&owner)`.
+///
+/// The returned reference borrows the `Rc` it was called on, exactly
+/// as for `owner.ro(&rc)`.  So the `Rc` must be kept in a binding for
+/// as long as the reference is used.  A common mistake with the
+/// owner methods is to pass a temporary, such as
+/// `owner.ro(&rc.clone())`, which is dropped at the end of the
+/// statement, so the reference can't be kept.  Calling the method on
+/// the `Rc` itself borrows the binding, with no clone and no
+/// temporary:
+///
+/// ```
+///# use qcell::{RcCellExt, TCell, TCellOwner};
+///# use std::rc::Rc;
+///# struct Marker;
+/// let mut owner = TCellOwner::<Marker>::new();
+/// let rc = Rc::new(TCell::<Marker, _>::new(String::from("text")));
+/// let text = rc.ro(&owner);
+/// assert_eq!(text, "text");
+/// rc.rw(&mut owner).push('!');
+/// assert_eq!(rc.clone_contents(&owner), "text!");
+///
+/// let count = Rc::new(TCell::<Marker, _>::new(1));
+/// assert_eq!(count.get(&owner), 1);
+/// ```
+///
+/// [`TCell`]: struct.TCell.html
+/// [`TLCell`]: struct.TLCell.html
+/// [`QCell`]: struct.QCell.html
+#[cfg(feature = "alloc")]
+pub trait RcCellExt: private::Sealed {
+    /// Type of the owner needed to access the cell
+    type Owner;
+
+    /// Type contained in the cell
+    type Value: ?Sized;
+
+    /// Borrow the contents of the cell immutably.
+    fn ro<'a>(&'a self, owner: &'a Self::Owner) -> &'a Self::Value;
+
+    /// Borrow the contents of the cell mutably.
+    #[allow(clippy::mut_from_ref)]
+    fn rw<'a>(&'a self, owner: &'a mut Self::Owner) -> &'a mut Self::Value;
+
+    /// Get a copy of the contents of the cell.
+    #[inline]
+    fn get(&self, owner: &Self::Owner) -> Self::Value
+    where
+        Self::Value: Copy,
+    {
+        *self.ro(owner)
+    }
+
+    /// Get a clone of the contents of the cell.
+    #[inline]
+    fn clone_contents(&self, owner: &Self::Owner) -> Self::Value
+    where
+        Self::Value: Clone,
+    {
+        self.ro(owner).clone()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<R: RcCellExt + ?Sized> private::Sealed for &R {}
+
+#[cfg(feature = "alloc")]
+impl<R: RcCellExt + ?Sized> RcCellExt for &R {
+    type Owner = R::Owner;
+    type Value = R::Value;
+
+    #[inline]
+    fn ro<'a>(&'a self, owner: &'a R::Owner) -> &'a R::Value {
+        (**self).ro(owner)
+    }
+
+    #[inline]
+    fn rw<'a>(&'a self, owner: &'a mut R::Owner) -> &'a mut R::Value {
+        (**self).rw(owner)
+    }
+}
+
+// Implement `RcCellExt` for `Rc` and `Arc` of a cell type, given the
+// generic parameters, the cell and owner types, and the contents type
+#[cfg(feature = "alloc")]
+macro_rules! rc_cell_ext {
+    ([$($g:tt)*] $cell:ty, $owner:ty, $value:ty) => {
+        crate::traits::rc_cell_ext!(@impl [$($g)*] alloc::rc::Rc<$cell>, $owner, $value);
+        crate::traits::rc_cell_ext!(@impl [$($g)*] alloc::sync::Arc<$cell>, $owner, $value);
+    };
+    (@impl [$($g:tt)*] $rc:ty, $owner:ty, $value:ty) => {
+        impl<$($g)*> crate::tcell::private::Sealed for $rc {}
+
+        impl<$($g)*> crate::RcCellExt for $rc {
+            type Owner = $owner;
+            type Value = $value;
+
+            #[inline]
+            fn ro<'a>(&'a self, owner: &'a $owner) -> &'a $value {
+                owner.ro(self)
+            }
+
+            #[inline]
+            fn rw<'a>(&'a self, owner: &'a mut $owner) -> &'a mut $value {
+                owner.rw(self)
+            }
+        }
+    };
+}
+#[cfg(feature = "alloc")]
+pub(crate) use rc_cell_ext;
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[test]
+    fn rc_cell_ext() {
+        use crate::{QCellOwner, RcCellExt, TCell, TCellOwner, TLCell, TLCellOwner};
+        struct Marker;
+        let mut owner = TCellOwner::<Marker>::new();
+        let rc = Rc::new(TCell::<Marker, _>::new(vec![1]));
+        rc.rw(&mut owner).push(2);
+        assert_eq!(rc.ro(&owner), &[1, 2]);
+        let rc_ref = &rc;
+        assert_eq!(rc_ref.clone_contents(&owner), vec![1, 2]);
+        let arc = Arc::new(TCell::<Marker, _>::new(3u8));
+        *arc.rw(&mut owner) += 1;
+        let arc_ref: &Arc<_> = &arc;
+        assert_eq!(RcCellExt::get(&arc_ref, &owner), 4);
+
+        let mut tlowner = TLCellOwner::<Marker>::new();
+        let tlrc = Rc::new(TLCell::<Marker, _>::new(5));
+        *tlrc.rw(&mut tlowner) *= 2;
+        assert_eq!(tlrc.get(&tlowner), 10);
+
+        let mut qowner = QCellOwner::new();
+        let qarc = Arc::new(qowner.cell(String::from("q")));
+        qarc.rw(&mut qowner).push('!');
+        assert_eq!(qarc.clone_contents(&qowner), "q!");
+    }
+
+    #[test]
+    fn each_owner() {
+        use crate::{
+            ro_each_owner, rw_each_owner, rw_each_owner3, QCellOwner, TCell, TCellOwner, TLCell,
+            TLCellOwner,
+        };
+        struct Marker;
+        let mut towner = TCellOwner::<Marker>::new();
+        let mut tlowner = TLCellOwner::<Marker>::new();
+        let mut qowner = QCellOwner::new();
+        let tc = TCell::<Marker, _>::new(1);
+        let tlc = TLCell::<Marker, _>::new(String::from("a"));
+        let qc = qowner.cell(vec![1u8]);
+
+        let (n, text) = rw_each_owner((&mut towner, &tc), (&mut tlowner, &tlc));
+        *n += 1;
+        text.push('b');
+        let (n, text, list) =
+            rw_each_owner3((&mut towner, &tc), (&mut tlowner, &tlc), (&mut qowner, &qc));
+        list.push(*n);
+        text.push_str(&n.to_string());
+        let (text, list) = ro_each_owner((&tlowner, &tlc), (&qowner, &qc));
+        assert_eq!((text.as_str(), list.as_slice()), ("ab2", &[1, 2][..]));
+
+        // Two distinct owners of the same type are allowed too
+        let mut qowner2 = QCellOwner::new();
+        let qc2 = qowner2.cell(3u8);
+        let (list, n) = rw_each_owner((&mut qowner, &qc), (&mut qowner2, &qc2));
+        list.push(*n);
+        assert_eq!(qowner.ro(&qc), &[1, 2, 3]);
+    }
+}
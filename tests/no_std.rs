@@ -0,0 +1,50 @@
+// Check that the cell types which don't need `std` can be used from a
+// `no_std` crate.  To check that they also build without `std`, run:
+//
+//     cargo test --no-default-features --test no_std
+//
+// `std` is only linked here for the test harness.
+
+#![no_std]
+
+extern crate std;
+
+use qcell::{tcell_marker, LCell, LCellOwner, TCell, TCellOwner, TLCell, TLCellOwner};
+
+#[test]
+fn tcell_no_std() {
+    tcell_marker!(
+        struct Marker;
+    );
+    let mut owner = TCellOwner::<Marker>::new_fast();
+    let c1 = TCell::<Marker, _>::new(100u32);
+    let c2 = TCell::<Marker, _>::new([1u8, 2, 3]);
+    *owner.rw(&c1) += 1;
+    owner.rw(&c2)[0] += 10;
+    let (r1, r2) = owner.rw2(&c1, &c2);
+    *r1 += u32::from(r2[0]);
+    assert_eq!(*owner.ro(&c1), 112);
+    assert!(TCellOwner::<Marker>::try_new_fast().is_err());
+    drop(owner);
+    let owner = TCellOwner::<Marker>::new_fast();
+    assert_eq!(*owner.ro(&c2), [11, 2, 3]);
+}
+
+#[test]
+fn tlcell_no_std() {
+    struct Marker;
+    // Safe because no other owner for `Marker` is ever created
+    let mut owner = unsafe { TLCellOwner::<Marker>::new_unchecked() };
+    let c1 = TLCell::<Marker, _>::new(100u32);
+    *owner.rw(&c1) += 1;
+    assert_eq!(*owner.ro(&c1), 101);
+}
+
+#[test]
+fn lcell_no_std() {
+    LCellOwner::scope(|mut owner| {
+        let c1 = LCell::new(100u32);
+        *owner.rw(&c1) += 1;
+        assert_eq!(*owner.ro(&c1), 101);
+    });
+}
@@ -0,0 +1,53 @@
+// Check that the calls which only need an allocator can be used from a
+// `no_std` crate.  To check that they also build without `std`, run:
+//
+//     cargo test --no-default-features --features alloc --test no_std_alloc
+//
+// `std` is only linked here for the test harness.
+
+#![no_std]
+
+extern crate alloc;
+extern crate std;
+
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::vec;
+use alloc::vec::Vec;
+use qcell::{tcell_marker, RcCellExt, TCell, TCellOwner, TLCell, TLCellOwner};
+
+#[test]
+fn tcell_no_std_alloc() {
+    tcell_marker!(
+        struct Marker;
+    );
+    let mut owner = TCellOwner::<Marker>::new_fast();
+    let cells = owner.cells_from_vec(vec![3u32, 1, 2]);
+    let mut refs: Vec<&TCell<Marker, u32>> = cells.iter().collect();
+    owner.sort_cells_by_key(&mut refs, |v| *v);
+    for (i, v) in owner.rw_slice(&refs).into_iter().enumerate() {
+        *v += 10 * i as u32;
+    }
+    assert_eq!(*owner.ro(&cells[0]), 23);
+    assert!(owner.try_rw_slice(&[&cells[1], &cells[1]]).is_err());
+
+    let boxed: Box<TCell<Marker, [u8]>> = TCell::from_boxed(Box::new([1u8, 2]) as Box<[u8]>);
+    owner.rw(&*boxed)[1] = 5;
+    assert_eq!(owner.ro(&*boxed), &[1, 5]);
+
+    let rc = Rc::new(owner.cell(7u32));
+    *rc.rw(&mut owner) += 1;
+    assert_eq!(rc.get(&owner), 8);
+}
+
+#[test]
+fn tlcell_no_std_alloc() {
+    struct Marker;
+    // Safe because no other owner for `Marker` is ever created
+    let mut owner = unsafe { TLCellOwner::<Marker>::new_unchecked() };
+    let cells = TLCell::<Marker, _>::wrap_vec(vec![1u32, 2]);
+    *owner.rw(&cells[1]) += 1;
+    assert_eq!(*owner.ro(&cells[1]), 3);
+    let rc = Rc::new(TLCell::<Marker, _>::new(5u32));
+    assert_eq!(rc.get(&owner), 5);
+}